@@ -1,5 +1,6 @@
 // Import Anchor framework and required types
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::get_stack_height;
 use puppet::program::Puppet;
 use puppet::{self, PuppetData};
 
@@ -30,13 +31,31 @@ pub mod anchor_hello_world {
     }
 
     // PDA Feature: Initialize user statistics account using Program Derived Address
-    pub fn initialize_user_stats(ctx: Context<InitializeUserStats>, name: String) -> Result<()> {
+    pub fn initialize_user_stats(
+        ctx: Context<InitializeUserStats>,
+        name: String,
+        max_points: Option<u64>,
+        max_level: Option<u64>,
+    ) -> Result<()> {
+        let max_points = max_points.unwrap_or(UserStats::DEFAULT_MAX_POINTS);
+        let max_level = max_level.unwrap_or(UserStats::DEFAULT_MAX_LEVEL);
+        require!(max_points > 0 && max_level > 0, MyError::InvalidCap);
+
         let user_stats = &mut ctx.accounts.user_stats;
         user_stats.name = name.clone();
         user_stats.level = 1;                                    // Start at level 1
         user_stats.points = 0;                                   // Start with 0 points
         user_stats.authority = ctx.accounts.authority.key();     // Set the owner
         user_stats.bump = ctx.bumps.user_stats;                  // Store bump for future use
+        user_stats.max_points = max_points;                      // Points never exceed this
+        user_stats.max_level = max_level;                        // Level never exceeds this
+
+        // Optional name reservation: `init` fails if the name PDA already exists
+        if let Some(name_registry) = &mut ctx.accounts.name_registry {
+            name_registry.authority = ctx.accounts.authority.key();
+            name_registry.bump = ctx.bumps.name_registry.unwrap();
+            msg!("Reserved name: {}", name);
+        }
 
         msg!("Initialized user stats for: {}", name);
         Ok(())
@@ -45,25 +64,46 @@ pub mod anchor_hello_world {
     // PDA Feature: Update user statistics (add points and recalculate level)
     pub fn update_user_stats(ctx: Context<UpdateUserStats>, points: u64) -> Result<()> {
         let user_stats = &mut ctx.accounts.user_stats;
-        user_stats.points += points;                             // Add new points
+        require!(user_stats.points < user_stats.max_points, MyError::CapReached);
+
+        // Add new points, stopping at the cap; overflow is an error rather than a wrap
+        user_stats.points = user_stats
+            .points
+            .checked_add(points)
+            .ok_or(MyError::PointsOverflow)?
+            .min(user_stats.max_points);
 
-        // Calculate new level based on points (every 100 points = 1 level)
+        // Calculate new level based on points (every 100 points = 1 level), up to the level cap
         let old_level = user_stats.level;
-        user_stats.level = (user_stats.points / 100) + 1;
+        user_stats.level = user_stats
+            .points
+            .checked_div(UserStats::POINTS_PER_LEVEL)
+            .and_then(|level| level.checked_add(1))
+            .ok_or(MyError::PointsOverflow)?
+            .min(user_stats.max_level);
 
         msg!("Updated user {} points: +{}, total: {}, level: {} -> {}",
              user_stats.name, points, user_stats.points, old_level, user_stats.level);
         Ok(())
     }
 
+    // PDA Feature: Resolve a reserved name to the authority that owns it
+    pub fn find_user_by_name(ctx: Context<FindUserByName>, name: String) -> Result<Pubkey> {
+        let authority = ctx.accounts.name_registry.authority;
+        msg!("Name {} is owned by {}", name, authority);
+        Ok(authority)
+    }
+
     // CPI Feature: Call puppet program through Cross-Program Invocation
     pub fn pull_strings(ctx: Context<PullStrings>, data: u64) -> Result<()> {
         // Get the puppet program account info
         let cpi_program = ctx.accounts.puppet_program.to_account_info();
 
         // Prepare the accounts needed for the puppet program's set_data instruction
+        // The authority signed our transaction, and that signature carries through the CPI
         let cpi_accounts = puppet::cpi::accounts::SetData {
             puppet: ctx.accounts.puppet.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
         };
 
         // Create CPI context (like preparing a phone call to another program)
@@ -84,6 +124,7 @@ pub mod anchor_hello_world {
         // Prepare the accounts needed for the puppet program's set_data instruction
         let cpi_accounts = puppet::cpi::accounts::SetData {
             puppet: ctx.accounts.puppet.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
         };
 
         // Create PDA signature seeds (this allows our program to "sign" on behalf of the PDA)
@@ -101,6 +142,28 @@ pub mod anchor_hello_world {
         msg!("Called puppet program via CPI with PDA signer");
         Ok(())
     }
+
+    // CPI Feature: Nested CPI - puppet makes a CPI of its own while handling ours
+    //
+    // The runtime allows at most 4 levels of CPI below the top-level instruction
+    // (stack height 5). This chain uses two: this program (height 1) -> puppet
+    // (height 2) -> system program (height 3).
+    pub fn pull_strings_nested(ctx: Context<PullStringsNested>, data: u64, lamports: u64) -> Result<()> {
+        msg!("anchor-hello-world running at stack height {}", get_stack_height());
+
+        let cpi_program = ctx.accounts.puppet_program.to_account_info();
+        let cpi_accounts = puppet::cpi::accounts::SetDataAndTip {
+            puppet: ctx.accounts.puppet.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            recipient: ctx.accounts.recipient.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        puppet::cpi::set_data_and_tip(cpi_ctx, data, lamports)?;
+
+        msg!("Nested CPI chain completed");
+        Ok(())
+    }
 }
 
 // Basic data account structure - stores simple data
@@ -119,6 +182,23 @@ pub struct UserStats {
     pub points: u64,         // User's accumulated points
     pub authority: Pubkey,   // The account that owns this user stats
     pub bump: u8,            // PDA bump value for address generation
+    pub max_points: u64,     // Points cap, set at initialization
+    pub max_level: u64,      // Level cap, set at initialization
+}
+
+impl UserStats {
+    pub const DEFAULT_MAX_POINTS: u64 = 1_000_000;
+    pub const DEFAULT_MAX_LEVEL: u64 = 100;
+    pub const POINTS_PER_LEVEL: u64 = 100;
+    pub const MAX_NAME_LEN: usize = 32;  // The name is a PDA seed, which can't exceed 32 bytes
+}
+
+// Name reservation (PDA example) - existence of this account means the name is taken
+#[account]
+#[derive(Default)]
+pub struct NameRegistry {
+    pub authority: Pubkey,   // The account that reserved the name
+    pub bump: u8,            // PDA bump value for address generation
 }
 
 // initialize account instruction
@@ -149,11 +229,22 @@ pub struct InitializeUserStats<'info> {
     #[account(
         init,                                                 // Create new account
         payer = authority,                                    // Who pays for account creation
-        space = 8 + 32 + 8 + 8 + 32 + 1 + 4 + name.len(),   // Calculate required space
+        space = 8 + 32 + 8 + 8 + 32 + 1 + 8 + 8 + 4 + name.len(), // Calculate required space
         seeds = [b"user-stats", authority.key().as_ref()],   // PDA seeds for deterministic address
-        bump                                                  // Auto-find bump value
+        bump,                                                 // Auto-find bump value
+        // Checked here rather than in the handler: it has to run before name_registry's
+        // seeds are derived, and a seed over 32 bytes panics instead of erroring
+        constraint = name.len() <= UserStats::MAX_NAME_LEN @ MyError::NameTooLong
     )]
     pub user_stats: Account<'info, UserStats>,
+    #[account(
+        init,                                                 // Creating it reserves the name
+        payer = authority,                                    // Who pays for account creation
+        space = 8 + 32 + 1,                                   // discriminator + authority + bump
+        seeds = [b"name", name.as_bytes()],                   // One PDA per name (max MAX_NAME_LEN bytes)
+        bump                                                  // Auto-find bump value
+    )]
+    pub name_registry: Option<Account<'info, NameRegistry>>, // Optional: omit to skip the uniqueness check
     #[account(mut)]                                          // Mutable (for paying fees)
     pub authority: Signer<'info>,                            // Must sign the transaction
     pub system_program: Program<'info, System>,              // Required for account creation
@@ -172,18 +263,33 @@ pub struct UpdateUserStats<'info> {
     pub authority: Signer<'info>,                           // Must be the owner
 }
 
+// Account validation structure for name lookup
+#[derive(Accounts)]
+#[instruction(name: String)] // Instruction parameter used in seeds calculation
+pub struct FindUserByName<'info> {
+    #[account(
+        seeds = [b"name", name.as_bytes()],                  // Same seeds as the reservation
+        bump = name_registry.bump                            // Use stored bump value
+    )]
+    pub name_registry: Account<'info, NameRegistry>,         // Fails if the name was never reserved
+}
+
 // Account validation structure for basic CPI call
 #[derive(Accounts)]
 pub struct PullStrings<'info> {
     #[account(mut)]                              // The puppet account we want to modify
     pub puppet: Account<'info, PuppetData>,
+    pub authority: Signer<'info>,                // The puppet's authority (checked by the puppet)
     pub puppet_program: Program<'info, Puppet>,  // The puppet program we're calling
 }
 
 // Account validation structure for CPI call with PDA signer
 #[derive(Accounts)]
 pub struct PullStringsWithPda<'info> {
-    #[account(mut)]                              // The puppet account we want to modify
+    #[account(
+        mut,                                     // The puppet account we want to modify
+        constraint = puppet.authority == authority.key() @ MyError::PuppetAuthorityMismatch
+    )]
     pub puppet: Account<'info, PuppetData>,
     pub puppet_program: Program<'info, Puppet>,  // The puppet program we're calling
     #[account(
@@ -194,8 +300,31 @@ pub struct PullStringsWithPda<'info> {
     pub authority: UncheckedAccount<'info>,      // PDA that will "sign" the CPI call
 }
 
+// Account validation structure for nested CPI call
+#[derive(Accounts)]
+pub struct PullStringsNested<'info> {
+    #[account(mut)]                              // The puppet account we want to modify
+    pub puppet: Account<'info, PuppetData>,
+    #[account(mut)]                              // Pays the tip inside the puppet's own CPI
+    pub payer: Signer<'info>,
+    #[account(mut)]                              // Receives the tip
+    pub recipient: SystemAccount<'info>,
+    pub puppet_program: Program<'info, Puppet>,  // The puppet program we're calling
+    pub system_program: Program<'info, System>,  // Called by the puppet, not by us
+}
+
 #[error_code]
 pub enum MyError {
     #[msg("Data value must be less than 100")]
     DataTooLarge,
+    #[msg("User stats are already at the points cap")]
+    CapReached,
+    #[msg("Caps must be greater than zero")]
+    InvalidCap,
+    #[msg("Points overflow")]
+    PointsOverflow,
+    #[msg("Authority PDA is not the puppet's authority")]
+    PuppetAuthorityMismatch,
+    #[msg("Name must be at most 32 bytes")]
+    NameTooLong,
 }
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::get_stack_height;
+use anchor_lang::solana_program::log::sol_log_data;
+use anchor_lang::system_program;
 
 declare_id!("AGnEXNeEkkUk6yxKgUr19Q4CVTnHaMDo4RUGSq5vS6kf");
 
@@ -6,10 +9,19 @@ declare_id!("AGnEXNeEkkUk6yxKgUr19Q4CVTnHaMDo4RUGSq5vS6kf");
 pub mod puppet {
     use super::*;
 
+    // The creator starts out as the authority; hand it to someone else (e.g. a
+    // caller program's PDA) with set_authority
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let puppet_account = &mut ctx.accounts.puppet;
         puppet_account.data = 0;
-        msg!("Puppet account initialized with data: 0");
+        puppet_account.authority = ctx.accounts.user.key();
+        msg!("Puppet account initialized with data: 0, authority: {}", puppet_account.authority);
+        Ok(())
+    }
+
+    pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.puppet.authority = new_authority;
+        msg!("Puppet authority set to: {}", new_authority);
         Ok(())
     }
 
@@ -22,6 +34,43 @@ pub mod puppet {
         Ok(data)
     }
 
+    // Like set_data, but also logs the change so callers can see the old value too;
+    // return data only carries the new one. See DataChanged for the log layout.
+    pub fn set_data_logged(ctx: Context<SetData>, data: u64) -> Result<u64> {
+        let puppet_account = &mut ctx.accounts.puppet;
+        let change = DataChanged {
+            old: puppet_account.data,
+            new: data,
+        };
+        puppet_account.data = data;
+
+        sol_log_data(&[DATA_CHANGED_LOG_TAG, &change.try_to_vec()?]);
+
+        msg!("Puppet data changed from {} to {}", change.old, change.new);
+        Ok(data)
+    }
+
+    // Set the data, then make a CPI of our own (a system transfer), so a caller reaching
+    // this over CPI builds a two-level chain: caller -> puppet -> system program
+    pub fn set_data_and_tip(ctx: Context<SetDataAndTip>, data: u64, lamports: u64) -> Result<()> {
+        msg!("Puppet running at stack height {}", get_stack_height());
+
+        ctx.accounts.puppet.data = data;
+
+        // `payer` signed the outer transaction; that signature carries through each CPI
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, lamports)?;
+
+        msg!("Puppet data set to: {}, tipped {} lamports", data, lamports);
+        Ok(())
+    }
+
     pub fn get_data(ctx: Context<GetData>) -> Result<u64> {
         let puppet_account = &ctx.accounts.puppet;
         msg!("Puppet data is: {}", puppet_account.data);
@@ -33,6 +82,19 @@ pub mod puppet {
 #[derive(Default)]
 pub struct PuppetData {
     pub data: u64,
+    pub authority: Pubkey,  // Who may pull this puppet's strings (must sign set_data)
+}
+
+// Tag logged as the first field of the data-changed program data log
+pub const DATA_CHANGED_LOG_TAG: &[u8] = b"data_changed";
+
+// Logged by set_data_logged as "Program data: <tag> <change>", both fields base64:
+// the tag is DATA_CHANGED_LOG_TAG, the change is this struct Borsh-encoded
+// (16 bytes: old then new, little-endian u64s)
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct DataChanged {
+    pub old: u64,
+    pub new: u64,
 }
 
 #[derive(Accounts)]
@@ -40,7 +102,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 8  // 8 bytes for discriminator + 8 bytes for data
+        space = 8 + 8 + 32  // 8 bytes for discriminator + 8 bytes for data + 32 bytes for authority
     )]
     pub puppet: Account<'info, PuppetData>,
     #[account(mut)]
@@ -48,10 +110,29 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub puppet: Account<'info, PuppetData>,
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SetData<'info> {
+    #[account(mut, has_one = authority)]
+    pub puppet: Account<'info, PuppetData>,
+    pub authority: Signer<'info>,  // The puppet's authority; a PDA signs over CPI
+}
+
+#[derive(Accounts)]
+pub struct SetDataAndTip<'info> {
     #[account(mut)]
     pub puppet: Account<'info, PuppetData>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
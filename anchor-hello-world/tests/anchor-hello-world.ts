@@ -95,7 +95,7 @@ describe("anchor-hello-world-complete", () => {
 
     // Initialize user statistics
     await program.methods
-      .initializeUserStats("Alice")
+      .initializeUserStats("Alice", null, null)
       .accounts({
         userStats: userStatsPda,
         authority: authority.publicKey,
@@ -140,6 +140,217 @@ describe("anchor-hello-world-complete", () => {
     expect(userStats.level.toNumber()).to.equal(2); // 150 / 100 + 1 = 2
   });
 
+  it("Name registry uniqueness test", async () => {
+    console.log("=== Testing Name Registry ===");
+
+    const name = `Bob-${Date.now() % 100000}`;
+    const [nameRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("name"), Buffer.from(name)],
+      program.programId
+    );
+
+    const users = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const user of users) {
+      const signature = await program.provider.connection.requestAirdrop(
+        user.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await program.provider.connection.confirmTransaction(signature);
+    }
+
+    const initializeWithName = (user: anchor.web3.Keypair) => {
+      const [userStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user-stats"), user.publicKey.toBuffer()],
+        program.programId
+      );
+      return program.methods
+        .initializeUserStats(name, null, null)
+        .accounts({
+          userStats: userStatsPda,
+          nameRegistry: nameRegistryPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([user])
+        .rpc();
+    };
+
+    // First user reserves the name
+    await initializeWithName(users[0]);
+
+    const registry = await program.account.nameRegistry.fetch(nameRegistryPda);
+    expect(registry.authority.toString()).to.equal(users[0].publicKey.toString());
+
+    // The name resolves to the first user's authority
+    const owner = await program.methods
+      .findUserByName(name)
+      .accounts({
+        nameRegistry: nameRegistryPda,
+      } as any)
+      .view();
+    console.log(`Name ${name} resolves to:`, owner.toString());
+    expect(owner.toString()).to.equal(users[0].publicKey.toString());
+
+    // Second user with the same name must fail because the registry PDA already exists
+    try {
+      await initializeWithName(users[1]);
+      expect.fail("Expected duplicate name to fail");
+    } catch (error) {
+      console.log("Expected error caught:", error.message);
+      expect(error.message).to.include("already in use");
+    }
+  });
+
+  it("Name too long test", async () => {
+    console.log("=== Testing Name Length Limit ===");
+
+    const user = anchor.web3.Keypair.generate();
+    const signature = await program.provider.connection.requestAirdrop(
+      user.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.provider.connection.confirmTransaction(signature);
+
+    const [userStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user-stats"), user.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // 33 bytes can't be a seed, so there is no registry PDA to pass; any address will do
+    const name = "x".repeat(33);
+    try {
+      await program.methods
+        .initializeUserStats(name, null, null)
+        .accounts({
+          userStats: userStatsPda,
+          nameRegistry: anchor.web3.Keypair.generate().publicKey,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([user])
+        .rpc();
+      expect.fail("Expected a 33-byte name to fail");
+    } catch (error) {
+      console.log("Expected error caught:", error.message);
+      expect(error.message).to.include("NameTooLong");
+    }
+
+    const userStatsInfo = await program.provider.connection.getAccountInfo(userStatsPda);
+    expect(userStatsInfo).to.be.null;
+  });
+
+  it("User stats cap test", async () => {
+    console.log("=== Testing User Stats Caps ===");
+
+    const authority = anchor.web3.Keypair.generate();
+
+    const signature = await program.provider.connection.requestAirdrop(
+      authority.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.provider.connection.confirmTransaction(signature);
+
+    const [userStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user-stats"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // Cap at 200 points and level 2
+    await program.methods
+      .initializeUserStats("Carol", new anchor.BN(200), new anchor.BN(2))
+      .accounts({
+        userStats: userStatsPda,
+        authority: authority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([authority])
+      .rpc();
+
+    const updateUserStats = (points: number) =>
+      program.methods
+        .updateUserStats(new anchor.BN(points))
+        .accounts({
+          userStats: userStatsPda,
+          authority: authority.publicKey,
+        } as any)
+        .signers([authority])
+        .rpc();
+
+    // 150 + 100 would be 250 points / level 3, both clamped to the caps
+    await updateUserStats(150);
+    await updateUserStats(100);
+
+    const userStats = await program.account.userStats.fetch(userStatsPda);
+    console.log("Capped user stats:", {
+      points: userStats.points.toString(),
+      level: userStats.level.toString()
+    });
+    expect(userStats.points.toNumber()).to.equal(200);
+    expect(userStats.level.toNumber()).to.equal(2);
+
+    // Already at the cap, so further updates are rejected
+    try {
+      await updateUserStats(1);
+      expect.fail("Expected update at the cap to fail");
+    } catch (error) {
+      console.log("Expected error caught:", error.message);
+      expect(error.message).to.include("CapReached");
+    }
+  });
+
+  it("User stats overflow test", async () => {
+    console.log("=== Testing Points Overflow ===");
+
+    const authority = anchor.web3.Keypair.generate();
+
+    const signature = await program.provider.connection.requestAirdrop(
+      authority.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await program.provider.connection.confirmTransaction(signature);
+
+    const [userStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user-stats"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // Uncapped (u64::MAX points and levels) so only the overflow check applies
+    const u64Max = new anchor.BN("18446744073709551615");
+    await program.methods
+      .initializeUserStats("Dave", u64Max, u64Max)
+      .accounts({
+        userStats: userStatsPda,
+        authority: authority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([authority])
+      .rpc();
+
+    const updateUserStats = (points: anchor.BN) =>
+      program.methods
+        .updateUserStats(points)
+        .accounts({
+          userStats: userStatsPda,
+          authority: authority.publicKey,
+        } as any)
+        .signers([authority])
+        .rpc();
+
+    // Land just below u64::MAX, then push past it
+    await updateUserStats(u64Max.subn(10));
+
+    try {
+      await updateUserStats(new anchor.BN(100));
+      expect.fail("Expected points overflow to fail");
+    } catch (error) {
+      console.log("Expected error caught:", error.message);
+      expect(error.message).to.include("PointsOverflow");
+    }
+
+    const userStats = await program.account.userStats.fetch(userStatsPda);
+    expect(userStats.points.eq(u64Max.subn(10))).to.be.true;
+  });
+
   it("CPI functionality test", async () => {
     console.log("=== Testing CPI Functionality ===");
 
@@ -168,6 +379,7 @@ describe("anchor-hello-world-complete", () => {
       .pullStrings(new anchor.BN(100))
       .accounts({
         puppet: puppetAccount.publicKey,
+        authority: program.provider.publicKey,
         puppetProgram: puppetProgram.programId,
       } as any)
       .rpc();
@@ -183,7 +395,13 @@ describe("anchor-hello-world-complete", () => {
 
     const puppetAccount = anchor.web3.Keypair.generate();
 
-    // Initialize puppet account
+    // Calculate authority PDA
+    const [authorityPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("authority")],
+      program.programId
+    );
+
+    // Initialize puppet account, then hand control to the PDA
     await puppetProgram.methods
       .initialize()
       .accounts({
@@ -194,11 +412,13 @@ describe("anchor-hello-world-complete", () => {
       .signers([puppetAccount])
       .rpc();
 
-    // Calculate authority PDA
-    const [authorityPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("authority")],
-      program.programId
-    );
+    await puppetProgram.methods
+      .setAuthority(authorityPda)
+      .accounts({
+        puppet: puppetAccount.publicKey,
+        authority: program.provider.publicKey,
+      } as any)
+      .rpc();
 
     console.log("Authority PDA:", authorityPda.toString());
 
@@ -217,4 +437,167 @@ describe("anchor-hello-world-complete", () => {
     console.log("Puppet data after PDA CPI:", puppetData.data.toString());
     expect(puppetData.data.toNumber()).to.equal(200);
   });
+
+  it("CPI with PDA signer authority mismatch test", async () => {
+    console.log("=== Testing CPI with Mismatched PDA Authority ===");
+
+    const puppetAccount = anchor.web3.Keypair.generate();
+
+    // Initialize puppet account controlled by the wallet, not the PDA
+    await puppetProgram.methods
+      .initialize()
+      .accounts({
+        puppet: puppetAccount.publicKey,
+        user: program.provider.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([puppetAccount])
+      .rpc();
+
+    const [authorityPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("authority")],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .pullStringsWithPda(new anchor.BN(200))
+        .accounts({
+          puppet: puppetAccount.publicKey,
+          puppetProgram: puppetProgram.programId,
+          authority: authorityPda,
+        } as any)
+        .rpc();
+      expect.fail("Should have rejected a puppet not controlled by the PDA");
+    } catch (error) {
+      expect(error.message).to.include("PuppetAuthorityMismatch");
+      console.log("✅ Mismatched authority correctly rejected");
+    }
+
+    let puppetData = await puppetProgram.account.puppetData.fetch(puppetAccount.publicKey);
+    expect(puppetData.data.toNumber()).to.equal(0);
+
+    // Once the PDA controls the puppet, calling the puppet directly can't skip it
+    await puppetProgram.methods
+      .setAuthority(authorityPda)
+      .accounts({
+        puppet: puppetAccount.publicKey,
+        authority: program.provider.publicKey,
+      } as any)
+      .rpc();
+
+    try {
+      await puppetProgram.methods
+        .setData(new anchor.BN(200))
+        .accounts({
+          puppet: puppetAccount.publicKey,
+          authority: program.provider.publicKey,
+        } as any)
+        .rpc();
+      expect.fail("Should have rejected a direct call without the PDA");
+    } catch (error) {
+      expect(error.message).to.include("ConstraintHasOne");
+      console.log("✅ Direct call without the PDA correctly rejected");
+    }
+
+    puppetData = await puppetProgram.account.puppetData.fetch(puppetAccount.publicKey);
+    expect(puppetData.data.toNumber()).to.equal(0);
+  });
+
+  it("Nested CPI test", async () => {
+    console.log("=== Testing Nested CPI (program -> puppet -> system) ===");
+
+    const puppetAccount = anchor.web3.Keypair.generate();
+    const recipient = anchor.web3.Keypair.generate();
+    const tip = anchor.web3.LAMPORTS_PER_SOL / 100;
+
+    await puppetProgram.methods
+      .initialize()
+      .accounts({
+        puppet: puppetAccount.publicKey,
+        user: program.provider.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([puppetAccount])
+      .rpc();
+
+    const tx = await program.methods
+      .pullStringsNested(new anchor.BN(300), new anchor.BN(tip))
+      .accounts({
+        puppet: puppetAccount.publicKey,
+        payer: program.provider.publicKey,
+        recipient: recipient.publicKey,
+        puppetProgram: puppetProgram.programId,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc({ commitment: "confirmed" });
+
+    const puppetData = await puppetProgram.account.puppetData.fetch(puppetAccount.publicKey);
+    expect(puppetData.data.toNumber()).to.equal(300);
+
+    const recipientBalance = await program.provider.connection.getBalance(recipient.publicKey, "confirmed");
+    expect(recipientBalance).to.equal(tip);
+
+    // Each level logs its own stack height, outermost first
+    const txDetails = await program.provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const logs = txDetails.meta.logMessages;
+    const heightLogs = logs.filter((log) => log.includes("stack height"));
+    console.log("Stack height logs:", heightLogs);
+    expect(heightLogs).to.have.length(2);
+    expect(heightLogs[0]).to.include("anchor-hello-world running at stack height 1");
+    expect(heightLogs[1]).to.include("Puppet running at stack height 2");
+
+    // The system program shows up as the third invoke level
+    expect(logs.some((log) => log.includes(`${anchor.web3.SystemProgram.programId} invoke [3]`))).to.be.true;
+  });
+
+  it("Puppet logged data change test", async () => {
+    console.log("=== Testing Puppet Data Change Log ===");
+
+    const puppetAccount = anchor.web3.Keypair.generate();
+
+    await puppetProgram.methods
+      .initialize()
+      .accounts({
+        puppet: puppetAccount.publicKey,
+        user: program.provider.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([puppetAccount])
+      .rpc();
+
+    await puppetProgram.methods
+      .setData(new anchor.BN(5))
+      .accounts({ puppet: puppetAccount.publicKey, authority: program.provider.publicKey } as any)
+      .rpc();
+
+    const tx = await puppetProgram.methods
+      .setDataLogged(new anchor.BN(7))
+      .accounts({ puppet: puppetAccount.publicKey, authority: program.provider.publicKey } as any)
+      .rpc({ commitment: "confirmed" });
+
+    const txDetails = await program.provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+
+    // "Program data: <tag> <change>", both base64
+    const dataLog = txDetails.meta.logMessages.find((log) => log.startsWith("Program data: "));
+    expect(dataLog).to.not.be.undefined;
+    const [tag, change] = dataLog
+      .slice("Program data: ".length)
+      .split(" ")
+      .map((field) => Buffer.from(field, "base64"));
+
+    expect(tag.toString()).to.equal("data_changed");
+    expect(change.length).to.equal(16);
+    const oldValue = change.readBigUInt64LE(0);
+    const newValue = change.readBigUInt64LE(8);
+    console.log(`Logged change: ${oldValue} -> ${newValue}`);
+    expect(oldValue).to.equal(BigInt(5));
+    expect(newValue).to.equal(BigInt(7));
+  });
 });
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::log::sol_log_data;
 
 declare_id!("CU6rekujN2XpAqGsdpEmYgWZb5YDbb4cuBHJki6oTdJQ");
 
@@ -6,33 +8,69 @@ declare_id!("CU6rekujN2XpAqGsdpEmYgWZb5YDbb4cuBHJki6oTdJQ");
 pub mod anchor_note {
     use super::*;
 
-    pub fn initialize_user_index(ctx: Context<InitializeUserIndex>) -> Result<()> {
+    pub fn initialize_user_index(ctx: Context<InitializeUserIndex>, max_notes: u32) -> Result<()> {
+        init_user_index(&mut ctx.accounts.user_index, ctx.accounts.user.key(), max_notes)
+    }
+
+    /// Initialize the caller's note index only if it does not exist yet, so clients can
+    /// call it before create_note without checking first.
+    ///
+    /// `init_if_needed` also accepts an account that already exists, so the handler must
+    /// not overwrite it unconditionally: doing so would let anyone who can call this
+    /// instruction reset `note_ids`/`next_note_id` (a reinitialization attack). A freshly
+    /// created index still has the default authority; any other index is left untouched.
+    /// Anchor checks the space of an existing account, so `max_notes` must match the
+    /// value it was created with.
+    pub fn ensure_user_index(ctx: Context<EnsureUserIndex>, max_notes: u32) -> Result<()> {
         let user_index = &mut ctx.accounts.user_index;
-        user_index.authority = ctx.accounts.user.key();
-        user_index.note_count = 0;
-        user_index.note_ids = Vec::new();
 
-        msg!("User note index initialized for {}", ctx.accounts.user.key());
-        Ok(())
+        if user_index.authority != Pubkey::default() {
+            require_keys_eq!(user_index.authority, ctx.accounts.user.key(), NoteError::Unauthorized);
+            msg!("User note index already exists for {}", ctx.accounts.user.key());
+            return Ok(());
+        }
+
+        init_user_index(user_index, ctx.accounts.user.key(), max_notes)
     }
 
     pub fn create_note(ctx: Context<CreateNote>, note_id: u64, message: String) -> Result<()> {
         require!(message.len() <= 1000, NoteError::MessageTooLong);
+        require!(!message.is_empty(), NoteError::EmptyMessage);
 
         let user_index = &mut ctx.accounts.user_index;
         let now = Clock::get()?.unix_timestamp;
 
-        require!(note_id == user_index.note_count, NoteError::InvalidNoteId);
+        // Fresh ids come from next_note_id, which only ever grows. Ids below it belong
+        // to deleted notes and may be reused, except recently deleted ones, which must
+        // be reclaimed first; this keeps a note closed earlier in the same transaction
+        // from being reinitialized under its old id
+        let reusing_id = note_id < user_index.next_note_id;
+        require!(note_id <= user_index.next_note_id, NoteError::InvalidNoteId);
+        require!(!user_index.deleted_ids.contains(&note_id), NoteError::IdRecentlyDeleted);
+        require!(!user_index.note_ids.contains(&note_id), NoteError::InvalidNoteId);
+        require!(
+            user_index.note_ids.len() < user_index.max_notes as usize,
+            NoteError::IndexFull
+        );
 
         let note = &mut ctx.accounts.note;
         note.authority = ctx.accounts.user.key();
         note.note_id = note_id;
+        note.content_hash = hash(message.as_bytes()).to_bytes();
         note.message = message;
         note.create_at = now;
         note.update_at = now;
+        note.edit_count = 0;
+        note.compressed = false;
+        note.stars = 0;
 
         user_index.note_ids.push(note_id);
         user_index.note_count += 1;
+        if !reusing_id {
+            user_index.next_note_id += 1;
+        }
+
+        log_note_created(note_id, now, now);
 
         msg!("Note {} created successfully", note_id);
         Ok(())
@@ -43,40 +81,351 @@ pub mod anchor_note {
         let note = &ctx.accounts.note;
 
         user_index.note_ids.retain(|&id| id != note_id);
+        user_index.note_count = user_index.note_count.saturating_sub(1);
+        user_index.tombstone(note_id);
+        user_index.untag(note_id);
+
+        // The message is gone once the account closes, so keep a hash of it in the logs
+        emit!(NoteDeleted {
+            note_id: note.note_id,
+            authority: note.authority,
+            content_hash: hash(note.message.as_bytes()).to_bytes(),
+        });
 
         msg!("Note {} deleted successfully", note.note_id);
         Ok(())
     }
 
+    /// Like delete_note, but the rent refund goes to `recipient` instead of the authority.
+    ///
+    /// The `close` constraint only accepts an account from the context, so the note is
+    /// closed by hand: move its lamports, hand it back to the system program and
+    /// shrink it to zero bytes. Anchor then sees it as closed and skips writing it back.
+    pub fn delete_note_to(ctx: Context<DeleteNoteTo>, note_id: u64, recipient: Pubkey) -> Result<()> {
+        let user_index = &mut ctx.accounts.user_index;
+        let note = &ctx.accounts.note;
+
+        user_index.note_ids.retain(|&id| id != note_id);
+        user_index.note_count = user_index.note_count.saturating_sub(1);
+        user_index.tombstone(note_id);
+        user_index.untag(note_id);
+
+        emit!(NoteDeleted {
+            note_id: note.note_id,
+            authority: note.authority,
+            content_hash: hash(note.message.as_bytes()).to_bytes(),
+        });
+
+        let note_info = note.to_account_info();
+        let recipient_info = ctx.accounts.recipient_account.to_account_info();
+        let refund = note_info.lamports();
+
+        **recipient_info.try_borrow_mut_lamports()? = recipient_info
+            .lamports()
+            .checked_add(refund)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **note_info.try_borrow_mut_lamports()? = 0;
+        note_info.assign(&System::id());
+        note_info.resize(0)?;
+
+        msg!("Note {} deleted, {} lamports refunded to {}", note_id, refund, recipient);
+        Ok(())
+    }
+
+    /// Set how long a note may go without updates before close_if_stale can close it.
+    /// 0 disables stale cleanup.
+    pub fn set_stale_after(ctx: Context<SetStaleAfter>, stale_after_seconds: i64) -> Result<()> {
+        require!(stale_after_seconds >= 0, NoteError::InvalidStaleAfter);
+
+        let user_index = &mut ctx.accounts.user_index;
+        user_index.stale_after_seconds = stale_after_seconds;
+
+        msg!("Notes for {} go stale after {} seconds", user_index.authority, stale_after_seconds);
+        Ok(())
+    }
+
+    /// Close a note that has not been updated for longer than the index's
+    /// `stale_after_seconds`, refunding rent to its authority. Anyone may call this,
+    /// so abandoned notes can be cleaned up automatically.
+    pub fn close_if_stale(ctx: Context<CloseIfStale>, note_id: u64) -> Result<()> {
+        let user_index = &mut ctx.accounts.user_index;
+        let note = &ctx.accounts.note;
+        let now = Clock::get()?.unix_timestamp;
+
+        let idle_seconds = now.saturating_sub(note.update_at);
+        require!(
+            user_index.stale_after_seconds > 0 && idle_seconds > user_index.stale_after_seconds,
+            NoteError::NotStale
+        );
+
+        user_index.note_ids.retain(|&id| id != note_id);
+        user_index.note_count = user_index.note_count.saturating_sub(1);
+        user_index.tombstone(note_id);
+        user_index.untag(note_id);
+
+        msg!("Note {} closed after {} idle seconds", note.note_id, idle_seconds);
+        Ok(())
+    }
+
+    /// Clear the tombstone of a recently deleted id so create_note may reuse it.
+    pub fn reclaim_id(ctx: Context<ReclaimId>, note_id: u64) -> Result<()> {
+        let user_index = &mut ctx.accounts.user_index;
+
+        let position = user_index
+            .deleted_ids
+            .iter()
+            .position(|&id| id == note_id)
+            .ok_or(NoteError::IdNotDeleted)?;
+        user_index.deleted_ids.remove(position);
+
+        msg!("Note id {} reclaimed", note_id);
+        Ok(())
+    }
+
+    /// Replace the tags of an indexed note, keeping the user's tag index in sync.
+    /// Pass an empty list to clear them.
+    pub fn set_note_tags(ctx: Context<SetNoteTags>, note_id: u64, tags: Vec<String>) -> Result<()> {
+        require!(tags.len() <= Note::MAX_TAGS_PER_NOTE, NoteError::TooManyTags);
+        for tag in &tags {
+            require!(
+                !tag.is_empty() && tag.len() <= UserNoteIndex::MAX_TAG_LEN,
+                NoteError::InvalidTag
+            );
+        }
+
+        let user_index = &mut ctx.accounts.user_index;
+        require!(user_index.note_ids.contains(&note_id), NoteError::InvalidNoteId);
+
+        user_index.untag(note_id);
+        for tag in &tags {
+            user_index.tag(tag, note_id)?;
+        }
+
+        msg!("Note {} tagged with {:?}", note_id, tags);
+        Ok(())
+    }
+
+    /// Ids of the user's notes carrying `tag`, empty if none do
+    pub fn get_note_ids_by_tag(ctx: Context<GetUserNoteIds>, tag: String) -> Result<Vec<u64>> {
+        let user_index = &ctx.accounts.user_index;
+        Ok(user_index
+            .tag_index
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .map(|entry| entry.note_ids.clone())
+            .unwrap_or_default())
+    }
+
     pub fn get_user_note_ids(ctx: Context<GetUserNoteIds>) -> Result<Vec<u64>> {
         let user_index = &ctx.accounts.user_index;
         Ok(user_index.note_ids.clone())
     }
 
+    /// Number of notes the user currently has. Ids are allocated by `next_note_id`,
+    /// so `note_count` goes down again on delete.
+    pub fn get_user_note_count(ctx: Context<GetUserNoteIds>) -> Result<u64> {
+        let user_index = &ctx.accounts.user_index;
+        Ok(user_index.note_count)
+    }
+
+    /// Return the ids in `note_ids` whose note account no longer exists, e.g. because it
+    /// was closed with `delete`, which doesn't know about the index.
+    ///
+    /// Pass one note PDA per entry of `note_ids`, in the same order, as remaining
+    /// accounts. The transaction account limit caps how large an index can be audited.
+    pub fn audit_index(ctx: Context<GetUserNoteIds>) -> Result<Vec<u64>> {
+        let user_index = &ctx.accounts.user_index;
+        let note_accounts = ctx.remaining_accounts;
+        require!(
+            note_accounts.len() == user_index.note_ids.len(),
+            NoteError::AuditAccountMismatch
+        );
+
+        let authority = ctx.accounts.user.key();
+        let mut dangling_ids = Vec::new();
+        for (&note_id, note_account) in user_index.note_ids.iter().zip(note_accounts) {
+            let (expected, _) = Pubkey::find_program_address(
+                &[authority.as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(note_account.key(), expected, NoteError::AuditAccountMismatch);
+
+            // A closed note is back to a system-owned, empty account
+            if note_account.owner != ctx.program_id || note_account.data_is_empty() {
+                dangling_ids.push(note_id);
+            }
+        }
+
+        msg!("Audited {} notes, {} dangling", note_accounts.len(), dangling_ids.len());
+        Ok(dangling_ids)
+    }
+
+    /// Return a note's content so other programs can read it over CPI.
+    /// Compressed messages are expanded first; the message is then truncated so the
+    /// whole `NoteContent` fits in return data.
+    pub fn read_note(ctx: Context<ReadNote>, _note_id: u64) -> Result<NoteContent> {
+        let note = &ctx.accounts.note;
+        let message = if note.compressed {
+            rle_decompress(&note.message, Note::MAX_DECOMPRESSED_LEN)?
+        } else {
+            note.message.clone()
+        };
+        Ok(NoteContent {
+            note_id: note.note_id,
+            message: truncate_message(&message, NoteContent::MAX_MESSAGE_LEN).to_string(),
+            create_at: note.create_at,
+            update_at: note.update_at,
+        })
+    }
+
     pub fn create(ctx: Context<Create>, note_id: u64, message: String) -> Result<()> {
         require!(message.len() <= 1000, NoteError::MessageTooLong);
+        require!(!message.is_empty(), NoteError::EmptyMessage);
 
         let note = &mut ctx.accounts.note;
         let now = Clock::get()?.unix_timestamp;
 
         note.authority = ctx.accounts.user.key();
         note.note_id = note_id;
+        note.content_hash = hash(message.as_bytes()).to_bytes();
         note.message = message;
         note.create_at = now;
         note.update_at = now;
+        note.edit_count = 0;
+        note.compressed = false;
+        note.stars = 0;
 
         msg!("Note {} created successfully", note.note_id);
         Ok(())
     }
 
+    /// Like `create`, but run-length encodes the message so long repetitive content fits
+    /// the 1000-byte cap. Messages that don't shrink are stored as-is.
+    pub fn create_compressed(ctx: Context<Create>, note_id: u64, message: String) -> Result<()> {
+        require!(message.len() <= Note::MAX_DECOMPRESSED_LEN, NoteError::MessageTooLong);
+        require!(!message.is_empty(), NoteError::EmptyMessage);
+
+        let encoded = rle_compress(&message);
+        let compressed = encoded.len() < message.len();
+        let stored = if compressed { encoded } else { message };
+        require!(stored.len() <= 1000, NoteError::MessageTooLong);
+
+        let note = &mut ctx.accounts.note;
+        let now = Clock::get()?.unix_timestamp;
+
+        note.authority = ctx.accounts.user.key();
+        note.note_id = note_id;
+        note.content_hash = hash(stored.as_bytes()).to_bytes();
+        note.message = stored;
+        note.create_at = now;
+        note.update_at = now;
+        note.edit_count = 0;
+        note.compressed = compressed;
+        note.stars = 0;
+
+        msg!("Note {} created ({} bytes stored, compressed: {})", note.note_id, note.message.len(), compressed);
+        Ok(())
+    }
+
     pub fn update(ctx: Context<Update>, _note_id: u64, message: String) -> Result<()> {
         require!(message.len() <= 1000, NoteError::MessageTooLong);
+        require!(!message.is_empty(), NoteError::EmptyMessage);
 
         let note = &mut ctx.accounts.note;
+        require!(!note.is_expired(Clock::get()?.slot), NoteError::NoteExpired);
+        note.content_hash = hash(message.as_bytes()).to_bytes();
         note.message = message;
+        note.compressed = false;
         note.update_at = Clock::get()?.unix_timestamp;
+        note.edit_count = note
+            .edit_count
+            .checked_add(1)
+            .ok_or(NoteError::EditCountOverflow)?;
+
+        msg!("Note {} updated successfully (edit #{})", note.note_id, note.edit_count);
+        Ok(())
+    }
+
+    /// Set (or clear with `None`) the slot after which the note can no longer be updated
+    pub fn set_note_expiry(ctx: Context<Update>, _note_id: u64, expires_at_slot: Option<u64>) -> Result<()> {
+        let note = &mut ctx.accounts.note;
+        note.expires_at_slot = expires_at_slot;
 
-        msg!("Note {} updated successfully", note.note_id);
+        match expires_at_slot {
+            Some(slot) => msg!("Note {} expires at slot {}", note.note_id, slot),
+            None => msg!("Note {} no longer expires", note.note_id),
+        }
+        Ok(())
+    }
+
+    /// Bump `update_at` on a batch of the signer's notes to one shared timestamp,
+    /// marking them as recently active without changing their content.
+    ///
+    /// Pass the note PDAs, writable, as remaining accounts; at most
+    /// `Note::MAX_TOUCH_BATCH` per call.
+    pub fn touch_notes<'info>(ctx: Context<'_, '_, 'info, 'info, TouchNotes<'info>>) -> Result<()> {
+        let note_accounts = ctx.remaining_accounts;
+        require!(
+            !note_accounts.is_empty() && note_accounts.len() <= Note::MAX_TOUCH_BATCH,
+            NoteError::InvalidTouchBatch
+        );
+
+        let authority = ctx.accounts.authority.key();
+        let now = Clock::get()?.unix_timestamp;
+        for note_account in note_accounts {
+            require!(note_account.is_writable, ErrorCode::ConstraintMut);
+
+            // Checks the owner and discriminator, then the same has_one rule as `update`
+            let mut note = Account::<Note>::try_from(note_account)?;
+            require_keys_eq!(note.authority, authority, NoteError::Unauthorized);
+
+            note.update_at = now;
+            note.exit(ctx.program_id)?;
+        }
+
+        msg!("Touched {} notes at {}", note_accounts.len(), now);
+        Ok(())
+    }
+
+    /// Whether the note has reached its expiry slot
+    pub fn is_expired(ctx: Context<ReadNote>, _note_id: u64) -> Result<bool> {
+        Ok(ctx.accounts.note.is_expired(Clock::get()?.slot))
+    }
+
+    /// Whether the stored message still hashes to the `content_hash` recorded when it
+    /// was last written through this program.
+    ///
+    /// Every instruction that writes `message` also writes `content_hash`, so a mismatch
+    /// means the account data changed some other way, e.g. an upgraded program version
+    /// that writes the message without rehashing, or a corrupted account.
+    pub fn verify_note_integrity(ctx: Context<ReadNote>, _note_id: u64) -> Result<bool> {
+        let note = &ctx.accounts.note;
+        let intact = hash(note.message.as_bytes()).to_bytes() == note.content_hash;
+
+        msg!("Note {} integrity check: {}", note.note_id, if intact { "ok" } else { "MISMATCH" });
+        Ok(intact)
+    }
+
+    /// Star someone's note. Any signer may star a note once; the Star PDA for
+    /// (note, voter) is created here, so a second star fails on its init.
+    pub fn star_note(ctx: Context<StarNote>, _note_id: u64) -> Result<()> {
+        let star = &mut ctx.accounts.star;
+        star.note = ctx.accounts.note.key();
+        star.voter = ctx.accounts.voter.key();
+
+        let note = &mut ctx.accounts.note;
+        note.stars = note.stars.checked_add(1).ok_or(NoteError::StarCountOverflow)?;
+
+        msg!("Note {} starred by {} ({} stars)", note.note_id, star.voter, note.stars);
+        Ok(())
+    }
+
+    /// Remove the signer's star, closing their Star PDA and refunding its rent
+    pub fn unstar_note(ctx: Context<UnstarNote>, _note_id: u64) -> Result<()> {
+        let note = &mut ctx.accounts.note;
+        note.stars = note.stars.saturating_sub(1);
+
+        msg!("Note {} unstarred by {} ({} stars)", note.note_id, ctx.accounts.voter.key(), note.stars);
         Ok(())
     }
 
@@ -85,6 +434,162 @@ pub mod anchor_note {
         msg!("Note {} deleted successfully", note.note_id);
         Ok(())
     }
+
+    /// Move a note to a new authority.
+    ///
+    /// Note PDAs are derived from the authority, so rewriting `note.authority` in place
+    /// would leave the note at an address only the old authority's key can derive.
+    /// Instead the note is copied to the PDA derived from the new authority (same
+    /// note_id) and the old account is closed, refunding its rent to the old authority.
+    /// Both authorities must sign; the new authority pays for the new account.
+    /// User indexes are not updated, so this is meant for notes made with `create`.
+    pub fn migrate_note(ctx: Context<MigrateNote>, note_id: u64) -> Result<()> {
+        let old_note = &ctx.accounts.old_note;
+        let new_note = &mut ctx.accounts.new_note;
+
+        new_note.authority = ctx.accounts.new_authority.key();
+        new_note.note_id = note_id;
+        new_note.message = old_note.message.clone();
+        new_note.create_at = old_note.create_at;
+        new_note.update_at = old_note.update_at;
+        new_note.expires_at_slot = old_note.expires_at_slot;
+        new_note.edit_count = old_note.edit_count;
+        new_note.content_hash = old_note.content_hash;
+        new_note.compressed = old_note.compressed;
+        // Star PDAs are derived from the old note's address, so the count starts over
+        new_note.stars = 0;
+
+        msg!(
+            "Note {} migrated from {} to {}",
+            note_id,
+            ctx.accounts.authority.key(),
+            new_note.authority
+        );
+        Ok(())
+    }
+}
+
+fn init_user_index(user_index: &mut UserNoteIndex, authority: Pubkey, max_notes: u32) -> Result<()> {
+    require!(
+        max_notes > 0 && max_notes <= UserNoteIndex::MAX_NOTES_CEILING,
+        NoteError::InvalidMaxNotes
+    );
+
+    user_index.authority = authority;
+    user_index.note_count = 0;
+    user_index.note_ids = Vec::new();
+    user_index.max_notes = max_notes;
+    user_index.stale_after_seconds = 0;
+    user_index.deleted_ids = Vec::new();
+    user_index.tag_index = Vec::new();
+    user_index.next_note_id = 0;
+
+    msg!("User note index initialized for {} with capacity {}", authority, max_notes);
+    Ok(())
+}
+
+/// Longest prefix of `message` that is at most `max_len` bytes and ends on a char boundary
+fn truncate_message(message: &str, max_len: usize) -> &str {
+    if message.len() <= max_len {
+        return message;
+    }
+    let mut end = max_len;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    &message[..end]
+}
+
+/// Marks an encoded run in a compressed message
+pub const RLE_ESCAPE: char = '\u{1}';
+/// Shortest run worth encoding; shorter runs are kept literally
+pub const RLE_MIN_RUN: usize = 4;
+
+/// Run-length encode `message`: each run of at least RLE_MIN_RUN copies of a char
+/// (and any RLE_ESCAPE) becomes ESCAPE, the run length in decimal, ':', then the char.
+/// Everything else is copied unchanged, so the result is still valid UTF-8.
+pub fn rle_compress(message: &str) -> String {
+    let mut encoded = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut run = 1;
+        while chars.peek() == Some(&c) {
+            chars.next();
+            run += 1;
+        }
+        if run >= RLE_MIN_RUN || c == RLE_ESCAPE {
+            encoded.push(RLE_ESCAPE);
+            encoded.push_str(&run.to_string());
+            encoded.push(':');
+            encoded.push(c);
+        } else {
+            for _ in 0..run {
+                encoded.push(c);
+            }
+        }
+    }
+    encoded
+}
+
+/// Reverse rle_compress, failing once the output would exceed `max_len` bytes
+/// so a tiny encoded run can't expand into an unbounded message
+pub fn rle_decompress(encoded: &str, max_len: usize) -> Result<String> {
+    let mut message = String::new();
+    let mut chars = encoded.chars();
+    while let Some(c) = chars.next() {
+        let (run, c) = if c == RLE_ESCAPE {
+            let mut run: usize = 0;
+            loop {
+                match chars.next() {
+                    Some(':') => break,
+                    Some(digit) => {
+                        let digit = digit.to_digit(10).ok_or(NoteError::InvalidCompressedData)?;
+                        run = run
+                            .checked_mul(10)
+                            .and_then(|run| run.checked_add(digit as usize))
+                            .ok_or(NoteError::InvalidCompressedData)?;
+                    }
+                    None => return err!(NoteError::InvalidCompressedData),
+                }
+            }
+            (run, chars.next().ok_or(NoteError::InvalidCompressedData)?)
+        } else {
+            (1, c)
+        };
+
+        // Check the size before expanding the run
+        let expanded_len = run
+            .checked_mul(c.len_utf8())
+            .and_then(|len| len.checked_add(message.len()))
+            .ok_or(NoteError::DecompressedTooLong)?;
+        require!(expanded_len <= max_len, NoteError::DecompressedTooLong);
+        for _ in 0..run {
+            message.push(c);
+        }
+    }
+    Ok(message)
+}
+
+/// Tag logged as the first field of the `note_created` program data log
+pub const NOTE_CREATED_LOG_TAG: &[u8] = b"note_created";
+
+/// Emit a compact binary "Program data:" log for indexers that skip Anchor event decoding.
+/// Logged as two base64 fields: the tag, then a 24-byte payload of little-endian
+/// [0..8] note_id: u64 | [8..16] create_at: i64 | [16..24] update_at: i64
+fn log_note_created(note_id: u64, create_at: i64, update_at: i64) {
+    let mut payload = [0u8; 24];
+    payload[0..8].copy_from_slice(&note_id.to_le_bytes());
+    payload[8..16].copy_from_slice(&create_at.to_le_bytes());
+    payload[16..24].copy_from_slice(&update_at.to_le_bytes());
+    sol_log_data(&[NOTE_CREATED_LOG_TAG, &payload]);
+}
+
+/// Emitted by delete_note; `content_hash` is the SHA-256 of the deleted message
+#[event]
+pub struct NoteDeleted {
+    pub note_id: u64,
+    pub authority: Pubkey,
+    pub content_hash: [u8; 32],
 }
 
 #[account]
@@ -94,32 +599,165 @@ pub struct Note {
     pub message: String,
     pub create_at: i64,
     pub update_at: i64,
+    pub expires_at_slot: Option<u64>,
+    pub edit_count: u32,
+    pub content_hash: [u8; 32], // SHA-256 of message, rewritten with it
+    pub compressed: bool,       // message holds rle_compress output
+    pub stars: u32,             // one per live Star PDA for this note
 }
 
 impl Note {
     // 8(discriminator) + 32(authority) + 8(note_id) + 4 + 1000(message) + 8(create_at) + 8(update_at)
-    pub const MAX_SIZE: usize = 8 + 32 + 8 + 4 + 1000 + 8 + 8;
+    // + 1 + 8(expires_at_slot) + 4(edit_count) + 32(content_hash) + 1(compressed) + 4(stars)
+    pub const MAX_SIZE: usize = 8 + 32 + 8 + 4 + 1000 + 8 + 8 + 1 + 8 + 4 + 32 + 1 + 4;
+
+    // largest message create_compressed accepts, and read_note will expand to
+    pub const MAX_DECOMPRESSED_LEN: usize = 4000;
+
+    // most tags set_note_tags accepts for one note
+    pub const MAX_TAGS_PER_NOTE: usize = 5;
+
+    // most notes touch_notes updates in one call
+    pub const MAX_TOUCH_BATCH: usize = 10;
+
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        matches!(self.expires_at_slot, Some(slot) if current_slot >= slot)
+    }
+}
+
+/// Records that `voter` starred `note`; seeds [b"star", note, voter]
+#[account]
+pub struct Star {
+    pub note: Pubkey,
+    pub voter: Pubkey,
+}
+
+impl Star {
+    // 8(discriminator) + 32(note) + 32(voter)
+    pub const SPACE: usize = 8 + 32 + 32;
+}
+
+/// Returned by read_note
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NoteContent {
+    pub note_id: u64,
+    pub message: String,
+    pub create_at: i64,
+    pub update_at: i64,
+}
+
+impl NoteContent {
+    // 1024(max return data) - 8(note_id) - 4(message len) - 8(create_at) - 8(update_at)
+    pub const MAX_MESSAGE_LEN: usize = 1024 - 8 - 4 - 8 - 8;
 }
 
 /// 用户笔记索引
 #[account]
 pub struct UserNoteIndex {
     pub authority: Pubkey,    // 32 bytes
-    pub note_count: u64,      // 8 bytes
+    pub note_count: u64,      // 8 bytes, live notes
     pub note_ids: Vec<u64>,   // 4 + (8 * max_notes)
+    pub max_notes: u32,       // 4 bytes
+    pub stale_after_seconds: i64, // 8 bytes, 0 = notes never go stale
+    pub deleted_ids: Vec<u64>, // 4 + (8 * MAX_DELETED_IDS), oldest first
+    pub tag_index: Vec<TagEntry>, // 4 + (TagEntry::SPACE * MAX_TAGS)
+    pub next_note_id: u64,    // 8 bytes, next fresh id, never decreases
+}
+
+/// Note ids carrying one tag, stored in UserNoteIndex::tag_index
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TagEntry {
+    pub tag: String,
+    pub note_ids: Vec<u64>,
+}
+
+impl TagEntry {
+    // 4 + MAX_TAG_LEN(tag) + 4 + (8 * MAX_IDS_PER_TAG)(note_ids)
+    pub const SPACE: usize = 4 + UserNoteIndex::MAX_TAG_LEN + 4 + (8 * UserNoteIndex::MAX_IDS_PER_TAG);
 }
 
 impl UserNoteIndex {
-    // max 100 notes
-    pub const MAX_SIZE: usize = 8 + 32 + 8 + 4 + (8 * 100);
+    // largest capacity whose account still fits the 10KiB CPI allocation limit
+    pub const MAX_NOTES_CEILING: u32 = 1000;
+
+    // how many recently deleted ids are remembered before the oldest is forgotten
+    pub const MAX_DELETED_IDS: usize = 20;
+
+    // bounds on the tag index: distinct tags, bytes per tag, and notes per tag
+    pub const MAX_TAGS: usize = 10;
+    pub const MAX_TAG_LEN: usize = 16;
+    pub const MAX_IDS_PER_TAG: usize = 20;
+
+    // 8(discriminator) + 32(authority) + 8(note_count) + 4 + (8 * max_notes)(note_ids) + 4(max_notes)
+    // + 8(stale_after_seconds) + 4 + (8 * MAX_DELETED_IDS)(deleted_ids) + 4 + (TagEntry::SPACE * MAX_TAGS)(tag_index)
+    // + 8(next_note_id)
+    pub fn space(max_notes: u32) -> usize {
+        8 + 32 + 8 + 4 + (8 * max_notes as usize) + 4 + 8 + 4 + (8 * Self::MAX_DELETED_IDS)
+            + 4 + (TagEntry::SPACE * Self::MAX_TAGS) + 8
+    }
+
+    /// Remember `note_id` as recently deleted, forgetting the oldest tombstone when full
+    pub fn tombstone(&mut self, note_id: u64) {
+        if self.deleted_ids.len() >= Self::MAX_DELETED_IDS {
+            self.deleted_ids.remove(0);
+        }
+        self.deleted_ids.push(note_id);
+    }
+
+    /// Add `note_id` under `tag`, creating the tag entry if needed
+    pub fn tag(&mut self, tag: &str, note_id: u64) -> Result<()> {
+        let position = match self.tag_index.iter().position(|entry| entry.tag == tag) {
+            Some(position) => position,
+            None => {
+                require!(self.tag_index.len() < Self::MAX_TAGS, NoteError::TagIndexFull);
+                self.tag_index.push(TagEntry {
+                    tag: tag.to_string(),
+                    note_ids: Vec::new(),
+                });
+                self.tag_index.len() - 1
+            }
+        };
+
+        let entry = &mut self.tag_index[position];
+        if !entry.note_ids.contains(&note_id) {
+            require!(entry.note_ids.len() < Self::MAX_IDS_PER_TAG, NoteError::TagIndexFull);
+            entry.note_ids.push(note_id);
+        }
+        Ok(())
+    }
+
+    /// Remove `note_id` from every tag, dropping tags left without notes
+    pub fn untag(&mut self, note_id: u64) {
+        for entry in self.tag_index.iter_mut() {
+            entry.note_ids.retain(|&id| id != note_id);
+        }
+        self.tag_index.retain(|entry| !entry.note_ids.is_empty());
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(max_notes: u32)]
 pub struct InitializeUserIndex<'info> {
     #[account(
         init,
         payer = user,
-        space = UserNoteIndex::MAX_SIZE,
+        space = UserNoteIndex::space(max_notes),
+        seeds = [user.key().as_ref(), b"index"],
+        bump
+    )]
+    pub user_index: Account<'info, UserNoteIndex>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(max_notes: u32)]
+pub struct EnsureUserIndex<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserNoteIndex::space(max_notes),
         seeds = [user.key().as_ref(), b"index"],
         bump
     )]
@@ -173,6 +811,99 @@ pub struct DeleteNote<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(note_id: u64, recipient: Pubkey)]
+pub struct DeleteNoteTo<'info> {
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub note: Account<'info, Note>,
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"index"],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub user_index: Account<'info, UserNoteIndex>,
+    pub authority: Signer<'info>,
+    /// CHECK: only receives lamports; must be the `recipient` argument and not the note
+    #[account(
+        mut,
+        address = recipient @ NoteError::RecipientMismatch,
+        constraint = recipient_account.key() != note.key() @ NoteError::RecipientMismatch
+    )]
+    pub recipient_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStaleAfter<'info> {
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"index"],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub user_index: Account<'info, UserNoteIndex>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct SetNoteTags<'info> {
+    #[account(
+        seeds = [authority.key().as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub note: Account<'info, Note>,
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"index"],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub user_index: Account<'info, UserNoteIndex>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimId<'info> {
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"index"],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub user_index: Account<'info, UserNoteIndex>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct CloseIfStale<'info> {
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = authority @ NoteError::Unauthorized,
+        close = authority
+    )]
+    pub note: Account<'info, Note>,
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"index"],
+        bump,
+        has_one = authority @ NoteError::Unauthorized
+    )]
+    pub user_index: Account<'info, UserNoteIndex>,
+    /// Receives the rent refund; does not need to sign
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct GetUserNoteIds<'info> {
     #[account(
@@ -183,6 +914,16 @@ pub struct GetUserNoteIds<'info> {
     pub user: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct ReadNote<'info> {
+    #[account(
+        seeds = [note.authority.as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub note: Account<'info, Note>,
+}
+
 #[derive(Accounts)]
 #[instruction(note_id: u64)]
 pub struct Create<'info> {
@@ -199,6 +940,35 @@ pub struct Create<'info> {
    pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct MigrateNote<'info> {
+    #[account(
+        mut,
+        seeds = [authority.key().as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = authority @ NoteError::Unauthorized,
+        close = authority
+    )]
+    pub old_note: Account<'info, Note>,
+    #[account(
+        init,
+        payer = new_authority,
+        space = Note::MAX_SIZE,
+        seeds = [new_authority.key().as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub new_note: Account<'info, Note>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = new_authority.key() != authority.key() @ NoteError::SameAuthority
+    )]
+    pub new_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(note_id: u64)]
 pub struct Update<'info> {
@@ -212,6 +982,54 @@ pub struct Update<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct StarNote<'info> {
+    #[account(
+        mut,
+        seeds = [note.authority.as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub note: Account<'info, Note>,
+    #[account(
+        init,
+        payer = voter,
+        space = Star::SPACE,
+        seeds = [b"star", note.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub star: Account<'info, Star>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct UnstarNote<'info> {
+    #[account(
+        mut,
+        seeds = [note.authority.as_ref(), b"note", note_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub note: Account<'info, Note>,
+    #[account(
+        mut,
+        seeds = [b"star", note.key().as_ref(), voter.key().as_ref()],
+        bump,
+        has_one = voter,
+        close = voter
+    )]
+    pub star: Account<'info, Star>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TouchNotes<'info> {
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(note_id: u64)]
 pub struct Delete<'info> {
@@ -235,4 +1053,42 @@ pub enum NoteError {
     Unauthorized,
     #[msg("Invalid note ID")]
     InvalidNoteId,
+    #[msg("Note index is full")]
+    IndexFull,
+    #[msg("Invalid max notes")]
+    InvalidMaxNotes,
+    #[msg("Message cannot be empty")]
+    EmptyMessage,
+    #[msg("Note has expired")]
+    NoteExpired,
+    #[msg("Note is not stale yet")]
+    NotStale,
+    #[msg("Stale threshold cannot be negative")]
+    InvalidStaleAfter,
+    #[msg("Note id was recently deleted; reclaim it before reuse")]
+    IdRecentlyDeleted,
+    #[msg("Note id is not in the recently deleted list")]
+    IdNotDeleted,
+    #[msg("Edit count overflow")]
+    EditCountOverflow,
+    #[msg("New authority must differ from the current authority")]
+    SameAuthority,
+    #[msg("Too many tags for one note")]
+    TooManyTags,
+    #[msg("Tags must be 1 to 16 bytes")]
+    InvalidTag,
+    #[msg("Tag index is full")]
+    TagIndexFull,
+    #[msg("Compressed message is malformed")]
+    InvalidCompressedData,
+    #[msg("Decompressed message is too long")]
+    DecompressedTooLong,
+    #[msg("Refund recipient must match the recipient argument and not be the note")]
+    RecipientMismatch,
+    #[msg("Pass exactly one note account per indexed id, in index order")]
+    AuditAccountMismatch,
+    #[msg("Touch between 1 and 10 notes per call")]
+    InvalidTouchBatch,
+    #[msg("Star count overflow")]
+    StarCountOverflow,
 }
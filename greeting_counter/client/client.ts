@@ -5,11 +5,15 @@ import * as borsh from "borsh";
 
 class GreetingAccount {
     counter = 0;
+    authority = new Uint8Array(32);
 
-    constructor(fields: { counter: number } | undefined = undefined) {
+    constructor(fields: { counter: number, authority?: Uint8Array } | undefined = undefined) {
         if (fields && fields.counter) {
             this.counter = fields.counter;
         }
+        if (fields && fields.authority) {
+            this.authority = fields.authority;
+        }
     }
 }
 
@@ -18,7 +22,8 @@ const GreetingSchema: borsh.Schema = new Map([
     [GreetingAccount, {
       kind: "struct",
       fields: [
-        ["counter", "u32"]
+        ["counter", "u32"],
+        ["authority", [32]] // all zeros until TransferAuthority is called
       ]
     }]
   ]);
@@ -80,7 +85,7 @@ async function main() {
         const instruction = new TransactionInstruction({
             keys: [{ pubkey: greetingAccount.publicKey, isSigner: false, isWritable: true }],
             programId: PROGRAM_ID,
-            data: Buffer.from([]),
+            data: Buffer.from([0]), // 0 = Increment
         });
 
         // Create a new transaction with a recent blockhash
@@ -11,6 +11,45 @@ use solana_program::{
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct GreetingAccount {
     pub counter: u32,
+    // Key allowed to set and transfer the account, all zeros until first transferred
+    pub authority: Pubkey,
+}
+
+impl GreetingAccount {
+    // A fresh account is controlled by its own keypair until an authority is set
+    pub fn current_authority(&self, account_key: &Pubkey) -> Pubkey {
+        if self.authority == Pubkey::default() {
+            *account_key
+        } else {
+            self.authority
+        }
+    }
+
+    // Reject callers that are not the signing current authority
+    pub fn check_authority(&self, account_key: &Pubkey, authority: &AccountInfo) -> ProgramResult {
+        if !authority.is_signer {
+            msg!("Authority must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *authority.key != self.current_authority(account_key) {
+            msg!("{} is not the authority of this greeting account", authority.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+}
+
+// Same instruction set as greeting_counter_structured, kept in a single file
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub enum GreetingInstruction {
+    // Increment the counter by 1
+    Increment,
+    // Set the counter to a specific value
+    // Accounts: greeting account, current authority (signer)
+    SetCounter { value: u32 },
+    // Hand the account over to a new authority
+    // Accounts: greeting account, current authority (signer)
+    TransferAuthority { new_authority: Pubkey },
 }
 
 entrypoint!(process_instruction);
@@ -22,10 +61,20 @@ pub fn process_instruction(
 ) -> ProgramResult {
     msg!("Greeting Counter program started");
 
+    // Decode the instruction (first byte is the variant discriminator)
+    let instruction = GreetingInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
     let accounts_iter = &mut accounts.iter(); // Create an iterator for the accounts
 
     let account = next_account_info(accounts_iter)?; // Get the first account
 
+    // A freshly created (or never created) account has no data to deserialize
+    if account.data_is_empty() {
+        msg!("Greeting Account {} is not initialized", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+
     // Check if the account is the correct type
     if account.owner != program_id {
         msg!("Greeting Account does not have the correct program id");
@@ -35,13 +84,154 @@ pub fn process_instruction(
     // Deserialize the account data
     let mut greeting_account = GreetingAccount::try_from_slice(&account.data.borrow_mut())?;
 
-    // Increment the counter
-    greeting_account.counter += 1;
+    match instruction {
+        GreetingInstruction::Increment => {
+            // Increment the counter, failing instead of wrapping at u32::MAX
+            greeting_account.counter = greeting_account
+                .counter
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+        GreetingInstruction::SetCounter { value } => {
+            // Only the current authority may overwrite the counter
+            let authority = next_account_info(accounts_iter)?;
+            greeting_account.check_authority(account.key, authority)?;
+
+            // Set the counter to the requested value
+            greeting_account.counter = value;
+        }
+        GreetingInstruction::TransferAuthority { new_authority } => {
+            let authority = next_account_info(accounts_iter)?;
+
+            // Only the current authority may hand the account over
+            greeting_account.check_authority(account.key, authority)?;
+
+            msg!("Transferring authority from {} to {}", authority.key, new_authority);
+            greeting_account.authority = new_authority;
+        }
+    }
 
     // Serialize the updated account data
     greeting_account.serialize(&mut *account.data.borrow_mut())?;
 
-    msg!("Greeting Account updated");
+    msg!("Greeting Account updated to {}", greeting_account.counter);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run `instruction` against a greeting account holding `data`, passing `authority` as account 1
+    fn run(data: &mut [u8], account_key: &Pubkey, authority: &Pubkey, is_signer: bool, instruction: GreetingInstruction) -> ProgramResult {
+        let program_id = Pubkey::new_unique();
+        let (mut lamports, mut authority_lamports, mut authority_data) = (0, 0, vec![]);
+        let account = AccountInfo::new(account_key, false, true, &mut lamports, data, &program_id, false, 0);
+        let authority = AccountInfo::new(authority, is_signer, false, &mut authority_lamports, &mut authority_data, &program_id, false, 0);
+        process_instruction(&program_id, &[account, authority], &instruction.try_to_vec().unwrap())
+    }
+
+    fn greeting_data(authority: Pubkey) -> Vec<u8> {
+        GreetingAccount { counter: 3, authority }.try_to_vec().unwrap()
+    }
+
+    fn stored(data: &[u8]) -> GreetingAccount {
+        GreetingAccount::try_from_slice(data).unwrap()
+    }
+
+    #[test]
+    fn authority_can_transfer_and_set_counter() {
+        let (account_key, authority_key, new_authority) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = greeting_data(authority_key);
+
+        run(&mut data, &account_key, &authority_key, true, GreetingInstruction::SetCounter { value: 9 }).unwrap();
+        assert_eq!(stored(&data).counter, 9);
+
+        run(&mut data, &account_key, &authority_key, true, GreetingInstruction::TransferAuthority { new_authority }).unwrap();
+        assert_eq!(stored(&data).authority, new_authority);
+
+        // the previous authority has lost control
+        assert_eq!(
+            run(&mut data, &account_key, &authority_key, true, GreetingInstruction::SetCounter { value: 1 }),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn fresh_account_is_controlled_by_its_own_key() {
+        let account_key = Pubkey::new_unique();
+        let mut data = greeting_data(Pubkey::default());
+
+        run(&mut data, &account_key, &account_key, true, GreetingInstruction::SetCounter { value: 9 }).unwrap();
+        assert_eq!(stored(&data).counter, 9);
+    }
+
+    #[test]
+    fn other_signer_cannot_transfer_or_set_counter() {
+        let (account_key, authority_key, intruder) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = greeting_data(authority_key);
+
+        assert_eq!(
+            run(&mut data, &account_key, &intruder, true, GreetingInstruction::SetCounter { value: 9 }),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            run(&mut data, &account_key, &intruder, true, GreetingInstruction::TransferAuthority { new_authority: intruder }),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let greeting = stored(&data);
+        assert_eq!((greeting.counter, greeting.authority), (3, authority_key));
+    }
+
+    #[test]
+    fn authority_must_sign() {
+        let (account_key, authority_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = greeting_data(authority_key);
+
+        assert_eq!(
+            run(&mut data, &account_key, &authority_key, false, GreetingInstruction::SetCounter { value: 9 }),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            run(&mut data, &account_key, &authority_key, false, GreetingInstruction::TransferAuthority { new_authority: Pubkey::new_unique() }),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(stored(&data).counter, 3);
+    }
+
+    #[test]
+    fn increment_adds_one() {
+        let account_key = Pubkey::new_unique();
+        let mut data = greeting_data(Pubkey::default());
+
+        run(&mut data, &account_key, &account_key, false, GreetingInstruction::Increment).unwrap();
+        run(&mut data, &account_key, &account_key, false, GreetingInstruction::Increment).unwrap();
+        assert_eq!(stored(&data).counter, 5);
+    }
+
+    #[test]
+    fn increment_at_max_fails_instead_of_wrapping() {
+        let account_key = Pubkey::new_unique();
+        let mut data = GreetingAccount { counter: u32::MAX, authority: Pubkey::default() }.try_to_vec().unwrap();
+
+        assert_eq!(
+            run(&mut data, &account_key, &account_key, false, GreetingInstruction::Increment),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(stored(&data).counter, u32::MAX);
+    }
+
+    #[test]
+    fn empty_account_is_uninitialized() {
+        let account_key = Pubkey::new_unique();
+
+        for instruction in [GreetingInstruction::Increment, GreetingInstruction::SetCounter { value: 1 }] {
+            assert_eq!(
+                run(&mut [], &account_key, &account_key, true, instruction),
+                Err(ProgramError::UninitializedAccount)
+            );
+        }
+    }
+}
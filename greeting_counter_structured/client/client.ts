@@ -4,7 +4,10 @@ import * as borsh from "borsh";
 import { Connection, Keypair, PublicKey, sendAndConfirmTransaction, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
 
 class GreetingAccount {
+    version = 0;
     counter = 0;
+    last_greeted_at = 0;
+    last_increment_slot = 0;
 
     constructor(fields: { counter: number } | undefined = undefined) {
         if (fields && fields.counter) {
@@ -17,7 +20,10 @@ const GreetingAccountSchema = new Map([
     [GreetingAccount, {
         kind: "struct",
         fields: [
-            ["counter", "u32"]
+            ["version", "u8"],
+            ["counter", "u32"],
+            ["last_greeted_at", "u64"], // i64 on-chain; borsh-js has no signed types
+            ["last_increment_slot", "u64"]
         ]
     }]
 ]);
@@ -25,6 +31,10 @@ const GreetingAccountSchema = new Map([
 enum GreetingCounterInstruction {
     Increment = 0,
     SetCounter = 1,
+    Migrate = 2,
+    TimeSinceLastGreeting = 3,
+    BenchmarkSerde = 4,
+    IncrementBy = 5,
 }
 
 class SetCounterInstructionData {
@@ -16,6 +16,10 @@ pub enum GreetingError {
     IncorrectOwner,
     InvalidCounterValue,
     CounterMaximumLimitReached,
+    AccountNeedsMigration,
+    UnsupportedAccountVersion,
+    BenchmarkTooLarge,
+    IncrementThrottled,
 }
 
 /// Allow automatic conversion to ProgramError using `.into()`.
@@ -41,6 +45,18 @@ impl PrintProgramError for GreetingError {
             GreetingError::CounterMaximumLimitReached => {
                 msg!("Error: Counter has reached its maximum limit.");
             }
+            GreetingError::AccountNeedsMigration => {
+                msg!("Error: Account uses the legacy layout, run Migrate first.");
+            }
+            GreetingError::UnsupportedAccountVersion => {
+                msg!("Error: Unsupported account version.");
+            }
+            GreetingError::BenchmarkTooLarge => {
+                msg!("Error: Too many benchmark iterations for the compute budget.");
+            }
+            GreetingError::IncrementThrottled => {
+                msg!("Error: The counter was already incremented in this slot.");
+            }
         }
     }
 }
@@ -2,11 +2,25 @@ use borsh::{BorshSerialize, BorshDeserialize};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
 pub enum GreetingCounterInstruction {
-    // Increment the counter by 1
+    // Increment the counter by 1, at most once per slot
     Increment,
     // Set the counter to a specific value
     SetCounter {
         value: u32,
     },
+    // Upgrade an older (v0 unversioned, v1 or v2) account to the current layout
+    // Accounts: [writable] greeting account, [signer, writable] payer, [] system program
+    Migrate,
+    // Report the seconds elapsed since the last Increment
+    TimeSinceLastGreeting,
+    // Serialize and deserialize a GreetingAccount `iterations` times, logging compute units
+    // around the loop. No accounts.
+    BenchmarkSerde {
+        iterations: u32,
+    },
+    // Increment the counter by `step`, at most once per slot and never past MAX_COUNTER
+    IncrementBy {
+        step: u32,
+    },
 }
 
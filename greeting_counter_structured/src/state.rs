@@ -1,6 +1,301 @@
 use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::{
+    clock::{Clock, UnixTimestamp},
+    msg,
+    program_error::ProgramError,
+};
+
+use crate::error::GreetingError;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct GreetingAccount {
+    pub version: u8,
     pub counter: u32,
+    pub last_greeted_at: UnixTimestamp,
+    pub last_increment_slot: u64,
+}
+
+impl GreetingAccount {
+    /// Layout version written by this program
+    pub const CURRENT_VERSION: u8 = 3;
+    /// v3 size: 1 (version) + 4 (counter) + 8 (last_greeted_at) + 8 (last_increment_slot)
+    pub const LEN: usize = 1 + 4 + 8 + 8;
+    /// v2 accounts had last_greeted_at but no last_increment_slot
+    pub const V2_LEN: usize = 1 + 4 + 8;
+    /// v1 accounts had a version prefix but no last_greeted_at
+    pub const V1_LEN: usize = 1 + 4;
+    /// v0 accounts were a bare u32 counter with no version prefix
+    pub const LEGACY_LEN: usize = 4;
+    /// Highest value Increment and IncrementBy will take the counter to
+    pub const MAX_COUNTER: u32 = 1_000_000;
+
+    /// Deserialize and validate the account version.
+    /// A zeroed version byte means the account was created but never written to.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() == Self::LEGACY_LEN
+            || (data.len() == Self::V1_LEN && data[0] == 1)
+            || (data.len() == Self::V2_LEN && data[0] == 2)
+        {
+            return Err(GreetingError::AccountNeedsMigration.into());
+        }
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        match data[0] {
+            0 => Ok(GreetingAccount {
+                version: Self::CURRENT_VERSION,
+                counter: 0,
+                last_greeted_at: 0,
+                last_increment_slot: 0,
+            }),
+            Self::CURRENT_VERSION => Ok(Self::deserialize(&mut &data[..])?),
+            _ => Err(GreetingError::UnsupportedAccountVersion.into()),
+        }
+    }
+
+    /// Whether an Increment in `slot` must be rejected because one already landed in it.
+    /// An account that has never been greeted is never throttled: its zeroed
+    /// last_increment_slot would otherwise block the first Increment in slot 0.
+    pub fn is_throttled(&self, slot: u64) -> bool {
+        self.last_greeted_at != 0 && self.last_increment_slot == slot
+    }
+
+    /// Add `step` to the counter and stamp the time and slot from `clock`.
+    /// Fails with IncrementThrottled for a second Increment in the same slot, and with
+    /// CounterMaximumLimitReached instead of overflowing or passing MAX_COUNTER.
+    pub fn increment(&mut self, step: u32, clock: &Clock) -> Result<(), ProgramError> {
+        // Throttle: allow one Increment per slot to rate limit spammy callers
+        if self.is_throttled(clock.slot) {
+            msg!("Error: Counter already incremented in slot {}", clock.slot);
+            return Err(GreetingError::IncrementThrottled.into());
+        }
+
+        // Increment counter, refusing to overflow or pass MAX_COUNTER
+        self.counter = self
+            .counter
+            .checked_add(step)
+            .filter(|counter| *counter <= Self::MAX_COUNTER)
+            .ok_or_else(|| {
+                msg!("Error: {} + {} exceeds the limit of {}", self.counter, step, Self::MAX_COUNTER);
+                GreetingError::CounterMaximumLimitReached
+            })?;
+
+        // Remember when it happened
+        self.last_greeted_at = clock.unix_timestamp;
+        self.last_increment_slot = clock.slot;
+        Ok(())
+    }
+
+    /// Seconds elapsed between the last Increment and `now`, or None if the
+    /// account has never been greeted
+    pub fn seconds_since_last_greeting(&self, now: UnixTimestamp) -> Option<UnixTimestamp> {
+        if self.last_greeted_at == 0 {
+            return None;
+        }
+        Some(now.saturating_sub(self.last_greeted_at))
+    }
+
+    /// Read the fields out of an older (v0, v1 or v2) account
+    pub fn unpack_legacy(data: &[u8]) -> Result<Self, ProgramError> {
+        let (version, counter, last_greeted_at) = match data.len() {
+            Self::LEGACY_LEN => (0, u32::try_from_slice(data)?, 0),
+            Self::V1_LEN if data[0] == 1 => (1, u32::try_from_slice(&data[1..])?, 0),
+            Self::V2_LEN if data[0] == 2 => (
+                2,
+                u32::try_from_slice(&data[1..5])?,
+                UnixTimestamp::try_from_slice(&data[5..])?,
+            ),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(GreetingAccount {
+            version,
+            counter,
+            last_greeted_at,
+            last_increment_slot: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v3_account() -> GreetingAccount {
+        GreetingAccount {
+            version: GreetingAccount::CURRENT_VERSION,
+            counter: 7,
+            last_greeted_at: 1_700_000_000,
+            last_increment_slot: 42,
+        }
+    }
+
+    #[test]
+    fn unpack_asks_older_layouts_to_migrate() {
+        let legacy = 7u32.try_to_vec().unwrap();
+        let v1 = [&[1u8][..], &7u32.to_le_bytes()].concat();
+        let v2 = [&[2u8][..], &7u32.to_le_bytes(), &1_700_000_000i64.to_le_bytes()].concat();
+
+        for data in [legacy, v1, v2] {
+            assert_eq!(
+                GreetingAccount::unpack(&data).unwrap_err(),
+                GreetingError::AccountNeedsMigration.into()
+            );
+        }
+    }
+
+    #[test]
+    fn unpack_treats_zeroed_account_as_fresh() {
+        let account = GreetingAccount::unpack(&[0u8; GreetingAccount::LEN]).unwrap();
+
+        assert_eq!(account.version, GreetingAccount::CURRENT_VERSION);
+        assert_eq!(account.counter, 0);
+        assert_eq!(account.last_greeted_at, 0);
+        assert_eq!(account.last_increment_slot, 0);
+    }
+
+    #[test]
+    fn unpack_reads_current_version() {
+        let account = GreetingAccount::unpack(&v3_account().try_to_vec().unwrap()).unwrap();
+
+        assert_eq!(account.counter, 7);
+        assert_eq!(account.last_greeted_at, 1_700_000_000);
+        assert_eq!(account.last_increment_slot, 42);
+    }
+
+    #[test]
+    fn unpack_rejects_unknown_version() {
+        let mut data = v3_account().try_to_vec().unwrap();
+        data[0] = GreetingAccount::CURRENT_VERSION + 1;
+
+        assert_eq!(
+            GreetingAccount::unpack(&data).unwrap_err(),
+            GreetingError::UnsupportedAccountVersion.into()
+        );
+    }
+
+    #[test]
+    fn unpack_rejects_short_data() {
+        assert_eq!(
+            GreetingAccount::unpack(&[GreetingAccount::CURRENT_VERSION; 3]).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn seconds_since_last_greeting_after_clock_advances() {
+        let account = v3_account();
+
+        assert_eq!(account.seconds_since_last_greeting(1_700_000_000), Some(0));
+        assert_eq!(account.seconds_since_last_greeting(1_700_000_090), Some(90));
+        assert_eq!(account.seconds_since_last_greeting(1_700_086_400), Some(86_400));
+    }
+
+    #[test]
+    fn seconds_since_last_greeting_is_none_before_first_greeting() {
+        let account = GreetingAccount::unpack(&[0u8; GreetingAccount::LEN]).unwrap();
+
+        assert_eq!(account.seconds_since_last_greeting(1_700_000_000), None);
+    }
+
+    fn clock_at(slot: u64) -> Clock {
+        Clock {
+            slot,
+            unix_timestamp: 1_700_000_000 + slot as i64,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn first_increment_in_slot_zero_is_not_throttled() {
+        let mut account = GreetingAccount::unpack(&[0u8; GreetingAccount::LEN]).unwrap();
+
+        assert!(!account.is_throttled(0));
+        account.increment(1, &clock_at(0)).unwrap();
+        assert_eq!(account.counter, 1);
+        assert_eq!(account.last_increment_slot, 0);
+    }
+
+    #[test]
+    fn second_increment_in_the_same_slot_is_throttled() {
+        let mut account = GreetingAccount::unpack(&[0u8; GreetingAccount::LEN]).unwrap();
+        account.increment(1, &clock_at(5)).unwrap();
+
+        assert_eq!(
+            account.increment(1, &clock_at(5)).unwrap_err(),
+            GreetingError::IncrementThrottled.into()
+        );
+        assert_eq!(account.counter, 1);
+
+        account.increment(1, &clock_at(6)).unwrap();
+        assert_eq!(account.counter, 2);
+        assert_eq!(account.last_greeted_at, clock_at(6).unix_timestamp);
+    }
+
+    #[test]
+    fn increment_by_step_below_the_cap() {
+        let mut account = v3_account();
+
+        account.increment(10, &clock_at(100)).unwrap();
+        assert_eq!(account.counter, 17);
+    }
+
+    #[test]
+    fn increment_may_reach_the_cap_exactly() {
+        let mut account = v3_account();
+        account.counter = GreetingAccount::MAX_COUNTER - 5;
+
+        account.increment(5, &clock_at(100)).unwrap();
+        assert_eq!(account.counter, GreetingAccount::MAX_COUNTER);
+    }
+
+    #[test]
+    fn increment_past_the_cap_is_rejected() {
+        let mut account = v3_account();
+        account.counter = GreetingAccount::MAX_COUNTER - 5;
+
+        assert_eq!(
+            account.increment(6, &clock_at(100)).unwrap_err(),
+            GreetingError::CounterMaximumLimitReached.into()
+        );
+        assert_eq!(
+            account.increment(u32::MAX, &clock_at(100)).unwrap_err(),
+            GreetingError::CounterMaximumLimitReached.into()
+        );
+        assert_eq!(account.counter, GreetingAccount::MAX_COUNTER - 5);
+        assert_eq!(account.last_increment_slot, 42);
+    }
+
+    #[test]
+    fn unpack_legacy_reads_each_older_layout() {
+        let legacy = 7u32.try_to_vec().unwrap();
+        let v1 = [&[1u8][..], &8u32.to_le_bytes()].concat();
+        let v2 = [&[2u8][..], &9u32.to_le_bytes(), &1_700_000_000i64.to_le_bytes()].concat();
+
+        let account = GreetingAccount::unpack_legacy(&legacy).unwrap();
+        assert_eq!((account.version, account.counter, account.last_greeted_at), (0, 7, 0));
+        let account = GreetingAccount::unpack_legacy(&v1).unwrap();
+        assert_eq!((account.version, account.counter, account.last_greeted_at), (1, 8, 0));
+        let account = GreetingAccount::unpack_legacy(&v2).unwrap();
+        assert_eq!((account.version, account.counter, account.last_greeted_at), (2, 9, 1_700_000_000));
+        assert_eq!(account.last_increment_slot, 0);
+    }
+
+    #[test]
+    fn unpack_legacy_rejects_unknown_versions_and_sizes() {
+        let v1_with_wrong_version = [&[5u8][..], &8u32.to_le_bytes()].concat();
+        let v2_with_wrong_version = [&[1u8][..], &9u32.to_le_bytes(), &0i64.to_le_bytes()].concat();
+
+        for data in [
+            v1_with_wrong_version,
+            v2_with_wrong_version,
+            v3_account().try_to_vec().unwrap(),
+            vec![0u8; 3],
+        ] {
+            assert_eq!(
+                GreetingAccount::unpack_legacy(&data).unwrap_err(),
+                ProgramError::InvalidAccountData
+            );
+        }
+    }
 }
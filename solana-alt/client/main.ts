@@ -78,7 +78,8 @@ class ALTTutorial {
 
     // Create instruction to increment the counter by 1
     const incrementInstruction = this.programUtils.incrementCounterInstruction(
-      counterKeypair.publicKey   // The counter account to increment
+      counterKeypair.publicKey,  // The counter account to increment
+      this.payer.publicKey       // The signer recorded as last_modified_by
     );
 
     // Send another legacy transaction to increment the counter
@@ -87,6 +88,13 @@ class ALTTutorial {
     // Read the updated counter value to verify the increment worked
     counterData = await this.programUtils.getCounterData(counterKeypair.publicKey);
     console.log(`📊 Updated count value: ${counterData?.count || 0}`);
+
+    // The program records whoever signed the increment
+    const modifiedBy = counterData?.lastModifiedBy.toBase58();
+    console.log(`👤 Last modified by: ${modifiedBy}`);
+    if (modifiedBy !== this.payer.publicKey.toBase58()) {
+      throw new Error(`Expected last_modified_by to be ${this.payer.publicKey.toBase58()}, got ${modifiedBy}`);
+    }
   }
 
   /**
@@ -162,7 +170,8 @@ class ALTTutorial {
     // Step 2: Batch increment counters
     console.log('\n📈 Step 2: Batch Counter Increments');
     const batchIncrementInstruction = this.programUtils.batchIncrementInstruction(
-      counterKeypairs.map(kp => kp.publicKey)
+      counterKeypairs.map(kp => kp.publicKey),
+      this.payer.publicKey
     );
 
     await this.altManager.sendVersionedTransaction(
@@ -15,13 +15,27 @@ import { serialize, deserialize } from 'borsh';  // Borsh serialization/deserial
  *
  * The discriminator (number) tells the program which instruction to execute:
  * - 0: CreateCounter - Initialize a new counter account
- * - 1: IncrementCounter - Increment a single counter by 1
- * - 2: BatchIncrement - Increment multiple counters (showcases ALT power)
+ * - 1: IncrementCounter - Increment a single counter by 1, recording the signer
+ * - 2: BatchIncrement - Increment multiple counters (showcases ALT power), recording the signer
+ * - 3: CreateManyCounters - Create multiple counters in one instruction
+ * - 4: BudgetedBatchIncrement - Batch increment that stops before compute runs out
+ * - 5: ResetAll - Reset the authority's counters back to 0
+ * - 6: GetVersion - Return the deployed program version
+ * - 7: SumCounters - Sum the counts of all passed counters
+ * - 8: CreateCounterWith - Create a counter starting at a given value
+ * - 9: DistributeCount - Move a fixed amount from one counter into several others
  */
 export enum TutorialInstruction {
   CreateCounter = 0,      // Creates a new counter with initial value 0
   IncrementCounter = 1,   // Increments a single counter by 1
   BatchIncrement = 2,     // Batch increment multiple counters (ALT showcase)
+  CreateManyCounters = 3, // Create multiple counters with a single rent lookup
+  BudgetedBatchIncrement = 4, // Batch increment that stops early when compute runs low
+  ResetAll = 5,           // Reset every counter owned by the signing authority to 0
+  GetVersion = 6,         // Return the program version string
+  SumCounters = 7,        // Sum the counts of all passed counters
+  CreateCounterWith = 8,  // Creates a new counter with a given initial value (u64 LE follows)
+  DistributeCount = 9,    // Moves per_account (u64 LE follows) from a source counter to each destination
 }
 
 /**
@@ -31,18 +45,22 @@ export enum TutorialInstruction {
  * The structure must exactly match the Rust definition:
  * - count: u64 (8 bytes) - the current counter value
  * - authority: Pubkey (32 bytes) - who owns/can modify this counter
+ * - last_modified_by: Pubkey (32 bytes) - who last changed the count
  */
 export class Counter {
   count: number;          // Current count value
   authority: PublicKey;   // Owner of this counter
+  lastModifiedBy: PublicKey; // Signer of the last change
 
-  constructor(fields?: { count?: number; authority?: PublicKey }) {
+  constructor(fields?: { count?: number; authority?: PublicKey; lastModifiedBy?: PublicKey }) {
     if (fields) {
       this.count = fields.count || 0;
       this.authority = fields.authority || PublicKey.default;
+      this.lastModifiedBy = fields.lastModifiedBy || PublicKey.default;
     } else {
       this.count = 0;
       this.authority = PublicKey.default;
+      this.lastModifiedBy = PublicKey.default;
     }
   }
 
@@ -54,12 +72,13 @@ export class Counter {
       fields: [
         ['count', 'u64'],      // 64-bit unsigned integer
         ['authority', [32]],   // 32-byte array (Pubkey)
+        ['lastModifiedBy', [32]], // 32-byte array (Pubkey)
       ],
     }],
   ]);
 
-  // Total space required for this account: 8 bytes (u64) + 32 bytes (Pubkey)
-  static LEN = 8 + 32;
+  // Total space required for this account: 8 bytes (u64) + 32 bytes (Pubkey) + 32 bytes (Pubkey)
+  static LEN = 8 + 32 + 32;
 
   /**
    * Deserialize Counter data from a buffer using Borsh
@@ -75,10 +94,12 @@ export class Counter {
 
       // Convert the authority bytes to PublicKey
       const authority = new PublicKey(decoded.authority);
+      const lastModifiedBy = new PublicKey(decoded.lastModifiedBy);
 
       return new Counter({
         count: Number(decoded.count),
         authority: authority,
+        lastModifiedBy: lastModifiedBy,
       });
     } catch (error) {
       throw new Error(`Failed to deserialize Counter: ${error}`);
@@ -139,11 +160,14 @@ export class ProgramUtils {
    *
    * This instruction tells our program to increment the specified counter by 1.
    * It's used in the basic demo to show traditional single-account operations.
+   * The modifier account is required (breaking change): older clients that only
+   * pass the counter are rejected with NotEnoughAccountKeys.
    *
    * @param counterAccount - The counter account to increment
+   * @param modifier - Signer recorded as the counter's last_modified_by
    * @returns TransactionInstruction ready to be included in a transaction
    */
-  incrementCounterInstruction(counterAccount: PublicKey): TransactionInstruction {
+  incrementCounterInstruction(counterAccount: PublicKey, modifier: PublicKey): TransactionInstruction {
     // Create instruction data: just the instruction discriminator (1 for IncrementCounter)
     const data = Buffer.from([TutorialInstruction.IncrementCounter]);
 
@@ -151,6 +175,8 @@ export class ProgramUtils {
       keys: [
         // Account 0: Counter account (will be modified, no signature required)
         { pubkey: counterAccount, isSigner: false, isWritable: true },
+        // Account 1: Modifier (must sign, recorded as last_modified_by)
+        { pubkey: modifier, isSigner: true, isWritable: false },
       ],
       programId: this.programId,  // Our deployed program
       data,                       // Instruction data (just the discriminator)
@@ -163,22 +189,30 @@ export class ProgramUtils {
    * This is the showcase instruction for ALT! It can increment many counters
    * in a single transaction, demonstrating the power of Address Lookup Tables.
    *
+   * The modifier must be passed first (breaking change): the program records it as
+   * last_modified_by on every counter and rejects the batch if it didn't sign.
+   *
    * @param counterAccounts - Array of counter accounts to increment
+   * @param modifier - Signer recorded as each counter's last_modified_by
    * @returns TransactionInstruction that can handle up to 256 counters with ALT
    */
-  batchIncrementInstruction(counterAccounts: PublicKey[]): TransactionInstruction {
+  batchIncrementInstruction(counterAccounts: PublicKey[], modifier: PublicKey): TransactionInstruction {
     // Create instruction data: just the instruction discriminator (2 for BatchIncrement)
     const data = Buffer.from([TutorialInstruction.BatchIncrement]);
 
     // Map each counter account to the required account meta format
-    const keys = counterAccounts.map(account => ({
-      pubkey: account,           // The counter account address
-      isSigner: false,          // No signature required for existing accounts
-      isWritable: true,         // Will be modified (incremented)
-    }));
+    const keys = [
+      // Account 0: Modifier (must sign, recorded as last_modified_by)
+      { pubkey: modifier, isSigner: true, isWritable: false },
+      ...counterAccounts.map(account => ({
+        pubkey: account,           // The counter account address
+        isSigner: false,          // No signature required for existing accounts
+        isWritable: true,         // Will be modified (incremented)
+      })),
+    ];
 
     return new TransactionInstruction({
-      keys,                     // Array of all counter accounts
+      keys,                     // Modifier followed by all counter accounts
       programId: this.programId, // Our deployed program
       data,                     // Instruction data (just the discriminator)
     });
@@ -221,6 +255,7 @@ export class ProgramUtils {
         // pub struct Counter {
         //     pub count: u64,        // 8 bytes at offset 0
         //     pub authority: Pubkey, // 32 bytes at offset 8
+        //     pub last_modified_by: Pubkey, // 32 bytes at offset 40
         // }
 
         try {
@@ -232,8 +267,11 @@ export class ProgramUtils {
           // slice(8, 40) means: start at byte 8, end before byte 40 (so bytes 8-39 inclusive)
           const authority = new PublicKey(accountInfo.data.slice(8, 40));
 
+          // Read last_modified_by (Pubkey) from bytes 40-71
+          const lastModifiedBy = new PublicKey(accountInfo.data.slice(40, 72));
+
           console.log(`✅ Successfully parsed counter manually: count=${Number(count)}, authority=${authority.toBase58()}`);
-          return new Counter({ count: Number(count), authority });
+          return new Counter({ count: Number(count), authority, lastModifiedBy });
 
         } catch (manualError) {
           console.error('❌ Manual parsing also failed:', manualError);
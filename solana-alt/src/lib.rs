@@ -3,17 +3,21 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint,
+    compute_units::sol_remaining_compute_units,
     entrypoint::ProgramResult,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction,
-    program::invoke,
+    program::{invoke, set_return_data},
     system_program,
     sysvar::Sysvar,
 };
 
+/// Crate version, returned by the GetVersion instruction
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // Define the program entry point - this macro sets up the main function for the Solana program
 entrypoint!(process_instruction);
 
@@ -26,11 +30,51 @@ pub enum TutorialInstruction {
     /// 2. [] system_program
     CreateCounter,
     /// 0. [writable] counter_account
-    /// 1. [] system_program
+    /// 1. [signer] modifier, recorded as last_modified_by
+    ///
+    /// Breaking change: the modifier account is required, so clients that only
+    /// pass the counter fail with NotEnoughAccountKeys
     IncrementCounter,
-    /// 0...n. [writable] counter_accounts
-    /// n+1. [] system_program
+    /// 0. [signer] modifier, recorded as last_modified_by on every counter
+    /// 1. ... [writable] counter_accounts
+    ///
+    /// Breaking change: the modifier now comes first, ahead of the counters
     BatchIncrement,
+    /// 0. [signer, writable] payer
+    /// 1. [] system_program
+    /// 2. [signer, writable] first new counter_account
+    /// 3. ... [signer, writable] further new counter_accounts
+    CreateManyCounters,
+    /// 0. [signer] modifier, recorded as last_modified_by on every counter
+    /// 1. ... [writable] counter_accounts
+    ///
+    /// Like BatchIncrement, but stops early when compute units run low and
+    /// returns the number of counter accounts processed as a little-endian u32.
+    /// Breaking change: the modifier now comes first, ahead of the counters
+    BudgetedBatchIncrement,
+    /// 0. [signer] authority
+    /// 1. ... [writable] counter_accounts
+    ResetAll,
+    /// No accounts; returns the program version via return data
+    GetVersion,
+    /// 0. ... [] counter_accounts
+    ///
+    /// Sums the counts of all program-owned counters and returns the total
+    /// as a little-endian u64
+    SumCounters,
+    /// 0. [signer, writable] payer
+    /// 1. [signer, writable] counter_account
+    /// 2. [] system_program
+    ///
+    /// Like CreateCounter, but the count starts at `initial`
+    CreateCounterWith { initial: u64 },
+    /// 0. [signer] authority of the source counter
+    /// 1. [writable] source counter_account
+    /// 2. ... [writable] destination counter_accounts
+    ///
+    /// Moves `per_account` from the source to each destination, failing if
+    /// the source holds less than `per_account * destinations`
+    DistributeCount { per_account: u64 },
 }
 
 // Counter data structure that will be stored on-chain
@@ -41,11 +85,85 @@ pub struct Counter {
     pub count: u64,
     /// The authority (owner) of this counter - who can modify it
     pub authority: Pubkey,
+    /// The signer of the last instruction that changed the count
+    pub last_modified_by: Pubkey,
 }
 
 impl Counter {
-    /// Total space required for this account: 8 bytes (u64) + 32 bytes (Pubkey)
-    pub const LEN: usize = 8 + 32;
+    /// Total space required for this account: 8 bytes (u64) + 32 bytes (Pubkey) + 32 bytes (Pubkey)
+    pub const LEN: usize = 8 + 32 + 32;
+
+    /// Size of counters created before last_modified_by was added
+    pub const LEGACY_LEN: usize = 8 + 32;
+
+    /// Reads the counter stored in `account`, rejecting accounts with the old layout
+    /// or too little data with a clear error instead of borsh's opaque one
+    pub fn unpack(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data_len = account.data_len();
+        if data_len == Self::LEGACY_LEN {
+            msg!(
+                "Counter account {} uses the old {}-byte layout without last_modified_by",
+                account.key,
+                Self::LEGACY_LEN
+            );
+            return Err(CounterError::LegacyAccount.into());
+        }
+        if data_len < Self::LEN {
+            msg!(
+                "Counter account {} holds {} bytes, expected at least {}",
+                account.key,
+                data_len,
+                Self::LEN
+            );
+            return Err(CounterError::InvalidAccountData.into());
+        }
+
+        Ok(Self::try_from_slice(&account.data.borrow())?)
+    }
+
+    /// Adds 1 to the count and records `modifier` as last_modified_by,
+    /// failing with ArithmeticOverflow instead of wrapping at u64::MAX
+    pub fn increment(&mut self, modifier: Pubkey) -> ProgramResult {
+        self.count = self
+            .count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.last_modified_by = modifier;
+        Ok(())
+    }
+
+    /// Whether the given account may modify this counter: it must be the
+    /// stored authority and must have signed the transaction
+    pub fn is_authorized(&self, authority: &AccountInfo) -> bool {
+        authority.is_signer && *authority.key == self.authority
+    }
+}
+
+/// Custom errors returned by this program
+#[derive(thiserror::Error, Debug, Copy, Clone)]
+pub enum CounterError {
+    /// The account is too small to hold a Counter, e.g. a wrong account was passed
+    #[error("Invalid counter account data")]
+    InvalidAccountData,
+
+    /// The account was created before last_modified_by was added and is too small
+    /// for the current Counter layout
+    #[error("Legacy counter account")]
+    LegacyAccount,
+
+    /// The source counter holds less than the amount being moved out of it
+    #[error("Insufficient count")]
+    InsufficientCount,
+
+    /// The signer is not the authority of the counter it is trying to change
+    #[error("Signer is not the counter's authority")]
+    Unauthorized,
+}
+
+impl From<CounterError> for ProgramError {
+    fn from(e: CounterError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
 }
 
 /// Main instruction processing function - the heart of our Solana program
@@ -68,13 +186,26 @@ pub fn process_instruction(
 
     // Route to the appropriate handler function based on instruction type
     match instruction {
-        TutorialInstruction::CreateCounter => create_counter(program_id, accounts),
+        TutorialInstruction::CreateCounter => create_counter(program_id, accounts, 0),
         TutorialInstruction::IncrementCounter => increment_counter(program_id, accounts),
         TutorialInstruction::BatchIncrement => batch_increment(program_id, accounts),
+        TutorialInstruction::CreateManyCounters => create_many_counters(program_id, accounts),
+        TutorialInstruction::BudgetedBatchIncrement => {
+            budgeted_batch_increment(program_id, accounts)
+        }
+        TutorialInstruction::ResetAll => reset_all(program_id, accounts),
+        TutorialInstruction::GetVersion => get_version(),
+        TutorialInstruction::SumCounters => sum_counters(program_id, accounts),
+        TutorialInstruction::CreateCounterWith { initial } => {
+            create_counter(program_id, accounts, initial)
+        }
+        TutorialInstruction::DistributeCount { per_account } => {
+            distribute_count(program_id, accounts, per_account)
+        }
     }
 }
 
-/// Creates a new counter account with initial value of 0
+/// Creates a new counter account starting at `initial` (0 for CreateCounter)
 /// This function demonstrates basic account creation in Solana
 ///
 /// # Expected Accounts
@@ -84,7 +215,7 @@ pub fn process_instruction(
 ///
 /// # Returns
 /// * `ProgramResult` - Success or error result
-fn create_counter(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn create_counter(program_id: &Pubkey, accounts: &[AccountInfo], initial: u64) -> ProgramResult {
     // Create an iterator to safely access accounts in order
     let account_info_iter = &mut accounts.iter();
     let payer = next_account_info(account_info_iter)?;
@@ -123,17 +254,32 @@ fn create_counter(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResul
         &[payer.clone(), counter_account.clone(), system_program.clone()],
     )?;
 
-    // Initialize the counter data structure with default values
+    // Initialize the counter data structure with the payer as its authority
+    initialize_counter(counter_account, payer.key, initial)?;
+
+    msg!("Counter created successfully with initial value: {}", initial);
+    Ok(())
+}
+
+/// Writes a fresh counter into a newly created account
+///
+/// # Arguments
+/// * `counter_account` - The new counter account, already sized for Counter::LEN
+/// * `authority` - The payer, stored as both authority and last_modified_by
+/// * `initial` - The starting count
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result
+fn initialize_counter(counter_account: &AccountInfo, authority: &Pubkey, initial: u64) -> ProgramResult {
     let counter = Counter {
-        count: 0,              // Start counting from 0
-        authority: *payer.key, // Set the payer as the authority
+        count: initial,               // Start counting from the requested value
+        authority: *authority,        // Set the payer as the authority
+        last_modified_by: *authority, // The payer set the initial value
     };
 
     // Serialize and store the counter data in the account
     let mut data = counter_account.data.borrow_mut();
     counter.serialize(&mut &mut data[..])?;
-
-    msg!("Counter created successfully with initial value: 0");
     Ok(())
 }
 
@@ -142,6 +288,7 @@ fn create_counter(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResul
 ///
 /// # Expected Accounts
 /// 0. [writable] counter_account - The counter account to increment
+/// 1. [signer] modifier - Anyone may increment; their key is recorded as last_modified_by
 ///
 /// # Returns
 /// * `ProgramResult` - Success or error result
@@ -149,6 +296,12 @@ fn increment_counter(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
     // Get the counter account from the accounts array
     let account_info_iter = &mut accounts.iter();
     let counter_account = next_account_info(account_info_iter)?;
+    let modifier = next_account_info(account_info_iter)?;
+
+    // Security check: The modifier must sign so last_modified_by can't be forged
+    if !modifier.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
     // Security check: Verify that our program owns this account
     // This prevents other programs from modifying our data
@@ -156,14 +309,12 @@ fn increment_counter(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    // Read the current counter data from the account
-    // We borrow immutably first to read the data
-    let data = counter_account.data.borrow();
-    let mut counter = Counter::try_from_slice(&data)?;
-    drop(data); // Explicitly drop the immutable borrow before mutable borrow
+    // Read the current counter data from the account, rejecting old-layout or
+    // undersized accounts up front
+    let mut counter = Counter::unpack(counter_account)?;
 
-    // Increment the counter value
-    counter.count += 1;
+    // Increment the counter value and record who did it
+    counter.increment(*modifier.key)?;
 
     // Write the updated data back to the account
     // Now we borrow mutably to write the data
@@ -184,15 +335,25 @@ fn increment_counter(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
 /// that would otherwise require multiple transactions.
 ///
 /// # Expected Accounts
-/// 0...n. [writable] counter_accounts - Array of counter accounts to increment
+/// 0. [signer] modifier - Recorded as last_modified_by on every counter
+/// 1. ... [writable] counter_accounts - Array of counter accounts to increment
 ///
 /// # Returns
 /// * `ProgramResult` - Success or error result
 fn batch_increment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
-    msg!("Starting batch increment of {} counters", accounts.len());
+    let account_info_iter = &mut accounts.iter();
+    let modifier = next_account_info(account_info_iter)?;
+    let counter_accounts = account_info_iter.as_slice();
+
+    // Security check: The modifier must sign so last_modified_by can't be forged
+    if !modifier.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    msg!("Starting batch increment of {} counters", counter_accounts.len());
 
     // Iterate through all provided counter accounts
-    for (index, counter_account) in accounts.iter().enumerate() {
+    for (index, counter_account) in counter_accounts.iter().enumerate() {
         // Security check: Verify that our program owns this account
         // Skip invalid accounts instead of failing the entire transaction
         if counter_account.owner != program_id {
@@ -201,12 +362,10 @@ fn batch_increment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
         }
 
         // Read the current counter data
-        let data = counter_account.data.borrow();
-        let mut counter = Counter::try_from_slice(&data)?;
-        drop(data); // Release immutable borrow
+        let mut counter = Counter::unpack(counter_account)?;
 
-        // Increment the counter
-        counter.count += 1;
+        // Increment the counter and record who did it
+        counter.increment(*modifier.key)?;
 
         // Write the updated data back
         let mut data = counter_account.data.borrow_mut();
@@ -218,3 +377,889 @@ fn batch_increment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
     msg!("Batch operation completed successfully!");
     Ok(())
 }
+
+/// Creates several counter accounts in a single instruction
+/// The Rent sysvar is fetched once and reused for every account, which is cheaper
+/// than sending one CreateCounter instruction per counter.
+///
+/// # Expected Accounts
+/// 0. [signer, writable] payer - Account that pays for the transaction and rent
+/// 1. [] system_program - Solana's system program for account creation
+/// 2. [signer, writable] counter_account - First new, empty counter account to be created
+/// 3. ... [signer, writable] counter_accounts - Any number of further new counter accounts
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result
+fn create_many_counters(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let counter_accounts = account_info_iter.as_slice();
+
+    // Security check: Ensure the payer has signed this transaction
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Security check: Verify that the system program is actually the system program
+    if *system_program.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if counter_accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // Fetch rent once for the whole batch - every counter has the same size
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(Counter::LEN);
+
+    msg!("Creating {} counters", counter_accounts.len());
+
+    for (index, counter_account) in counter_accounts.iter().enumerate() {
+        // Security check: Each new account must sign its own creation
+        if !counter_account.is_signer {
+            msg!("Counter account at index {} is not a signer", index);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Security check: Refuse to touch accounts that already hold data or belong to a program
+        if !counter_account.data_is_empty() || *counter_account.owner != system_program::id() {
+            msg!("Counter account at index {} already exists", index);
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        invoke(
+            &system_instruction::create_account(
+                payer.key,
+                counter_account.key,
+                lamports,
+                Counter::LEN as u64,
+                program_id,
+            ),
+            &[payer.clone(), counter_account.clone(), system_program.clone()],
+        )?;
+
+        initialize_counter(counter_account, payer.key, 0)?;
+
+        msg!("Counter {} created: {}", index, counter_account.key);
+    }
+
+    msg!("Batch creation completed successfully!");
+    Ok(())
+}
+
+/// Compute units kept in reserve before starting on another counter.
+/// One increment (borsh round trip plus a log line) costs well under this, and the
+/// remainder leaves room to log the summary and set the return data.
+/// It is also the per-counter ceiling the tests hold BatchIncrement to: a full
+/// lookup table of 256 counters at 5,000 units each needs 1,280,000 units, which
+/// still fits the 1,400,000-unit transaction maximum.
+pub const COMPUTE_UNITS_PER_COUNTER: u64 = 5_000;
+
+/// Batch increment that finishes gracefully instead of running out of compute
+/// Before each counter the remaining compute budget is checked; if it drops below
+/// COMPUTE_UNITS_PER_COUNTER the loop stops and the caller can resume from the
+/// returned index in a follow-up transaction. Foreign, legacy, and undersized
+/// accounts are skipped with a log line, so the returned index stays meaningful.
+///
+/// # Expected Accounts
+/// 0. [signer] modifier - Recorded as last_modified_by on every counter
+/// 1. ... [writable] counter_accounts - Array of counter accounts to increment
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result; the number of counter accounts
+///   processed (incremented or skipped) is set as return data
+fn budgeted_batch_increment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let modifier = next_account_info(account_info_iter)?;
+    let counter_accounts = account_info_iter.as_slice();
+
+    // Security check: The modifier must sign so last_modified_by can't be forged
+    if !modifier.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    msg!("Starting budgeted batch increment of {} counters", counter_accounts.len());
+
+    let mut processed: u32 = 0;
+
+    for (index, counter_account) in counter_accounts.iter().enumerate() {
+        // Stop before the budget runs out rather than failing mid-loop
+        let remaining = sol_remaining_compute_units();
+        if remaining < COMPUTE_UNITS_PER_COUNTER {
+            msg!("Compute budget low ({} units left), stopping at index {}", remaining, index);
+            break;
+        }
+
+        processed += 1;
+
+        // Security check: Verify that our program owns this account
+        if counter_account.owner != program_id {
+            msg!("Skipping invalid account at index {}", index);
+            continue;
+        }
+
+        // Skip legacy or undersized counters so one bad account doesn't abort the batch
+        let mut counter = match Counter::unpack(counter_account) {
+            Ok(counter) => counter,
+            Err(err) => {
+                msg!("Skipping unreadable counter at index {}: {}", index, err);
+                continue;
+            }
+        };
+
+        counter.increment(*modifier.key)?;
+
+        let mut data = counter_account.data.borrow_mut();
+        counter.serialize(&mut &mut data[..])?;
+    }
+
+    msg!("Processed {} of {} counters", processed, counter_accounts.len());
+    set_return_data(&processed.to_le_bytes());
+    Ok(())
+}
+
+/// Resets every counter the signer is the authority of back to 0
+/// The inverse of BatchIncrement: counters that are not owned by this program, can't
+/// be read (legacy or undersized), or belong to a different authority are skipped
+/// with a warning instead of failing.
+///
+/// # Expected Accounts
+/// 0. [signer] authority - The authority whose counters should be reset
+/// 1. ... [writable] counter_accounts - Array of counter accounts to reset
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result
+fn reset_all(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let counter_accounts = account_info_iter.as_slice();
+
+    // Security check: Ensure the authority has signed this transaction
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    msg!("Resetting {} counters for {}", counter_accounts.len(), authority.key);
+
+    let mut reset = 0;
+    for (index, counter_account) in counter_accounts.iter().enumerate() {
+        // Security check: Verify that our program owns this account
+        if counter_account.owner != program_id {
+            msg!("Skipping invalid account at index {}", index);
+            continue;
+        }
+
+        // Skip legacy or undersized counters so one bad account doesn't abort the reset
+        let mut counter = match Counter::unpack(counter_account) {
+            Ok(counter) => counter,
+            Err(err) => {
+                msg!("Skipping unreadable counter at index {}: {}", index, err);
+                continue;
+            }
+        };
+
+        // Only reset counters that belong to the signer
+        if !counter.is_authorized(authority) {
+            msg!("Warning: not the authority of counter at index {}, skipping", index);
+            continue;
+        }
+
+        counter.count = 0;
+        counter.last_modified_by = *authority.key;
+
+        let mut data = counter_account.data.borrow_mut();
+        counter.serialize(&mut &mut data[..])?;
+        reset += 1;
+    }
+
+    msg!("Reset {} of {} counters", reset, counter_accounts.len());
+    Ok(())
+}
+
+/// Returns the program version so clients can check which build is deployed
+///
+/// # Expected Accounts
+/// None
+///
+/// # Returns
+/// * `ProgramResult` - Success; the UTF-8 version string is set as return data
+fn get_version() -> ProgramResult {
+    msg!("Program version: {}", VERSION);
+    set_return_data(VERSION.as_bytes());
+    Ok(())
+}
+
+/// Adds up the counts of every counter passed in
+/// A read-only aggregate that pairs with BatchIncrement: counters that are not
+/// owned by this program are skipped with a warning instead of failing.
+///
+/// # Expected Accounts
+/// 0. ... [] counter_accounts - Array of counter accounts to sum
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result (ArithmeticOverflow if the total
+///   exceeds u64::MAX); the total is set as return data
+fn sum_counters(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let mut total: u64 = 0;
+    let mut summed = 0;
+
+    for (index, counter_account) in accounts.iter().enumerate() {
+        // Security check: Verify that our program owns this account
+        if counter_account.owner != program_id {
+            msg!("Warning: skipping foreign account at index {}", index);
+            continue;
+        }
+
+        let counter = Counter::unpack(counter_account)?;
+        total = total
+            .checked_add(counter.count)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        summed += 1;
+    }
+
+    msg!("Sum of {} of {} counters: {}", summed, accounts.len(), total);
+    set_return_data(&total.to_le_bytes());
+    Ok(())
+}
+
+/// Moves `per_account` from a source counter into each destination counter
+/// The source is debited in full before any destination is credited, and any
+/// failure reverts the whole instruction, so the total count is conserved.
+///
+/// # Expected Accounts
+/// 0. [signer] authority - The authority of the source counter
+/// 1. [writable] source_account - The counter to take the count from
+/// 2. ... [writable] destination_accounts - Counters that each receive `per_account`
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result (InsufficientCount if the source
+///   holds less than `per_account` times the number of destinations, Unauthorized
+///   if the signer is not the source's authority)
+fn distribute_count(program_id: &Pubkey, accounts: &[AccountInfo], per_account: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let destination_accounts = account_info_iter.as_slice();
+
+    if destination_accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // Security check: Verify that our program owns the source
+    if source_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Security check: Only the source's authority may move its count away
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut source = Counter::unpack(source_account)?;
+    if *authority.key != source.authority {
+        msg!("{} is not the authority of the source counter", authority.key);
+        return Err(CounterError::Unauthorized.into());
+    }
+
+    let total = per_account
+        .checked_mul(destination_accounts.len() as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    source.count = source.count.checked_sub(total).ok_or_else(|| {
+        msg!("Source holds {}, cannot distribute {}", source.count, total);
+        CounterError::InsufficientCount
+    })?;
+    source.last_modified_by = *authority.key;
+    source.serialize(&mut &mut source_account.data.borrow_mut()[..])?;
+
+    for (index, destination_account) in destination_accounts.iter().enumerate() {
+        // Security check: Destinations must be our counters, and not the source itself
+        if destination_account.owner != program_id {
+            msg!("Destination at index {} is not a counter", index);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if destination_account.key == source_account.key {
+            msg!("Destination at index {} is the source counter", index);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut destination = Counter::unpack(destination_account)?;
+        destination.count = destination
+            .count
+            .checked_add(per_account)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        destination.last_modified_by = *authority.key;
+        destination.serialize(&mut &mut destination_account.data.borrow_mut()[..])?;
+
+        msg!("Counter {} increased to {}", index, destination.count);
+    }
+
+    msg!(
+        "Distributed {} to each of {} counters, source now {}",
+        per_account,
+        destination_accounts.len(),
+        source.count
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        entrypoint::{deserialize, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        instruction::Instruction,
+        program::get_return_data,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        sync::Once,
+    };
+
+    #[test]
+    fn counter_is_created_with_initial_value_and_payer_authority() {
+        let (key, payer, program_id) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = 0;
+        let mut data = vec![0u8; Counter::LEN];
+        let counter_account = AccountInfo::new(&key, true, true, &mut lamports, &mut data, &program_id, false, 0);
+
+        initialize_counter(&counter_account, &payer, 42).unwrap();
+
+        let counter = Counter::unpack(&counter_account).unwrap();
+        assert_eq!(counter.count, 42);
+        assert_eq!(counter.authority, payer);
+        assert_eq!(counter.last_modified_by, payer);
+    }
+
+    #[test]
+    fn increment_rejects_overflow_at_max_count() {
+        let mut counter = Counter {
+            count: u64::MAX,
+            authority: Pubkey::new_unique(),
+            last_modified_by: Pubkey::default(),
+        };
+
+        assert_eq!(counter.increment(Pubkey::new_unique()), Err(ProgramError::ArithmeticOverflow));
+        assert_eq!(counter.count, u64::MAX);
+        assert_eq!(counter.last_modified_by, Pubkey::default());
+    }
+
+    #[test]
+    fn increment_counter_at_max_count_fails_instead_of_wrapping() {
+        let (key, modifier_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = 0;
+        let mut data = vec![0u8; Counter::LEN];
+        let counter_account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        let mut modifier_lamports = 0;
+        let mut modifier_data = vec![];
+        let modifier = AccountInfo::new(&modifier_key, true, false, &mut modifier_lamports, &mut modifier_data, &program_id, false, 0);
+        initialize_counter(&counter_account, &modifier_key, u64::MAX).unwrap();
+
+        assert_eq!(
+            increment_counter(&program_id, &[counter_account.clone(), modifier]),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(Counter::unpack(&counter_account).unwrap().count, u64::MAX);
+    }
+
+    #[test]
+    fn batch_increment_records_the_signing_modifier() {
+        let (modifier_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (first_key, second_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut modifier_lamports = 0;
+        let mut modifier_data = vec![];
+        let modifier = AccountInfo::new(&modifier_key, true, false, &mut modifier_lamports, &mut modifier_data, &program_id, false, 0);
+        let (mut first_lamports, mut second_lamports) = (0, 0);
+        let (mut first_data, mut second_data) = (vec![0u8; Counter::LEN], vec![0u8; Counter::LEN]);
+        let first = AccountInfo::new(&first_key, false, true, &mut first_lamports, &mut first_data, &program_id, false, 0);
+        let second = AccountInfo::new(&second_key, false, true, &mut second_lamports, &mut second_data, &program_id, false, 0);
+        let authority = Pubkey::new_unique();
+        initialize_counter(&first, &authority, 0).unwrap();
+        initialize_counter(&second, &authority, 7).unwrap();
+
+        batch_increment(&program_id, &[modifier, first.clone(), second.clone()]).unwrap();
+
+        let (first, second) = (Counter::unpack(&first).unwrap(), Counter::unpack(&second).unwrap());
+        assert_eq!((first.count, second.count), (1, 8));
+        assert_eq!(first.last_modified_by, modifier_key);
+        assert_eq!(second.last_modified_by, modifier_key);
+        assert_eq!(first.authority, authority);
+    }
+
+    #[test]
+    fn batch_increment_requires_a_signing_modifier() {
+        let (modifier_key, counter_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut modifier_lamports = 0;
+        let mut modifier_data = vec![];
+        let modifier = AccountInfo::new(&modifier_key, false, false, &mut modifier_lamports, &mut modifier_data, &program_id, false, 0);
+        let mut lamports = 0;
+        let mut data = vec![0u8; Counter::LEN];
+        let counter_account = AccountInfo::new(&counter_key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+        initialize_counter(&counter_account, &modifier_key, 3).unwrap();
+
+        assert_eq!(
+            batch_increment(&program_id, &[modifier, counter_account.clone()]),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(Counter::unpack(&counter_account).unwrap().count, 3);
+    }
+
+    // Units the runtime charges per syscall; a msg! costs this or one unit per byte,
+    // whichever is larger
+    const SYSCALL_BASE_UNITS: u64 = 100;
+
+    thread_local! {
+        // Compute units left in the current test's simulated transaction
+        static COMPUTE_UNITS: Cell<u64> = const { Cell::new(u64::MAX) };
+        // Bytes passed to the last set_return_data call
+        static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Syscall stubs backed by thread-local state, so tests running in parallel don't
+    // share a compute budget or return data. Logging and sol_remaining_compute_units
+    // are charged against COMPUTE_UNITS like the runtime's compute meter.
+    struct TestStubs;
+
+    impl SyscallStubs for TestStubs {
+        fn sol_log(&self, message: &str) {
+            charge(SYSCALL_BASE_UNITS.max(message.len() as u64));
+            println!("Program log: {}", message);
+        }
+
+        fn sol_remaining_compute_units(&self) -> u64 {
+            charge(SYSCALL_BASE_UNITS);
+            COMPUTE_UNITS.with(Cell::get)
+        }
+
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|return_data| Some((Pubkey::default(), return_data.borrow().clone())))
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        // Stands in for the system program's CreateAccount, the only CPI this program makes
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let Ok(SystemInstruction::CreateAccount { lamports, space, owner }) =
+                limited_deserialize(&instruction.data, 1_232)
+            else {
+                return Err(ProgramError::InvalidInstructionData);
+            };
+            let find = |index: usize| {
+                account_infos
+                    .iter()
+                    .find(|account| *account.key == instruction.accounts[index].pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)
+            };
+            let (from, to) = (find(0)?, find(1)?);
+
+            **from.try_borrow_mut_lamports()? -= lamports;
+            **to.try_borrow_mut_lamports()? += lamports;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+            Ok(())
+        }
+    }
+
+    // Lays accounts out the way the runtime serializes program input, so the
+    // AccountInfos from entrypoint::deserialize can be resized and reassigned.
+    // Each account is (key, is_signer, lamports, data, owner), all writable.
+    fn serialize_input(accounts: &[(Pubkey, bool, u64, Vec<u8>, Pubkey)]) -> Vec<u64> {
+        let mut input = (accounts.len() as u64).to_le_bytes().to_vec();
+        for (key, is_signer, lamports, data, owner) in accounts {
+            input.extend_from_slice(&[NON_DUP_MARKER, *is_signer as u8, 1, 0]);
+            input.extend_from_slice(&[0; 4]); // original data length, set by deserialize
+            input.extend_from_slice(key.as_ref());
+            input.extend_from_slice(owner.as_ref());
+            input.extend_from_slice(&lamports.to_le_bytes());
+            input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            input.extend_from_slice(data);
+            input.resize((input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+        }
+        input.extend_from_slice(&0u64.to_le_bytes()); // no instruction data
+        input.extend_from_slice(Pubkey::default().as_ref()); // program id, unused
+
+        // Copy into a u64 buffer so the header fields are aligned
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        for (word, bytes) in aligned.iter_mut().zip(input.chunks(8)) {
+            let mut padded = [0u8; 8];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(padded);
+        }
+        aligned
+    }
+
+    fn charge(units: u64) {
+        COMPUTE_UNITS.with(|remaining| remaining.set(remaining.get().saturating_sub(units)));
+    }
+
+    // Installs TestStubs once per test binary and starts this test's transaction
+    // with `compute_units` to spend
+    fn reset_stubs(compute_units: u64) {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+        COMPUTE_UNITS.with(|remaining| remaining.set(compute_units));
+        RETURN_DATA.with(|return_data| return_data.borrow_mut().clear());
+    }
+
+    fn return_data() -> Vec<u8> {
+        get_return_data().map(|(_, data)| data).unwrap_or_default()
+    }
+
+    #[test]
+    fn budgeted_batch_increment_skips_legacy_counters() {
+        reset_stubs(u64::MAX);
+
+        let (modifier_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (legacy_key, counter_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut modifier_lamports = 0;
+        let mut modifier_data = vec![];
+        let modifier = AccountInfo::new(&modifier_key, true, false, &mut modifier_lamports, &mut modifier_data, &program_id, false, 0);
+        let (mut legacy_lamports, mut counter_lamports) = (0, 0);
+        let mut legacy_data = vec![1u8; Counter::LEGACY_LEN];
+        let mut counter_data = vec![0u8; Counter::LEN];
+        let legacy = AccountInfo::new(&legacy_key, false, true, &mut legacy_lamports, &mut legacy_data, &program_id, false, 0);
+        let counter_account = AccountInfo::new(&counter_key, false, true, &mut counter_lamports, &mut counter_data, &program_id, false, 0);
+        initialize_counter(&counter_account, &modifier_key, 5).unwrap();
+
+        assert_eq!(
+            budgeted_batch_increment(&program_id, &[modifier, legacy.clone(), counter_account.clone()]),
+            Ok(())
+        );
+        assert_eq!(Counter::unpack(&counter_account).unwrap().count, 6);
+        assert!(legacy.data.borrow().iter().all(|b| *b == 1));
+        assert_eq!(return_data(), 2u32.to_le_bytes());
+    }
+
+    // Calls `f` with a signing modifier followed by `count` zeroed counters owned by `program_id`
+    fn with_counters<R>(program_id: &Pubkey, count: usize, f: impl FnOnce(&[AccountInfo]) -> R) -> R {
+        let modifier_key = Pubkey::new_unique();
+        let keys: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+        let mut modifier_lamports = 0;
+        let mut modifier_data = vec![];
+        let mut lamports = vec![0u64; count];
+        let mut data = vec![vec![0u8; Counter::LEN]; count];
+        let modifier = AccountInfo::new(&modifier_key, true, false, &mut modifier_lamports, &mut modifier_data, program_id, false, 0);
+        let counters = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| AccountInfo::new(key, false, true, lamports, data, program_id, false, 0));
+        let accounts: Vec<AccountInfo> = std::iter::once(modifier).chain(counters).collect();
+        for counter_account in &accounts[1..] {
+            initialize_counter(counter_account, &modifier_key, 0).unwrap();
+        }
+        f(&accounts)
+    }
+
+    fn counts(counters: &[AccountInfo]) -> Vec<u64> {
+        counters.iter().map(|c| Counter::unpack(c).unwrap().count).collect()
+    }
+
+    #[test]
+    fn budgeted_batch_increment_stops_early_when_budget_runs_low() {
+        // Enough for the opening log line and three budget checks that each leave
+        // at least COMPUTE_UNITS_PER_COUNTER; the fourth check comes up short
+        reset_stubs(SYSCALL_BASE_UNITS + 3 * SYSCALL_BASE_UNITS + COMPUTE_UNITS_PER_COUNTER);
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 10, |accounts| {
+            assert_eq!(budgeted_batch_increment(&program_id, accounts), Ok(()));
+
+            assert_eq!(return_data(), 3u32.to_le_bytes());
+            assert_eq!(counts(&accounts[1..]), [1, 1, 1, 0, 0, 0, 0, 0, 0, 0]);
+        });
+    }
+
+    // The largest compute budget a transaction can request
+    const MAX_TRANSACTION_COMPUTE_UNITS: u64 = 1_400_000;
+
+    // Compute units BatchIncrement is charged for `count` counters. The stubs meter
+    // syscalls (log lines and budget checks), which dominate the per-counter cost, so
+    // these numbers track the handler's own work rather than the full runtime cost.
+    fn batch_increment_units(count: usize) -> u64 {
+        reset_stubs(u64::MAX);
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, count, |accounts| {
+            batch_increment(&program_id, accounts).unwrap();
+            assert!(counts(&accounts[1..]).iter().all(|count| *count == 1));
+        });
+        u64::MAX - COMPUTE_UNITS.with(Cell::get)
+    }
+
+    #[test]
+    fn batch_increment_cost_per_counter_stays_under_threshold() {
+        // Units spent outside the loop, on the opening and closing log lines
+        let fixed = batch_increment_units(0);
+        let single = batch_increment_units(1) - fixed;
+
+        // A full lookup table of 256 counters must fit the largest transaction budget,
+        // which leaves COMPUTE_UNITS_PER_COUNTER per counter. Checking growing batches
+        // also catches per-counter work that scales with the batch size.
+        for count in [1, 8, 64, 256] {
+            let total = batch_increment_units(count);
+            let per_counter = (total - fixed) / count as u64;
+
+            assert!(
+                per_counter <= COMPUTE_UNITS_PER_COUNTER,
+                "{} counters cost {} units each, over the {} threshold",
+                count,
+                per_counter,
+                COMPUTE_UNITS_PER_COUNTER
+            );
+            assert_eq!(per_counter, single, "per-counter cost grew with {} counters", count);
+            assert!(total <= MAX_TRANSACTION_COMPUTE_UNITS);
+        }
+    }
+
+    #[test]
+    fn budgeted_batch_increment_processes_a_full_lookup_table_within_the_max_budget() {
+        reset_stubs(MAX_TRANSACTION_COMPUTE_UNITS);
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 256, |accounts| {
+            assert_eq!(budgeted_batch_increment(&program_id, accounts), Ok(()));
+
+            assert_eq!(return_data(), 256u32.to_le_bytes());
+            assert!(counts(&accounts[1..]).iter().all(|count| *count == 1));
+        });
+    }
+
+    fn set_count(counter_account: &AccountInfo, count: u64) {
+        let mut counter = Counter::unpack(counter_account).unwrap();
+        counter.count = count;
+        counter.serialize(&mut &mut counter_account.data.borrow_mut()[..]).unwrap();
+    }
+
+    #[test]
+    fn distribute_count_splits_the_source_evenly() {
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 4, |accounts| {
+            set_count(&accounts[1], 30);
+
+            assert_eq!(distribute_count(&program_id, accounts, 10), Ok(()));
+            assert_eq!(counts(&accounts[1..]), [0, 10, 10, 10]);
+        });
+    }
+
+    #[test]
+    fn distribute_count_leaves_the_remainder_on_the_source() {
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 4, |accounts| {
+            set_count(&accounts[1], 31);
+            set_count(&accounts[2], 5);
+
+            assert_eq!(distribute_count(&program_id, accounts, 10), Ok(()));
+            assert_eq!(counts(&accounts[1..]), [1, 15, 10, 10]);
+
+            // Too little left for another round: nothing moves
+            assert_eq!(
+                distribute_count(&program_id, accounts, 1),
+                Err(CounterError::InsufficientCount.into())
+            );
+            assert_eq!(counts(&accounts[1..]), [1, 15, 10, 10]);
+        });
+    }
+
+    #[test]
+    fn distribute_count_rejects_a_signer_that_is_not_the_authority() {
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 4, |accounts| {
+            set_count(&accounts[1], 30);
+
+            // Any key other than the source's authority, even one that signed
+            let mut other = accounts[4].clone();
+            other.is_signer = true;
+            let with_other = [other, accounts[1].clone(), accounts[2].clone(), accounts[3].clone()];
+            assert_eq!(
+                distribute_count(&program_id, &with_other, 10),
+                Err(CounterError::Unauthorized.into())
+            );
+
+            // The real authority has to sign
+            let mut unsigned = accounts[..4].to_vec();
+            unsigned[0].is_signer = false;
+            assert_eq!(
+                distribute_count(&program_id, &unsigned, 10),
+                Err(ProgramError::MissingRequiredSignature)
+            );
+            assert_eq!(counts(&accounts[1..]), [30, 0, 0, 0]);
+        });
+    }
+
+    #[test]
+    fn reset_all_zeroes_every_counter_of_the_authority() {
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 3, |accounts| {
+            batch_increment(&program_id, accounts).unwrap();
+            set_count(&accounts[3], 9);
+
+            assert_eq!(reset_all(&program_id, accounts), Ok(()));
+            assert_eq!(counts(&accounts[1..]), [0, 0, 0]);
+        });
+    }
+
+    #[test]
+    fn reset_all_skips_unreadable_counters() {
+        let (authority_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (legacy_key, short_key, counter_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut authority_lamports = 0;
+        let mut authority_data = vec![];
+        let authority = AccountInfo::new(&authority_key, true, false, &mut authority_lamports, &mut authority_data, &program_id, false, 0);
+        let (mut legacy_lamports, mut short_lamports, mut counter_lamports) = (0, 0, 0);
+        let mut legacy_data = vec![1u8; Counter::LEGACY_LEN];
+        let mut short_data = vec![1u8; 3];
+        let mut counter_data = vec![0u8; Counter::LEN];
+        let legacy = AccountInfo::new(&legacy_key, false, true, &mut legacy_lamports, &mut legacy_data, &program_id, false, 0);
+        let short = AccountInfo::new(&short_key, false, true, &mut short_lamports, &mut short_data, &program_id, false, 0);
+        let counter_account = AccountInfo::new(&counter_key, false, true, &mut counter_lamports, &mut counter_data, &program_id, false, 0);
+        initialize_counter(&counter_account, &authority_key, 4).unwrap();
+
+        assert_eq!(
+            reset_all(&program_id, &[authority, legacy.clone(), short.clone(), counter_account.clone()]),
+            Ok(())
+        );
+        assert_eq!(Counter::unpack(&counter_account).unwrap().count, 0);
+        assert!(legacy.data.borrow().iter().all(|b| *b == 1));
+        assert!(short.data.borrow().iter().all(|b| *b == 1));
+    }
+
+    #[test]
+    fn create_many_counters_creates_each_counter_at_zero() {
+        reset_stubs(u64::MAX);
+        let (payer_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let counter_keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut input_accounts = vec![
+            (payer_key, true, 1_000_000_000, vec![], system_program::id()),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ];
+        input_accounts.extend(counter_keys.iter().map(|key| (*key, true, 0, vec![], system_program::id())));
+        let mut input = serialize_input(&input_accounts);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        assert_eq!(create_many_counters(&program_id, &accounts), Ok(()));
+
+        let rent_exempt = Rent::default().minimum_balance(Counter::LEN);
+        for counter_account in &accounts[2..] {
+            assert_eq!(counter_account.owner, &program_id);
+            assert_eq!(counter_account.lamports(), rent_exempt);
+            let counter = Counter::unpack(counter_account).unwrap();
+            assert_eq!(counter.count, 0);
+            assert_eq!(counter.authority, payer_key);
+        }
+        assert_eq!(accounts[0].lamports(), 1_000_000_000 - 3 * rent_exempt);
+    }
+
+    #[test]
+    fn create_many_counters_rejects_unsigned_or_existing_counters() {
+        reset_stubs(u64::MAX);
+        let (payer_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payer = (payer_key, true, 1_000_000_000, vec![], system_program::id());
+        let system = (system_program::id(), false, 1, vec![], Pubkey::default());
+
+        let mut input = serialize_input(&[payer.clone(), system.clone()]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        assert_eq!(create_many_counters(&program_id, &accounts), Err(ProgramError::NotEnoughAccountKeys));
+
+        let unsigned = (Pubkey::new_unique(), false, 0, vec![], system_program::id());
+        let mut input = serialize_input(&[payer.clone(), system.clone(), unsigned]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        assert_eq!(create_many_counters(&program_id, &accounts), Err(ProgramError::MissingRequiredSignature));
+
+        let existing = (Pubkey::new_unique(), true, 0, vec![0; Counter::LEN], program_id);
+        let mut input = serialize_input(&[payer, system, existing]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        assert_eq!(create_many_counters(&program_id, &accounts), Err(ProgramError::AccountAlreadyInitialized));
+        assert_eq!(accounts[0].lamports(), 1_000_000_000);
+    }
+
+    #[test]
+    fn sum_counters_totals_program_owned_counters() {
+        reset_stubs(u64::MAX);
+        let program_id = Pubkey::new_unique();
+
+        with_counters(&program_id, 3, |accounts| {
+            set_count(&accounts[1], 1);
+            set_count(&accounts[2], 20);
+            set_count(&accounts[3], 300);
+
+            assert_eq!(sum_counters(&program_id, &accounts[1..]), Ok(()));
+            assert_eq!(return_data(), 321u64.to_le_bytes());
+
+            set_count(&accounts[2], u64::MAX);
+            assert_eq!(sum_counters(&program_id, &accounts[1..]), Err(ProgramError::ArithmeticOverflow));
+        });
+    }
+
+    #[test]
+    fn sum_counters_skips_foreign_accounts() {
+        reset_stubs(u64::MAX);
+        let (program_id, other_program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (counter_key, foreign_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut counter_lamports, mut foreign_lamports) = (0, 0);
+        let (mut counter_data, mut foreign_data) = (vec![0u8; Counter::LEN], vec![0u8; Counter::LEN]);
+        let counter_account = AccountInfo::new(&counter_key, false, false, &mut counter_lamports, &mut counter_data, &program_id, false, 0);
+        let foreign = AccountInfo::new(&foreign_key, false, false, &mut foreign_lamports, &mut foreign_data, &other_program_id, false, 0);
+        initialize_counter(&counter_account, &counter_key, 7).unwrap();
+        initialize_counter(&foreign, &foreign_key, 1_000).unwrap();
+
+        assert_eq!(sum_counters(&program_id, &[foreign, counter_account]), Ok(()));
+        assert_eq!(return_data(), 7u64.to_le_bytes());
+    }
+
+    #[test]
+    fn get_version_returns_the_crate_version() {
+        reset_stubs(u64::MAX);
+        let instruction_data = TutorialInstruction::GetVersion.try_to_vec().unwrap();
+
+        assert_eq!(process_instruction(&Pubkey::new_unique(), &[], &instruction_data), Ok(()));
+        assert_eq!(String::from_utf8(return_data()).unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn increment_counter_rejects_undersized_accounts_with_a_clear_error() {
+        let (modifier_key, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        for (len, expected) in [
+            (0, CounterError::InvalidAccountData),
+            (Counter::LEGACY_LEN - 1, CounterError::InvalidAccountData),
+            (Counter::LEGACY_LEN, CounterError::LegacyAccount),
+            (Counter::LEN - 1, CounterError::InvalidAccountData),
+        ] {
+            let key = Pubkey::new_unique();
+            let (mut lamports, mut modifier_lamports) = (0, 0);
+            let (mut data, mut modifier_data) = (vec![0u8; len], vec![]);
+            let counter_account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &program_id, false, 0);
+            let modifier = AccountInfo::new(&modifier_key, true, false, &mut modifier_lamports, &mut modifier_data, &program_id, false, 0);
+
+            assert_eq!(
+                increment_counter(&program_id, &[counter_account, modifier]),
+                Err(expected.into()),
+                "{} byte account",
+                len
+            );
+        }
+    }
+}
@@ -26,7 +26,7 @@ async function main() {
             { pubkey: helloWorldProgramId, isSigner: false, isWritable: false },
         ],
         programId: cpiInvokeProgramId,
-        data: Buffer.from([]),
+        data: helloWorldProgramId.toBuffer(), // expected CPI target, checked on-chain
     });
 
     transaction.add(instruction);
@@ -6,20 +6,60 @@ use solana_program::{
     instruction,
     msg,
     program::invoke,
+    program_error::ProgramError,
+    pubkey,
 };
 
 entrypoint!(process_instruction);
 
+// solana_hello_world, the program this relay is built to call
+pub const HELLO_WORLD_PROGRAM_ID: Pubkey = pubkey!("7JL5oYxLowdhnd6fnDhD6tpXnBHZsPic6CbhRj4N2ZUh");
+
+// The only programs this relay will forward a CPI to. Targets must accept empty
+// instruction data with a single read-only account, which is all the relay sends.
+pub const ALLOWED_CPI_TARGETS: [Pubkey; 1] = [HELLO_WORLD_PROGRAM_ID];
+
+// Whether the relay may forward a CPI to `program_id`
+pub fn is_allowed_target(program_id: &Pubkey) -> bool {
+    ALLOWED_CPI_TARGETS.contains(program_id)
+}
+
 pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _instruction_data: &[u8],
+    instruction_data: &[u8],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
     let account = next_account_info(accounts_iter)?;
     let helloworld = next_account_info(accounts_iter)?;
 
+    // Optional: instruction data may carry the 32-byte program id we expect to call
+    let expected_program_id = match instruction_data.len() {
+        0 => None,
+        32 => Some(Pubkey::try_from(instruction_data).map_err(|_| ProgramError::InvalidInstructionData)?),
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+
+    // Only invoke real programs, never a data account passed in its place
+    if !helloworld.executable {
+        msg!("CPI target {} is not an executable program", helloworld.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Never forward to a program outside the allowlist, whatever the caller passes in
+    if !is_allowed_target(helloworld.key) {
+        msg!("CPI target {} is not in the allowlist", helloworld.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if let Some(expected_program_id) = expected_program_id {
+        if *helloworld.key != expected_program_id {
+            msg!("CPI target {} does not match expected program {}", helloworld.key, expected_program_id);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+    }
+
     msg!("CPI invoke program calling hello world from {}", account.key);
 
     let account_metas = vec![
@@ -41,4 +81,37 @@ pub fn process_instruction(
     msg!("CPI invoke program finished");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hello_world_is_an_allowed_target() {
+        assert!(is_allowed_target(&HELLO_WORLD_PROGRAM_ID));
+    }
+
+    #[test]
+    fn other_programs_are_not_allowed_targets() {
+        // greeting_counter needs a writable account and an instruction, so the relay can't call it
+        assert!(!is_allowed_target(&pubkey!("Dh7rYh3oeoKKsTkDUM7otJuL9zFLus9WQ4kWeak3RL7b")));
+        assert!(!is_allowed_target(&Pubkey::new_unique()));
+        assert!(!is_allowed_target(&Pubkey::default()));
+    }
+
+    #[test]
+    fn non_executable_target_is_rejected() {
+        let (relay_id, caller_key, loader) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut caller_lamports, mut caller_data) = (0, vec![]);
+        let caller = AccountInfo::new(&caller_key, false, false, &mut caller_lamports, &mut caller_data, &relay_id, false, 0);
+        // an allowlisted key passed as a plain data account
+        let (mut target_lamports, mut target_data) = (0, vec![]);
+        let target = AccountInfo::new(&HELLO_WORLD_PROGRAM_ID, false, false, &mut target_lamports, &mut target_data, &loader, false, 0);
+
+        assert_eq!(
+            process_instruction(&relay_id, &[caller, target], &[]),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+}
@@ -11,7 +11,8 @@ async function readMemo(memoAccount: PublicKey) {
         throw new Error('Memo account not found');
     }
 
-    const memo = borsh.deserialize(Memo.schema, Memo, accountInfo.data);
+    // a shorter update leaves unused bytes after the memo
+    const memo = borsh.deserializeUnchecked(Memo.schema, Memo, accountInfo.data);
     return formatMemoData(memo);
 }
 
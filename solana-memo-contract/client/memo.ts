@@ -4,11 +4,13 @@ import { PublicKey } from '@solana/web3.js';
 export class Memo {
     is_initialized: boolean;
     authorized: Uint8Array;
+    secondary_authority: Uint8Array;
     content: string;
 
-    constructor(fields: {is_initialized: boolean, authorized: Uint8Array, content: string}) {
+    constructor(fields: {is_initialized: boolean, authorized: Uint8Array, secondary_authority: Uint8Array, content: string}) {
         this.is_initialized = fields.is_initialized;
         this.authorized = fields.authorized;
+        this.secondary_authority = fields.secondary_authority;
         this.content = fields.content;
     }
     
@@ -20,6 +22,7 @@ export class Memo {
                 fields: [
                     ['is_initialized', 'u8'], // boolean as u8
                     ['authorized', [32]], // public key as 32 bytes
+                    ['secondary_authority', [32]], // all zeros when no co-signer is required
                     ['content', 'string'], // string
                 ]
             }
@@ -99,6 +102,7 @@ export function formatMemoData(memo: Memo): any {
     return {
         is_initialized: memo.is_initialized ? true : false,
         authorized: new PublicKey(memo.authorized).toBase58(),
+        secondary_authority: new PublicKey(memo.secondary_authority).toBase58(),
         content: memo.content,
     };
 }
@@ -26,6 +26,9 @@ pub enum MemoError {
 
     #[error("Unauthorized access")]     
     Unauthorized,
+
+    #[error("Memo account too small for new content")]
+    NeedsResize,
 }
 
 impl From<MemoError> for ProgramError {
@@ -64,6 +67,9 @@ impl PrintProgramError for MemoError {
             MemoError::Unauthorized => {
                 msg!("Error: Unauthorized access");
             }
+            MemoError::NeedsResize => {
+                msg!("Error: Memo account too small for new content");
+            }
         }
     }
 }
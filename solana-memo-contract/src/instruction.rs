@@ -4,21 +4,31 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+use crate::state::MemoAuditLog;
+
 
 #[derive(Debug, BorshDeserialize, BorshSerialize)]
 pub enum MemoInstruction {
-    /// Create a new memo
+    /// Create a new memo, returning (content length, account data length) as u64s via return data
     /// Accounts expected:
     /// 0. `[signer, writable]` Payer account to cover creation costs
     /// 1. `[writable]` New memo account
     /// 2. `[signer]` Memo owner/authority account
     /// 3. `[]` System program
+    /// 4. `[signer]` (optional) Secondary authority that must co-sign every update
     Initialize { content: String },
 
     /// Update memo content
-    /// Accounts expected:
+    /// Accounts expected for a memo without a secondary authority:
+    /// 0. `[signer]` Memo owner/authority account
+    /// 1. `[writable]` Memo account
+    /// 2. `[writable]` (optional) Memo audit log PDA to record the update time in
+    ///
+    /// Accounts expected for a memo with a secondary authority:
     /// 0. `[signer]` Memo owner/authority account
     /// 1. `[writable]` Memo account
+    /// 2. `[signer]` Secondary authority
+    /// 3. `[writable]` (optional) Memo audit log PDA to record the update time in
     Update { content: String },
 
     /// Delete memo
@@ -27,6 +37,34 @@ pub enum MemoInstruction {
     /// 1. `[writable]` Memo account
     /// 2. `[writable]` Account to receive rent refund
     Delete,
+
+    /// Create the audit log PDA for a memo
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer account
+    /// 1. `[signer]` Memo owner/authority account
+    /// 2. `[]` Memo account
+    /// 3. `[writable]` Audit log PDA, seeds [b"audit", memo_account]
+    /// 4. `[]` System program
+    InitializeAuditLog,
+
+    /// Return the recorded update timestamps (oldest first) via return data
+    /// Accounts expected:
+    /// 0. `[]` Memo account
+    /// 1. `[]` Audit log PDA
+    ReadAuditLog,
+
+    /// Return the program version via return data
+    /// Accounts expected: none
+    GetVersion,
+
+    /// Append content to the memo, joined with `separator` when one is given.
+    /// The account is resized and topped up to stay rent exempt.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Memo owner/authority account, pays for the extra space
+    /// 1. `[writable]` Memo account
+    /// 2. `[]` System program
+    /// 3. `[signer]` Secondary authority, required if the memo has one
+    Append { content: String, separator: Option<String> },
 }
 
 pub fn initialize(
@@ -46,6 +84,19 @@ pub fn initialize(
    Instruction::new_with_borsh(*program_id, &data, accounts)
 }
 
+pub fn initialize_multisig(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    memo_account: &Pubkey,
+    authority: &Pubkey,
+    secondary_authority: &Pubkey, // Second signer required for updates
+    content: String,
+) -> Instruction {
+    let mut instruction = initialize(program_id, payer, memo_account, authority, content);
+    instruction.accounts.push(AccountMeta::new_readonly(*secondary_authority, true));
+    instruction
+}
+
 pub fn update(
     program_id: &Pubkey,
     authority: &Pubkey,
@@ -60,6 +111,18 @@ pub fn update(
     Instruction::new_with_borsh(*program_id, &data, accounts)
 }
 
+pub fn update_multisig(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    memo_account: &Pubkey,
+    secondary_authority: &Pubkey,
+    content: String,
+) -> Instruction {
+    let mut instruction = update(program_id, authority, memo_account, content);
+    instruction.accounts.push(AccountMeta::new_readonly(*secondary_authority, true));
+    instruction
+}
+
 pub fn delete(
     program_id: &Pubkey,
     authority: &Pubkey,
@@ -70,7 +133,56 @@ pub fn delete(
     let accounts = vec![
         AccountMeta::new(*authority, true),
         AccountMeta::new(*memo_account, false),
-        AccountMeta::new_readonly(*receiver, false),
+        AccountMeta::new(*receiver, false),
     ];
     Instruction::new_with_borsh(*program_id, &data, accounts)
-}
\ No newline at end of file
+}
+
+pub fn initialize_audit_log(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    memo_account: &Pubkey,
+) -> Instruction {
+    let (audit_log, _) = MemoAuditLog::find_address(memo_account, program_id);
+    let data = MemoInstruction::InitializeAuditLog.try_to_vec().unwrap();
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*memo_account, false),
+        AccountMeta::new(audit_log, false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    Instruction::new_with_borsh(*program_id, &data, accounts)
+}
+
+pub fn read_audit_log(program_id: &Pubkey, memo_account: &Pubkey) -> Instruction {
+    let (audit_log, _) = MemoAuditLog::find_address(memo_account, program_id);
+    let data = MemoInstruction::ReadAuditLog.try_to_vec().unwrap();
+    let accounts = vec![
+        AccountMeta::new_readonly(*memo_account, false),
+        AccountMeta::new_readonly(audit_log, false),
+    ];
+    Instruction::new_with_borsh(*program_id, &data, accounts)
+}
+
+pub fn append(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    memo_account: &Pubkey,
+    content: String,
+    separator: Option<String>, // e.g. Some("\n".to_string()) to put each entry on its own line
+) -> Instruction {
+    let data = MemoInstruction::Append { content, separator }.try_to_vec().unwrap();
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*memo_account, false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    ];
+    Instruction::new_with_borsh(*program_id, &data, accounts)
+}
+
+pub fn get_version(program_id: &Pubkey) -> Instruction {
+    let data = MemoInstruction::GetVersion.try_to_vec().unwrap();
+    Instruction::new_with_borsh(*program_id, &data, vec![])
+}
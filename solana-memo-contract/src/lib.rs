@@ -3,3 +3,7 @@ pub mod entrypoint;
 pub mod processor;
 pub mod state;
 pub mod error;
+pub mod utils;
+
+/// Crate version, returned by the GetVersion instruction
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,9 +1,10 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    account_info::{next_account_info, AccountInfo}, entrypoint::ProgramResult, msg, program::invoke, program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_instruction, sysvar::Sysvar
+    account_info::{next_account_info, AccountInfo}, clock::Clock, entrypoint::ProgramResult, msg, program::{invoke, invoke_signed, set_return_data}, program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_instruction, sysvar::Sysvar
 };
 
-use crate::{instruction::MemoInstruction, state::Memo, error::MemoError};
+use crate::{instruction::MemoInstruction, state::{Memo, MemoAuditLog}, error::MemoError, utils::close_account, VERSION};
+use solana_rent_utils::assert_rent_exempt;
 
 pub struct Processor;
 
@@ -24,6 +25,14 @@ impl Processor {
                 Self::process_update(program_id, accounts, content)
             }
             MemoInstruction::Delete => Self::process_delete(program_id, accounts),
+            MemoInstruction::InitializeAuditLog => {
+                Self::process_initialize_audit_log(program_id, accounts)
+            }
+            MemoInstruction::ReadAuditLog => Self::process_read_audit_log(program_id, accounts),
+            MemoInstruction::GetVersion => Self::process_get_version(),
+            MemoInstruction::Append { content, separator } => {
+                Self::process_append(program_id, accounts, content, separator)
+            }
         }
     }
 
@@ -33,6 +42,7 @@ impl Processor {
         let memo_account_info = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
+        let secondary_authority_info = next_account_info(account_info_iter).ok();
 
         // check content length
         if content.len() > Memo::MAX_CONTENT_LENGTH {
@@ -49,11 +59,27 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        // check secondary authority is a distinct signer
+        let secondary_authority = match secondary_authority_info {
+            Some(secondary_authority_info) => {
+                if !secondary_authority_info.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if secondary_authority_info.key == authority_info.key {
+                    msg!("Secondary authority must differ from authority");
+                    return Err(ProgramError::InvalidArgument);
+                }
+                *secondary_authority_info.key
+            }
+            None => Pubkey::default(),
+        };
+
         // create memo account
         let rent = Rent::get()?; // get current sysvar rent configuration
         let memo = Memo {
             is_initialized: true,
             authority: *authority_info.key,
+            secondary_authority,
             content,
         };
 
@@ -75,8 +101,19 @@ impl Processor {
             ],
         )?;
 
-        memo.serialize(&mut *memo_account_info.data.borrow_mut())?; // memo struct to bytes and write to RefCell of memo account
+        // check the created account really is rent exempt (e.g. a pre-funded or resized account)
+        assert_rent_exempt(memo_account_info, &rent, MemoError::NotRentExempt)?;
+
+        // memo struct to bytes and write to RefCell of memo account, through a copy of the
+        // slice so the account's data_len() below isn't shortened by the write
+        memo.serialize(&mut &mut memo_account_info.data.borrow_mut()[..])?;
         
+        // return (content length, account data length) as two u64s so clients can skip a fetch
+        let content_len = memo.content.len() as u64;
+        let data_len = memo_account_info.data_len() as u64;
+        msg!("Memo content length: {} bytes, account data length: {} bytes", content_len, data_len);
+        set_return_data(&(content_len, data_len).try_to_vec()?);
+
         msg!("Memo account initialized successfully");
         Ok(())
     }
@@ -88,7 +125,7 @@ impl Processor {
 
         // check authority is signer
         if !authority_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+            return Err(MemoError::Unauthorized.into());
         }
 
         // check memo account is owned by program
@@ -101,7 +138,8 @@ impl Processor {
             return Err(MemoError::MemoContentTooLong.into());
         }
         
-        let mut memo = Memo::try_from_slice(&memo_account_info.data.borrow())?;
+        // a shorter update leaves unused bytes after the memo, so don't require an exact fit
+        let mut memo = Memo::deserialize(&mut &memo_account_info.data.borrow()[..])?;
 
         if !memo.is_initialized {
             return Err(MemoError::AccountNotInitialized.into());
@@ -111,10 +149,30 @@ impl Processor {
             return Err(MemoError::Unauthorized.into());
         }
 
+        // check secondary authority co-signed when the memo requires it
+        if memo.requires_secondary_authority() {
+            match next_account_info(account_info_iter) {
+                Ok(info) if info.is_signer && *info.key == memo.secondary_authority => {}
+                _ => return Err(MemoError::Unauthorized.into()),
+            }
+        }
+
         memo.content = content;
 
+        // the account was sized for the original content, so longer content may not fit
+        let new_size = memo.try_to_vec()?.len();
+        if new_size > memo_account_info.data_len() {
+            msg!("New memo needs {} bytes, account has {}", new_size, memo_account_info.data_len());
+            return Err(MemoError::NeedsResize.into());
+        }
+
         memo.serialize(&mut *memo_account_info.data.borrow_mut())?;
 
+        // record the update time when an audit log is supplied
+        if let Ok(audit_log_info) = next_account_info(account_info_iter) {
+            Self::record_audit_entry(program_id, memo_account_info, audit_log_info)?;
+        }
+
         msg!("Memo updated successfully");
 
         Ok(())
@@ -136,7 +194,7 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
     
-        let memo = Memo::try_from_slice(&memo_account_info.data.borrow())?;
+        let memo = Memo::deserialize(&mut &memo_account_info.data.borrow()[..])?;
         
         // check memo account is initialized
         if !memo.is_initialized {
@@ -148,26 +206,473 @@ impl Processor {
             return Err(MemoError::Unauthorized.into());
         }
     
-        let receiver_lamports = receiver_info.lamports(); // Get receiver's current balance
-        let memo_lamports = memo_account_info.lamports(); // Get memo account's full balance (rent-exempt deposit)
-    
-        // Add memo account balance to receiver's balance
-        **receiver_info.lamports.borrow_mut() = 
-            receiver_lamports
-            .checked_add(memo_lamports)
-            .ok_or(ProgramError::ArithmeticOverflow)?; 
-    
-        // Set memo account's balance to 0
-        **memo_account_info.lamports.borrow_mut() = 0;
-    
-        // Clear memo account data
-        let mut data = memo_account_info.data.borrow_mut();
-        for byte in data.iter_mut() {
-            *byte = 0;
-        }
+        // move the rent-exempt deposit to the receiver and clear the memo data
+        close_account(memo_account_info, receiver_info)?;
     
         msg!("Memo account deleted successfully");
         Ok(())
     }
+
+    fn process_initialize_audit_log(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let memo_account_info = next_account_info(account_info_iter)?;
+        let audit_log_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // check payer and authority are signers
+        if !payer_info.is_signer || !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // check memo account is owned by program
+        if memo_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let memo = Memo::deserialize(&mut &memo_account_info.data.borrow()[..])?;
+
+        if !memo.is_initialized {
+            return Err(MemoError::AccountNotInitialized.into());
+        }
+
+        // only the memo authority may attach an audit log
+        if memo.authority != *authority_info.key {
+            return Err(MemoError::Unauthorized.into());
+        }
+
+        let (expected_audit_log, bump_seed) = MemoAuditLog::find_address(memo_account_info.key, program_id);
+        if expected_audit_log != *audit_log_info.key {
+            msg!("Audit log account does not match the derived address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                audit_log_info.key,
+                rent.minimum_balance(MemoAuditLog::LEN),
+                MemoAuditLog::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                audit_log_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[MemoAuditLog::SEED_PREFIX, memo_account_info.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        let audit_log = MemoAuditLog::new(*memo_account_info.key);
+        audit_log.serialize(&mut *audit_log_info.data.borrow_mut())?;
+
+        msg!("Memo audit log initialized successfully");
+        Ok(())
+    }
+
+    fn process_read_audit_log(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let memo_account_info = next_account_info(account_info_iter)?;
+        let audit_log_info = next_account_info(account_info_iter)?;
+
+        let audit_log = Self::load_audit_log(program_id, memo_account_info, audit_log_info)?;
+        let timestamps = audit_log.ordered_timestamps();
+
+        msg!("Memo has {} recorded updates, returning last {}", audit_log.total_updates, timestamps.len());
+
+        set_return_data(&timestamps.try_to_vec()?);
+        Ok(())
+    }
+
+    fn process_append(program_id: &Pubkey, accounts: &[AccountInfo], content: String, separator: Option<String>) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let memo_account_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // check authority is signer
+        if !authority_info.is_signer {
+            return Err(MemoError::Unauthorized.into());
+        }
+
+        // check memo account is owned by program
+        if memo_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut memo = Memo::deserialize(&mut &memo_account_info.data.borrow()[..])?;
+
+        if !memo.is_initialized {
+            return Err(MemoError::AccountNotInitialized.into());
+        }
+
+        if memo.authority != *authority_info.key {
+            return Err(MemoError::Unauthorized.into());
+        }
+
+        // check secondary authority co-signed when the memo requires it
+        if memo.requires_secondary_authority() {
+            match next_account_info(account_info_iter) {
+                Ok(info) if info.is_signer && *info.key == memo.secondary_authority => {}
+                _ => return Err(MemoError::Unauthorized.into()),
+            }
+        }
+
+        // join with the separator, then check the combined length
+        if let Some(separator) = separator {
+            memo.content.push_str(&separator);
+        }
+        memo.content.push_str(&content);
+        if memo.content.len() > Memo::MAX_CONTENT_LENGTH {
+            return Err(MemoError::MemoContentTooLong.into());
+        }
+
+        // grow the account and top up rent before writing the longer memo
+        let new_size = memo.try_to_vec()?.len();
+        if new_size > memo_account_info.data_len() {
+            let shortfall = Rent::get()?
+                .minimum_balance(new_size)
+                .saturating_sub(memo_account_info.lamports());
+            if shortfall > 0 {
+                invoke(
+                    &system_instruction::transfer(authority_info.key, memo_account_info.key, shortfall),
+                    &[
+                        authority_info.clone(),
+                        memo_account_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+            memo_account_info.realloc(new_size, false)?;
+        }
+
+        memo.serialize(&mut *memo_account_info.data.borrow_mut())?;
+
+        msg!("Appended {} bytes, memo is now {} bytes", content.len(), memo.content.len());
+        Ok(())
+    }
+
+    fn process_get_version() -> ProgramResult {
+        msg!("Program version: {}", VERSION);
+        set_return_data(VERSION.as_bytes());
+        Ok(())
+    }
+
+    fn record_audit_entry(program_id: &Pubkey, memo_account_info: &AccountInfo, audit_log_info: &AccountInfo) -> ProgramResult {
+        let mut audit_log = Self::load_audit_log(program_id, memo_account_info, audit_log_info)?;
+
+        audit_log.push(Clock::get()?.unix_timestamp);
+        audit_log.serialize(&mut *audit_log_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    fn load_audit_log(program_id: &Pubkey, memo_account_info: &AccountInfo, audit_log_info: &AccountInfo) -> Result<MemoAuditLog, ProgramError> {
+        // check audit log account is owned by program
+        if audit_log_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let (expected_audit_log, _) = MemoAuditLog::find_address(memo_account_info.key, program_id);
+        if expected_audit_log != *audit_log_info.key {
+            msg!("Audit log account does not match the derived address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let audit_log = MemoAuditLog::try_from_slice(&audit_log_info.data.borrow())?;
+        if audit_log.memo != *memo_account_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(audit_log)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        entrypoint::{deserialize, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        instruction::Instruction,
+        program::get_return_data,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+        system_program,
+    };
+    use std::{cell::RefCell, sync::Once};
+
+    thread_local! {
+        // Bytes passed to the last set_return_data call in the current test
+        static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Stubs for the Rent sysvar, return data and the system program instructions this
+    // program invokes, with per-thread state so parallel tests don't interfere
+    struct TestStubs;
+
+    impl SyscallStubs for TestStubs {
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|return_data| Some((Pubkey::default(), return_data.borrow().clone())))
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let find = |index: usize| {
+                account_infos
+                    .iter()
+                    .find(|account| *account.key == instruction.accounts[index].pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)
+            };
+            let (from, to) = (find(0)?, find(1)?);
+
+            match limited_deserialize(&instruction.data, 1_232) {
+                Ok(SystemInstruction::Transfer { lamports }) => {
+                    **from.try_borrow_mut_lamports()? -= lamports;
+                    **to.try_borrow_mut_lamports()? += lamports;
+                }
+                Ok(SystemInstruction::CreateAccount { lamports, space, owner }) => {
+                    **from.try_borrow_mut_lamports()? -= lamports;
+                    **to.try_borrow_mut_lamports()? += lamports;
+                    to.realloc(space as usize, true)?;
+                    to.assign(&owner);
+                }
+                _ => return Err(ProgramError::InvalidInstructionData),
+            }
+            Ok(())
+        }
+    }
+
+    fn install_stubs() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+    }
+
+    // Lays accounts out the way the runtime serializes program input, so the
+    // AccountInfos from entrypoint::deserialize can be resized and reassigned.
+    // Each account is (key, is_signer, lamports, data, owner), all writable.
+    fn serialize_input(accounts: &[(Pubkey, bool, u64, Vec<u8>, Pubkey)]) -> Vec<u64> {
+        let mut input = (accounts.len() as u64).to_le_bytes().to_vec();
+        for (key, is_signer, lamports, data, owner) in accounts {
+            input.extend_from_slice(&[NON_DUP_MARKER, *is_signer as u8, 1, 0]);
+            input.extend_from_slice(&[0; 4]); // original data length, set by deserialize
+            input.extend_from_slice(key.as_ref());
+            input.extend_from_slice(owner.as_ref());
+            input.extend_from_slice(&lamports.to_le_bytes());
+            input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            input.extend_from_slice(data);
+            input.resize((input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+        }
+        input.extend_from_slice(&0u64.to_le_bytes()); // no instruction data
+        input.extend_from_slice(Pubkey::default().as_ref()); // program id, unused
+
+        // Copy into a u64 buffer so the header fields are aligned
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        for (word, bytes) in aligned.iter_mut().zip(input.chunks(8)) {
+            let mut padded = [0u8; 8];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(padded);
+        }
+        aligned
+    }
+
+    // Memo account data holding `content`, sized exactly for it
+    fn memo_data(authority: &Pubkey, secondary_authority: Pubkey, content: &str) -> Vec<u8> {
+        let memo = Memo {
+            is_initialized: true,
+            authority: *authority,
+            secondary_authority,
+            content: content.to_string(),
+        };
+        memo.try_to_vec().unwrap()
+    }
+
+    fn stored_content(data: &[u8]) -> String {
+        Memo::deserialize(&mut &data[..]).unwrap().content
+    }
+
+    // Run Update on a memo account holding `data`, with `authority` and then each of
+    // `co_signers` (key, is_signer) around the memo account
+    fn update(data: &mut [u8], authority: &Pubkey, co_signers: &[(Pubkey, bool)], content: &str) -> ProgramResult {
+        let (program_id, memo_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut authority_lamports, mut memo_lamports) = (0, 0);
+        let mut authority_data = vec![];
+        let mut accounts = vec![
+            AccountInfo::new(authority, true, false, &mut authority_lamports, &mut authority_data, &program_id, false, 0),
+            AccountInfo::new(&memo_key, false, true, &mut memo_lamports, data, &program_id, false, 0),
+        ];
+        let mut co_signer_lamports = vec![0u64; co_signers.len()];
+        let mut co_signer_data = vec![vec![]; co_signers.len()];
+        for (((key, is_signer), lamports), data) in co_signers.iter().zip(co_signer_lamports.iter_mut()).zip(co_signer_data.iter_mut()) {
+            accounts.push(AccountInfo::new(key, *is_signer, false, lamports, data, &program_id, false, 0));
+        }
+
+        let instruction = MemoInstruction::Update { content: content.to_string() };
+        Processor::process(&program_id, &accounts, &instruction.try_to_vec().unwrap())
+    }
+
+    #[test]
+    fn update_accepts_content_that_fits_exactly() {
+        let authority = Pubkey::new_unique();
+        let mut data = memo_data(&authority, Pubkey::default(), "hello");
+
+        assert_eq!(update(&mut data, &authority, &[], "world"), Ok(()));
+        assert_eq!(stored_content(&data), "world");
+    }
+
+    #[test]
+    fn update_one_byte_too_long_needs_a_resize() {
+        let authority = Pubkey::new_unique();
+        let mut data = memo_data(&authority, Pubkey::default(), "hello");
+
+        assert_eq!(update(&mut data, &authority, &[], "world!"), Err(MemoError::NeedsResize.into()));
+        assert_eq!(stored_content(&data), "hello");
+    }
+
+    #[test]
+    fn memo_stays_usable_after_a_shorter_update() {
+        let authority = Pubkey::new_unique();
+        let mut data = memo_data(&authority, Pubkey::default(), "hello");
+
+        assert_eq!(update(&mut data, &authority, &[], "hi"), Ok(()));
+        assert_eq!(stored_content(&data), "hi");
+
+        // the bytes left over from "hello" must not break the next read
+        assert_eq!(update(&mut data, &authority, &[], "hey"), Ok(()));
+        assert_eq!(stored_content(&data), "hey");
+        assert_eq!(update(&mut data, &authority, &[], "hello"), Ok(()));
+        assert_eq!(stored_content(&data), "hello");
+    }
+
+    #[test]
+    fn update_with_both_authorities_signing_succeeds() {
+        let (authority, secondary) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = memo_data(&authority, secondary, "hello");
+
+        assert_eq!(update(&mut data, &authority, &[(secondary, true)], "world"), Ok(()));
+        assert_eq!(stored_content(&data), "world");
+    }
+
+    #[test]
+    fn update_without_the_secondary_authority_fails() {
+        let (authority, secondary) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = memo_data(&authority, secondary, "hello");
+
+        // primary only
+        assert_eq!(update(&mut data, &authority, &[], "world"), Err(MemoError::Unauthorized.into()));
+        // secondary passed but did not sign
+        assert_eq!(update(&mut data, &authority, &[(secondary, false)], "world"), Err(MemoError::Unauthorized.into()));
+        assert_eq!(stored_content(&data), "hello");
+    }
+
+    #[test]
+    fn update_with_the_wrong_secondary_authority_fails() {
+        let (authority, secondary) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = memo_data(&authority, secondary, "hello");
+
+        assert_eq!(
+            update(&mut data, &authority, &[(Pubkey::new_unique(), true)], "world"),
+            Err(MemoError::Unauthorized.into())
+        );
+        assert_eq!(stored_content(&data), "hello");
+    }
+
+    // Run Append on a rent-exempt memo holding `memo_content`, returning the memo's
+    // content, data length and lamports afterwards
+    fn append(memo_content: &str, content: &str, separator: Option<&str>) -> Result<(String, usize, u64), ProgramError> {
+        install_stubs();
+        let (program_id, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = memo_data(&authority, Pubkey::default(), memo_content);
+        let rent_lamports = Rent::default().minimum_balance(data.len());
+        let mut input = serialize_input(&[
+            (authority, true, 1_000_000_000, vec![], system_program::id()),
+            (Pubkey::new_unique(), false, rent_lamports, data, program_id),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        let instruction = MemoInstruction::Append {
+            content: content.to_string(),
+            separator: separator.map(str::to_string),
+        };
+        Processor::process(&program_id, &accounts, &instruction.try_to_vec().unwrap())?;
+
+        // Read the memo back the way the runtime does, from the serialized length that
+        // realloc updated, since serializing into the data slice advances it.
+        // Accounts after the resized memo no longer parse, so only it is read.
+        drop(accounts);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let memo_account_info = &accounts[1];
+        let stored = stored_content(&memo_account_info.data.borrow());
+        Ok((stored, memo_account_info.data_len(), memo_account_info.lamports()))
+    }
+
+    #[test]
+    fn append_joins_with_the_separator_and_grows_the_account() {
+        let (content, data_len, lamports) = append("hello", "world", Some(", ")).unwrap();
+
+        assert_eq!(content, "hello, world");
+        assert_eq!(data_len, memo_data(&Pubkey::default(), Pubkey::default(), "hello, world").len());
+        assert_eq!(lamports, Rent::default().minimum_balance(data_len));
+    }
+
+    #[test]
+    fn append_without_a_separator_concatenates() {
+        let (content, data_len, lamports) = append("hello", "world", None).unwrap();
+
+        assert_eq!(content, "helloworld");
+        assert_eq!(data_len, memo_data(&Pubkey::default(), Pubkey::default(), "helloworld").len());
+        assert_eq!(lamports, Rent::default().minimum_balance(data_len));
+    }
+
+    #[test]
+    fn append_past_the_maximum_length_fails() {
+        let almost_full = "a".repeat(Memo::MAX_CONTENT_LENGTH - 1);
+
+        assert!(append(&almost_full, "b", None).is_ok());
+        assert_eq!(append(&almost_full, "b", Some(" ")), Err(MemoError::MemoContentTooLong.into()));
+    }
+
+    #[test]
+    fn initialize_returns_the_content_and_data_lengths() {
+        install_stubs();
+        let (program_id, payer, memo_key, authority) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut input = serialize_input(&[
+            (payer, true, 1_000_000_000, vec![], system_program::id()),
+            (memo_key, true, 0, vec![], system_program::id()),
+            (authority, true, 0, vec![], system_program::id()),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        let instruction = MemoInstruction::Initialize { content: "hello".to_string() };
+        assert_eq!(Processor::process(&program_id, &accounts, &instruction.try_to_vec().unwrap()), Ok(()));
+
+        let (_, return_data) = get_return_data().unwrap();
+        let (content_len, data_len) = <(u64, u64)>::try_from_slice(&return_data).unwrap();
+        let expected_len = memo_data(&authority, Pubkey::default(), "hello").len();
+        assert_eq!(content_len, 5);
+        assert_eq!(data_len, expected_len as u64);
+        assert_eq!(accounts[1].data_len(), expected_len);
+        assert_eq!(stored_content(&accounts[1].data.borrow()), "hello");
+    }
+}
@@ -5,9 +5,90 @@ use solana_program::pubkey::Pubkey;
 pub struct Memo {
     pub is_initialized: bool,
     pub authority: Pubkey,
+    pub secondary_authority: Pubkey, // Pubkey::default() when the memo only needs one signer
     pub content: String,
 }
 
 impl Memo {
     pub const MAX_CONTENT_LENGTH: usize = 1000;
-}
\ No newline at end of file
+
+    pub fn requires_secondary_authority(&self) -> bool {
+        self.secondary_authority != Pubkey::default()
+    }
+}
+/// Ring buffer of the most recent update timestamps for a memo.
+/// PDA derived from [b"audit", memo_account.key].
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MemoAuditLog {
+    pub memo: Pubkey,
+    pub next_index: u8,
+    pub total_updates: u64,
+    pub timestamps: [i64; MemoAuditLog::CAPACITY],
+}
+
+impl MemoAuditLog {
+    pub const CAPACITY: usize = 5;
+    pub const SEED_PREFIX: &'static [u8] = b"audit";
+    // 32(memo) + 1(next_index) + 8(total_updates) + 8 * CAPACITY(timestamps)
+    pub const LEN: usize = 32 + 1 + 8 + 8 * Self::CAPACITY;
+
+    pub fn new(memo: Pubkey) -> Self {
+        Self {
+            memo,
+            next_index: 0,
+            total_updates: 0,
+            timestamps: [0; Self::CAPACITY],
+        }
+    }
+
+    pub fn find_address(memo: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, memo.as_ref()], program_id)
+    }
+
+    /// Record a timestamp, overwriting the oldest entry once the ring is full
+    pub fn push(&mut self, timestamp: i64) {
+        self.timestamps[self.next_index as usize] = timestamp;
+        self.next_index = ((self.next_index as usize + 1) % Self::CAPACITY) as u8;
+        self.total_updates = self.total_updates.saturating_add(1);
+    }
+
+    /// Recorded timestamps, oldest first
+    pub fn ordered_timestamps(&self) -> Vec<i64> {
+        let len = (self.total_updates as usize).min(Self::CAPACITY);
+        let start = if len < Self::CAPACITY { 0 } else { self.next_index as usize };
+        (0..len)
+            .map(|i| self.timestamps[(start + i) % Self::CAPACITY])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_timestamps_before_the_ring_fills() {
+        let mut audit_log = MemoAuditLog::new(Pubkey::new_unique());
+        assert!(audit_log.ordered_timestamps().is_empty());
+
+        audit_log.push(10);
+        audit_log.push(20);
+
+        assert_eq!(audit_log.ordered_timestamps(), vec![10, 20]);
+        assert_eq!(audit_log.total_updates, 2);
+    }
+
+    #[test]
+    fn ordered_timestamps_after_the_ring_wraps() {
+        let mut audit_log = MemoAuditLog::new(Pubkey::new_unique());
+        for timestamp in 1..=6 {
+            audit_log.push(timestamp);
+        }
+
+        // the sixth push overwrote the oldest entry
+        assert_eq!(audit_log.ordered_timestamps(), vec![2, 3, 4, 5, 6]);
+        assert_eq!(audit_log.timestamps, [6, 2, 3, 4, 5]);
+        assert_eq!(audit_log.next_index, 1);
+        assert_eq!(audit_log.total_updates, 6);
+    }
+}
@@ -0,0 +1,88 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+};
+
+/// Close `account` by moving all of its lamports to `destination` and zeroing its data
+///
+/// Returns an error instead of panicking when the destination can't be credited, the
+/// account data is still borrowed, or the account isn't empty afterwards.
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    // check destination can be credited and is not the account being closed
+    if !destination.is_writable {
+        msg!("Destination account must be writable");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if destination.key == account.key {
+        msg!("Destination cannot be the account being closed");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // move the full balance (rent-exempt deposit) to the destination
+    let account_lamports = account.lamports();
+    let destination_lamports = destination
+        .lamports()
+        .checked_add(account_lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **destination.try_borrow_mut_lamports()? = destination_lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    // make sure the sweep left nothing behind
+    if account.lamports() != 0 {
+        msg!("Account still holds {} lamports after close", account.lamports());
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // clear the data so nothing can be read back before the runtime reclaims it
+    account.try_borrow_mut_data()?.fill(0);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn close_account_moves_lamports_and_zeroes_data() {
+        let (key, destination_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = 5_000;
+        let mut data = vec![7u8; 16];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        let mut destination_lamports = 1_000;
+        let mut destination_data = vec![];
+        let destination = AccountInfo::new(&destination_key, false, true, &mut destination_lamports, &mut destination_data, &owner, false, 0);
+
+        assert_eq!(close_account(&account, &destination), Ok(()));
+        // post-sweep postcondition: nothing is left behind in the closed account
+        assert_eq!(account.lamports(), 0);
+        assert_eq!(destination.lamports(), 6_000);
+        assert!(account.data.borrow().iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn close_account_rejects_read_only_destination() {
+        let (key, destination_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = 5_000;
+        let mut data = vec![7u8; 16];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        let mut destination_lamports = 1_000;
+        let mut destination_data = vec![];
+        let destination = AccountInfo::new(&destination_key, false, false, &mut destination_lamports, &mut destination_data, &owner, false, 0);
+
+        assert_eq!(close_account(&account, &destination), Err(ProgramError::InvalidArgument));
+        assert_eq!(account.lamports(), 5_000);
+        assert_eq!(destination.lamports(), 1_000);
+    }
+
+    #[test]
+    fn close_account_rejects_closing_into_itself() {
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = 5_000;
+        let mut data = vec![7u8; 16];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+
+        assert_eq!(close_account(&account, &account.clone()), Err(ProgramError::InvalidArgument));
+        assert_eq!(account.lamports(), 5_000);
+    }
+}
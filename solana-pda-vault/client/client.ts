@@ -74,7 +74,7 @@ async function main() {
             { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
         programId: vaultProgramId,
-        data: Buffer.from(serializedData),
+        data: Buffer.concat([Buffer.from([0]), Buffer.from(serializedData)]), // 0 = CreateVault
     });
 
     transaction.add(instruction);
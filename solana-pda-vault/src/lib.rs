@@ -1,20 +1,110 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::{Clock, UnixTimestamp},
     entrypoint,
     entrypoint::ProgramResult,
     msg,
-    program::invoke_signed,
+    program::{invoke_signed, set_return_data},
     program_error::ProgramError,
     pubkey::Pubkey,
+    rent::Rent,
     system_instruction,
     system_program,
+    sysvar::Sysvar,
 };
+use solana_rent_utils::assert_rent_exempt;
+use thiserror::Error;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct InstructionData {
-    pub vault_bump_seed: u8,
-    pub lamports: u64,
+pub enum VaultInstruction {
+    /// Create a vault that can be withdrawn from at any time.
+    /// `vault_bump_seed` must be the canonical bump from find_program_address, as for
+    /// every Create* instruction.
+    /// 0. `[signer, writable]` Payer / vault authority
+    /// 1. `[writable]` Vault PDA
+    /// 2. `[]` System program
+    CreateVault { vault_bump_seed: u8, lamports: u64 },
+
+    /// Create a vault that stays locked for `lock_duration` seconds
+    /// 0. `[signer, writable]` Payer / vault authority
+    /// 1. `[writable]` Vault PDA
+    /// 2. `[]` System program
+    CreateTimelockedVault {
+        vault_bump_seed: u8,
+        lamports: u64,
+        lock_duration: i64,
+    },
+
+    /// Withdraw lamports from the vault back to its authority
+    /// 0. `[signer, writable]` Vault authority
+    /// 1. `[writable]` Vault PDA
+    Withdraw { lamports: u64 },
+
+    /// Create a vault funded with the rent-exempt minimum plus `extra_lamports`
+    /// 0. `[signer, writable]` Payer / vault authority
+    /// 1. `[writable]` Vault PDA
+    /// 2. `[]` System program
+    CreateWithDeposit { vault_bump_seed: u8, extra_lamports: u64 },
+
+    /// Return a VaultPreview (vault PDA, bump, rent-exempt minimum) without creating anything
+    /// 0. `[]` Payer / would-be vault authority
+    PreviewCreate,
+
+    /// Move everything above the rent-exempt minimum to a treasury, keeping the vault open
+    /// 0. `[signer]` Vault authority
+    /// 1. `[writable]` Vault PDA
+    /// 2. `[writable]` Treasury
+    SweepToTreasury,
+}
+
+/// Data stored at the start of the vault account
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VaultState {
+    pub authority: Pubkey,
+    pub unlock_timestamp: UnixTimestamp,
+    /// Bump used to create the vault, so later instructions skip find_program_address
+    pub bump: u8,
+}
+
+impl VaultState {
+    /// Whether withdrawals and sweeps are allowed at `now`
+    pub fn is_unlocked(&self, now: UnixTimestamp) -> bool {
+        now >= self.unlock_timestamp
+    }
+}
+
+/// Returned by PreviewCreate
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VaultPreview {
+    pub vault: Pubkey,
+    pub bump: u8,
+    /// Rent-exempt minimum for VAULT_ACCOUNT_SIZE, what CreateWithDeposit funds before the deposit
+    pub rent_lamports: u64,
+}
+
+#[derive(Error, Debug, Copy, Clone)]
+pub enum VaultError {
+    #[error("Vault is still locked")]
+    StillLocked,
+
+    #[error("Invalid lock duration")]
+    InvalidLockDuration,
+
+    #[error("Insufficient vault balance")]
+    InsufficientFunds,
+
+    #[error("Vault is not rent exempt")]
+    NotRentExempt,
+
+    #[error("Vault bump seed is not canonical")]
+    NonCanonicalBump,
+}
+
+impl From<VaultError> for ProgramError {
+    fn from(e: VaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
 }
 
 pub const VAULT_ACCOUNT_SIZE: u64 = 1024;
@@ -26,9 +116,45 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = InstructionData::try_from_slice(instruction_data)
+    let instruction = VaultInstruction::try_from_slice(instruction_data)
         .map_err(|_| ProgramError::InvalidInstructionData)?;
 
+    match instruction {
+        VaultInstruction::CreateVault { vault_bump_seed, lamports } => {
+            create_vault(program_id, accounts, vault_bump_seed, lamports, 0)
+        }
+        VaultInstruction::CreateTimelockedVault { vault_bump_seed, lamports, lock_duration } => {
+            if lock_duration <= 0 {
+                return Err(VaultError::InvalidLockDuration.into());
+            }
+            let unlock_timestamp = Clock::get()?
+                .unix_timestamp
+                .checked_add(lock_duration)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            create_vault(program_id, accounts, vault_bump_seed, lamports, unlock_timestamp)
+        }
+        VaultInstruction::Withdraw { lamports } => withdraw(program_id, accounts, lamports),
+        VaultInstruction::CreateWithDeposit { vault_bump_seed, extra_lamports } => {
+            // Fund rent exemption for the full vault size, then the deposit on top
+            let lamports = Rent::get()?
+                .minimum_balance(VAULT_ACCOUNT_SIZE as usize)
+                .checked_add(extra_lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            msg!("Deposit: {} lamports on top of rent exemption", extra_lamports);
+            create_vault(program_id, accounts, vault_bump_seed, lamports, 0)
+        }
+        VaultInstruction::PreviewCreate => preview_create(program_id, accounts),
+        VaultInstruction::SweepToTreasury => sweep_to_treasury(program_id, accounts),
+    }
+}
+
+fn create_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vault_bump_seed: u8,
+    lamports: u64,
+    unlock_timestamp: UnixTimestamp,
+) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let payer = next_account_info(account_info_iter)?;
     let vault = next_account_info(account_info_iter)?;
@@ -38,14 +164,17 @@ fn process_instruction(
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    let vault_bump_seed = instruction.vault_bump_seed;
-    let lamports = instruction.lamports;
-
     msg!("Creating vault account...");
     msg!("Payer: {}", payer.key);
     msg!("Vault: {}", vault.key);
     msg!("Bump seed: {}", vault_bump_seed);
     msg!("Lamports: {}", lamports);
+    msg!("Unlock timestamp: {}", unlock_timestamp);
+
+    check_vault_bump(program_id, payer.key, vault_bump_seed)?;
+
+    let payer_before = payer.lamports();
+    let vault_before = vault.lamports();
 
     invoke_signed(
         &system_instruction::create_account(
@@ -68,8 +197,543 @@ fn process_instruction(
             ],
         ],
     )?;
+    assert_rent_exempt(vault, &Rent::get()?, VaultError::NotRentExempt)?;
+
+    let vault_state = VaultState {
+        authority: *payer.key,
+        unlock_timestamp,
+        bump: vault_bump_seed,
+    };
+    vault_state.serialize(&mut &mut vault.data.borrow_mut()[..])?;
+
+    log_balance_change("Payer", payer_before, payer.lamports());
+    log_balance_change("Vault", vault_before, vault.lamports());
 
     msg!("Vault account created successfully.");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+
+    load_unlocked_vault(program_id, authority, vault)?;
+
+    // Keep the vault rent exempt after the withdrawal
+    let rent_minimum = Rent::get()?.minimum_balance(vault.data_len());
+    let available = vault.lamports().saturating_sub(rent_minimum);
+    if lamports > available {
+        msg!("Requested {} lamports, only {} available", lamports, available);
+        return Err(VaultError::InsufficientFunds.into());
+    }
+
+    let authority_before = authority.lamports();
+    let vault_before = vault.lamports();
+
+    **vault.try_borrow_mut_lamports()? -= lamports;
+    **authority.try_borrow_mut_lamports()? = authority
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    log_balance_change("Authority", authority_before, authority.lamports());
+    log_balance_change("Vault", vault_before, vault.lamports());
+
+    msg!("Withdrew {} lamports from vault {}", lamports, vault.key);
+
+    Ok(())
+}
+
+fn sweep_to_treasury(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    let treasury = next_account_info(account_info_iter)?;
+
+    load_unlocked_vault(program_id, authority, vault)?;
+
+    if !treasury.is_writable {
+        msg!("Treasury {} is not writable", treasury.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if treasury.key == vault.key {
+        msg!("Treasury cannot be the vault itself");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Everything above the rent-exempt minimum is excess
+    let rent_minimum = Rent::get()?.minimum_balance(vault.data_len());
+    let excess = vault.lamports().saturating_sub(rent_minimum);
+    if excess == 0 {
+        msg!("Vault holds no lamports above its {} lamport rent minimum", rent_minimum);
+        return Ok(());
+    }
+
+    let treasury_before = treasury.lamports();
+    let vault_before = vault.lamports();
+
+    **vault.try_borrow_mut_lamports()? -= excess;
+    **treasury.try_borrow_mut_lamports()? = treasury
+        .lamports()
+        .checked_add(excess)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    log_balance_change("Treasury", treasury_before, treasury.lamports());
+    log_balance_change("Vault", vault_before, vault.lamports());
+
+    msg!("Swept {} lamports from vault {} to {}", excess, vault.key, treasury.key);
+
+    Ok(())
+}
+
+fn preview_create(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+
+    let (vault, bump) = Pubkey::find_program_address(&[b"vault", payer.key.as_ref()], program_id);
+    let preview = VaultPreview {
+        vault,
+        bump,
+        rent_lamports: Rent::get()?.minimum_balance(VAULT_ACCOUNT_SIZE as usize),
+    };
+
+    msg!("Vault for {} would be {} (bump {})", payer.key, preview.vault, preview.bump);
+    msg!("Rent-exempt minimum: {} lamports for {} bytes", preview.rent_lamports, VAULT_ACCOUNT_SIZE);
+
+    set_return_data(&preview.try_to_vec()?);
+
+    Ok(())
+}
+
+// Check that `vault` is this authority's vault and its lock has expired
+fn load_unlocked_vault(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    vault: &AccountInfo,
+) -> Result<VaultState, ProgramError> {
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if vault.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let vault_state = VaultState::deserialize(&mut &vault.data.borrow()[..])?;
+    if vault_state.authority != *authority.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Rebuild the address from the stored bump instead of searching for it again
+    let expected_vault = Pubkey::create_program_address(
+        &[b"vault", authority.key.as_ref(), &[vault_state.bump]],
+        program_id,
+    )?;
+    if expected_vault != *vault.key {
+        msg!("Vault does not match the derived address");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Reject withdrawals and sweeps until the lock has expired
+    let now = Clock::get()?.unix_timestamp;
+    if !vault_state.is_unlocked(now) {
+        msg!("Vault unlocks at {}, current time is {}", vault_state.unlock_timestamp, now);
+        return Err(VaultError::StillLocked.into());
+    }
+
+    Ok(vault_state)
+}
+
+// Check that `bump` is the canonical bump for this authority's vault, so the stored
+// bump can be trusted by create_program_address in later instructions
+fn check_vault_bump(program_id: &Pubkey, authority: &Pubkey, bump: u8) -> ProgramResult {
+    let (_, canonical_bump) = Pubkey::find_program_address(&[b"vault", authority.as_ref()], program_id);
+    if bump != canonical_bump {
+        msg!("Bump seed {} is not the canonical bump {}", bump, canonical_bump);
+        return Err(VaultError::NonCanonicalBump.into());
+    }
+    Ok(())
+}
+
+// Log an account balance before and after a transfer, with the signed delta
+fn log_balance_change(label: &str, before: u64, after: u64) {
+    let delta = after as i128 - before as i128;
+    msg!("{} balance: {} -> {} ({:+})", label, before, after, delta);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        entrypoint::{deserialize, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        instruction::Instruction,
+        program::get_return_data,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        sync::Once,
+    };
+
+    thread_local! {
+        // unix_timestamp reported by the Clock sysvar in the current test
+        static NOW: Cell<UnixTimestamp> = const { Cell::new(0) };
+        // Bytes passed to the last set_return_data call
+        static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        // Every msg! logged in the current test
+        static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Syscall stubs backed by thread-local state, so tests running in parallel can
+    // each warp their own clock and read their own return data and logs
+    struct TestStubs;
+
+    impl SyscallStubs for TestStubs {
+        fn sol_log(&self, message: &str) {
+            LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+            println!("Program log: {}", message);
+        }
+
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock { unix_timestamp: NOW.with(Cell::get), ..Clock::default() };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|return_data| Some((Pubkey::default(), return_data.borrow().clone())))
+        }
+
+        // Stands in for the system program's CreateAccount, the only CPI this program makes
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let Ok(SystemInstruction::CreateAccount { lamports, space, owner }) =
+                limited_deserialize(&instruction.data, 1_232)
+            else {
+                return Err(ProgramError::InvalidInstructionData);
+            };
+            let find = |index: usize| {
+                account_infos
+                    .iter()
+                    .find(|account| *account.key == instruction.accounts[index].pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)
+            };
+            let (from, to) = (find(0)?, find(1)?);
+
+            **from.try_borrow_mut_lamports()? -= lamports;
+            **to.try_borrow_mut_lamports()? += lamports;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+            Ok(())
+        }
+    }
+
+    // Installs TestStubs once per test binary and sets this test's clock to `now`
+    fn warp_to(now: UnixTimestamp) {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+        NOW.with(|clock| clock.set(now));
+    }
+
+    fn take_logs() -> Vec<String> {
+        LOGS.with(|logs| logs.take())
+    }
+
+    // Lays accounts out the way the runtime serializes program input, so the
+    // AccountInfos from entrypoint::deserialize can be resized and reassigned.
+    // Each account is (key, is_signer, lamports, data, owner), all writable.
+    fn serialize_input(accounts: &[(Pubkey, bool, u64, Vec<u8>, Pubkey)]) -> Vec<u64> {
+        let mut input = (accounts.len() as u64).to_le_bytes().to_vec();
+        for (key, is_signer, lamports, data, owner) in accounts {
+            input.extend_from_slice(&[NON_DUP_MARKER, *is_signer as u8, 1, 0]);
+            input.extend_from_slice(&[0; 4]); // original data length, set by deserialize
+            input.extend_from_slice(key.as_ref());
+            input.extend_from_slice(owner.as_ref());
+            input.extend_from_slice(&lamports.to_le_bytes());
+            input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            input.extend_from_slice(data);
+            input.resize((input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+        }
+        input.extend_from_slice(&0u64.to_le_bytes()); // no instruction data
+        input.extend_from_slice(Pubkey::default().as_ref()); // program id, unused
+
+        // Copy into a u64 buffer so the header fields are aligned
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        for (word, bytes) in aligned.iter_mut().zip(input.chunks(8)) {
+            let mut padded = [0u8; 8];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(padded);
+        }
+        aligned
+    }
+
+    // Input for a vault create: a funded payer, its (not yet created) vault PDA, and the system program
+    fn create_input(program_id: &Pubkey, payer: &Pubkey) -> (Vec<u64>, u8) {
+        let (vault, bump) = Pubkey::find_program_address(&[b"vault", payer.as_ref()], program_id);
+        let input = serialize_input(&[
+            (*payer, true, 1_000_000_000, vec![], system_program::ID),
+            (vault, false, 0, vec![], system_program::ID),
+            (system_program::ID, false, 1, vec![], Pubkey::default()),
+        ]);
+        (input, bump)
+    }
+
+    // Vault account data for `authority`, storing `bump` and unlocking at `unlock_timestamp`
+    fn vault_data(authority: &Pubkey, bump: u8, unlock_timestamp: UnixTimestamp) -> Vec<u8> {
+        let mut data = vec![0u8; VAULT_ACCOUNT_SIZE as usize];
+        let vault_state = VaultState { authority: *authority, unlock_timestamp, bump };
+        vault_state.serialize(&mut &mut data[..]).unwrap();
+        data
+    }
+
+    fn vault_rent_minimum() -> u64 {
+        Rent::default().minimum_balance(VAULT_ACCOUNT_SIZE as usize)
+    }
+
+    #[test]
+    fn timelocked_vault_unlocks_at_unlock_timestamp() {
+        let vault_state = VaultState {
+            authority: Pubkey::new_unique(),
+            unlock_timestamp: 1_700_000_000,
+            bump: 255,
+        };
+
+        assert!(!vault_state.is_unlocked(1_699_999_999));
+        assert!(vault_state.is_unlocked(1_700_000_000));
+        assert!(vault_state.is_unlocked(1_700_000_001));
+    }
+
+    #[test]
+    fn vault_without_lock_is_always_unlocked() {
+        let vault_state = VaultState {
+            authority: Pubkey::new_unique(),
+            unlock_timestamp: 0,
+            bump: 255,
+        };
+
+        assert!(vault_state.is_unlocked(0));
+        assert!(vault_state.is_unlocked(1_700_000_000));
+    }
+
+    // An authority whose canonical vault bump is below 255, so find_program_address needs retries
+    fn authority_with_retries(program_id: &Pubkey) -> (Pubkey, Pubkey, u8) {
+        loop {
+            let authority = Pubkey::new_unique();
+            let (vault, bump) = Pubkey::find_program_address(&[b"vault", authority.as_ref()], program_id);
+            if bump < 255 {
+                return (authority, vault, bump);
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_bump_is_accepted() {
+        let program_id = Pubkey::new_unique();
+        let (authority, _, bump) = authority_with_retries(&program_id);
+
+        assert_eq!(check_vault_bump(&program_id, &authority, bump), Ok(()));
+    }
+
+    #[test]
+    fn non_canonical_bump_is_rejected() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let (_, canonical_bump) = Pubkey::find_program_address(&[b"vault", authority.as_ref()], &program_id);
+
+        // a lower bump that still derives a valid (off-curve) address
+        let other_bump = (0..canonical_bump)
+            .rev()
+            .find(|bump| Pubkey::create_program_address(&[b"vault", authority.as_ref(), &[*bump]], &program_id).is_ok())
+            .unwrap();
+
+        assert_eq!(
+            check_vault_bump(&program_id, &authority, other_bump),
+            Err(VaultError::NonCanonicalBump.into())
+        );
+    }
+
+    #[test]
+    fn withdraw_rebuilds_the_vault_address_from_the_stored_bump() {
+        warp_to(0);
+        let program_id = Pubkey::new_unique();
+        let (authority_key, vault_key, bump) = authority_with_retries(&program_id);
+        let (mut authority_lamports, mut vault_lamports) = (0, vault_rent_minimum() + 1_000);
+        let (mut authority_data, mut data) = (vec![], vault_data(&authority_key, bump, 0));
+        let authority = AccountInfo::new(&authority_key, true, true, &mut authority_lamports, &mut authority_data, &system_program::ID, false, 0);
+        let vault = AccountInfo::new(&vault_key, false, true, &mut vault_lamports, &mut data, &program_id, false, 0);
+
+        assert_eq!(withdraw(&program_id, &[authority.clone(), vault.clone()], 1_000), Ok(()));
+        assert_eq!(authority.lamports(), 1_000);
+        assert_eq!(vault.lamports(), vault_rent_minimum());
+    }
+
+    #[test]
+    fn withdraw_rejects_a_stored_bump_that_derives_another_address() {
+        warp_to(0);
+        let program_id = Pubkey::new_unique();
+        let (authority_key, vault_key, bump) = authority_with_retries(&program_id);
+        // a lower bump that still derives a valid (off-curve) address, just not this vault's
+        let other_bump = (0..bump)
+            .rev()
+            .find(|bump| Pubkey::create_program_address(&[b"vault", authority_key.as_ref(), &[*bump]], &program_id).is_ok())
+            .unwrap();
+        let (mut authority_lamports, mut vault_lamports) = (0, vault_rent_minimum() + 1_000);
+        let (mut authority_data, mut data) = (vec![], vault_data(&authority_key, other_bump, 0));
+        let authority = AccountInfo::new(&authority_key, true, true, &mut authority_lamports, &mut authority_data, &system_program::ID, false, 0);
+        let vault = AccountInfo::new(&vault_key, false, true, &mut vault_lamports, &mut data, &program_id, false, 0);
+
+        assert_eq!(
+            withdraw(&program_id, &[authority.clone(), vault.clone()], 1_000),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(authority.lamports(), 0);
+    }
+
+    #[test]
+    fn timelocked_vault_rejects_withdrawals_until_it_unlocks() {
+        warp_to(1_000);
+        let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut input, bump) = create_input(&program_id, &payer);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let deposit = vault_rent_minimum() + 5_000;
+
+        let create = VaultInstruction::CreateTimelockedVault { vault_bump_seed: bump, lamports: deposit, lock_duration: 100 };
+        assert_eq!(process_instruction(&program_id, &accounts, &create.try_to_vec().unwrap()), Ok(()));
+        let vault_state = VaultState::deserialize(&mut &accounts[1].data.borrow()[..]).unwrap();
+        assert_eq!(vault_state.unlock_timestamp, 1_100);
+        assert_eq!(accounts[1].lamports(), deposit);
+
+        let withdraw = VaultInstruction::Withdraw { lamports: 5_000 }.try_to_vec().unwrap();
+        warp_to(1_099);
+        assert_eq!(
+            process_instruction(&program_id, &accounts[..2], &withdraw),
+            Err(VaultError::StillLocked.into())
+        );
+        assert_eq!(accounts[1].lamports(), deposit);
+
+        warp_to(1_100);
+        assert_eq!(process_instruction(&program_id, &accounts[..2], &withdraw), Ok(()));
+        assert_eq!(accounts[1].lamports(), vault_rent_minimum());
+        assert_eq!(accounts[0].lamports(), 1_000_000_000 - deposit + 5_000);
+    }
+
+    #[test]
+    fn sweep_leaves_the_vault_at_exactly_its_rent_minimum() {
+        warp_to(0);
+        let program_id = Pubkey::new_unique();
+        let (authority_key, vault_key, bump) = authority_with_retries(&program_id);
+        let treasury_key = Pubkey::new_unique();
+        let (mut authority_lamports, mut vault_lamports, mut treasury_lamports) = (0, vault_rent_minimum() + 7_000, 10);
+        let (mut authority_data, mut data, mut treasury_data) = (vec![], vault_data(&authority_key, bump, 0), vec![]);
+        let authority = AccountInfo::new(&authority_key, true, false, &mut authority_lamports, &mut authority_data, &system_program::ID, false, 0);
+        let vault = AccountInfo::new(&vault_key, false, true, &mut vault_lamports, &mut data, &program_id, false, 0);
+        let treasury = AccountInfo::new(&treasury_key, false, true, &mut treasury_lamports, &mut treasury_data, &system_program::ID, false, 0);
+        let accounts = [authority, vault, treasury];
+
+        assert_eq!(sweep_to_treasury(&program_id, &accounts), Ok(()));
+        assert_eq!(accounts[1].lamports(), vault_rent_minimum());
+        assert_eq!(accounts[2].lamports(), 7_010);
+
+        // Nothing left to sweep
+        assert_eq!(sweep_to_treasury(&program_id, &accounts), Ok(()));
+        assert_eq!(accounts[1].lamports(), vault_rent_minimum());
+        assert_eq!(accounts[2].lamports(), 7_010);
+    }
+
+    #[test]
+    fn sweep_waits_for_the_unlock() {
+        warp_to(99);
+        let program_id = Pubkey::new_unique();
+        let (authority_key, vault_key, bump) = authority_with_retries(&program_id);
+        let treasury_key = Pubkey::new_unique();
+        let (mut authority_lamports, mut vault_lamports, mut treasury_lamports) = (0, vault_rent_minimum() + 7_000, 0);
+        let (mut authority_data, mut data, mut treasury_data) = (vec![], vault_data(&authority_key, bump, 100), vec![]);
+        let authority = AccountInfo::new(&authority_key, true, false, &mut authority_lamports, &mut authority_data, &system_program::ID, false, 0);
+        let vault = AccountInfo::new(&vault_key, false, true, &mut vault_lamports, &mut data, &program_id, false, 0);
+        let treasury = AccountInfo::new(&treasury_key, false, true, &mut treasury_lamports, &mut treasury_data, &system_program::ID, false, 0);
+        let accounts = [authority, vault, treasury];
+
+        assert_eq!(sweep_to_treasury(&program_id, &accounts), Err(VaultError::StillLocked.into()));
+        assert_eq!(accounts[2].lamports(), 0);
+
+        warp_to(100);
+        assert_eq!(sweep_to_treasury(&program_id, &accounts), Ok(()));
+        assert_eq!(accounts[2].lamports(), 7_000);
+    }
+
+    #[test]
+    fn withdraw_fails_instead_of_overflowing_the_authority() {
+        warp_to(0);
+        let program_id = Pubkey::new_unique();
+        let (authority_key, vault_key, bump) = authority_with_retries(&program_id);
+        let (mut authority_lamports, mut vault_lamports) = (u64::MAX, vault_rent_minimum() + 1);
+        let (mut authority_data, mut data) = (vec![], vault_data(&authority_key, bump, 0));
+        let authority = AccountInfo::new(&authority_key, true, true, &mut authority_lamports, &mut authority_data, &system_program::ID, false, 0);
+        let vault = AccountInfo::new(&vault_key, false, true, &mut vault_lamports, &mut data, &program_id, false, 0);
+
+        assert_eq!(withdraw(&program_id, &[authority, vault], 1), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn preview_matches_the_vault_a_real_create_makes() {
+        warp_to(0);
+        let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut input, _) = create_input(&program_id, &payer);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        let preview_instruction = VaultInstruction::PreviewCreate.try_to_vec().unwrap();
+        assert_eq!(process_instruction(&program_id, &accounts[..1], &preview_instruction), Ok(()));
+        let (_, return_data) = get_return_data().unwrap();
+        let preview = VaultPreview::try_from_slice(&return_data).unwrap();
+
+        let create = VaultInstruction::CreateWithDeposit { vault_bump_seed: preview.bump, extra_lamports: 0 };
+        assert_eq!(process_instruction(&program_id, &accounts, &create.try_to_vec().unwrap()), Ok(()));
+
+        assert_eq!(*accounts[1].key, preview.vault);
+        assert_eq!(accounts[1].lamports(), preview.rent_lamports);
+        assert_eq!(accounts[0].lamports(), 1_000_000_000 - preview.rent_lamports);
+        assert_eq!(VaultState::deserialize(&mut &accounts[1].data.borrow()[..]).unwrap().bump, preview.bump);
+    }
+
+    #[test]
+    fn balance_logs_show_the_deltas_of_a_create_and_a_withdrawal() {
+        warp_to(0);
+        let (program_id, payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut input, bump) = create_input(&program_id, &payer);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let deposit = vault_rent_minimum() + 2_500;
+
+        let create = VaultInstruction::CreateVault { vault_bump_seed: bump, lamports: deposit };
+        take_logs();
+        assert_eq!(process_instruction(&program_id, &accounts, &create.try_to_vec().unwrap()), Ok(()));
+        let logs = take_logs();
+        assert!(logs.contains(&format!("Payer balance: 1000000000 -> {} (-{})", 1_000_000_000 - deposit, deposit)));
+        assert!(logs.contains(&format!("Vault balance: 0 -> {} (+{})", deposit, deposit)));
+
+        let withdraw = VaultInstruction::Withdraw { lamports: 2_500 };
+        assert_eq!(process_instruction(&program_id, &accounts[..2], &withdraw.try_to_vec().unwrap()), Ok(()));
+        let logs = take_logs();
+        assert!(logs.contains(&format!("Authority balance: {} -> {} (+2500)", 1_000_000_000 - deposit, 1_000_000_000 - vault_rent_minimum())));
+        assert!(logs.contains(&format!("Vault balance: {} -> {} (-2500)", deposit, vault_rent_minimum())));
+    }
+}
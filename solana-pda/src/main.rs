@@ -5,6 +5,73 @@ fn main() {
     println!("=== Solana PDA ===");
     demonstrate_basic_pda();
     demonstrate_ata_calculation();
+    demonstrate_seed_collisions();
+    demonstrate_pda_resolver();
+}
+
+// PDAs derived for one (user_id, account_type) seed pair
+struct SeedDerivation {
+    seeds: (String, String),
+    separate: Pubkey, // seeds passed as two separate byte slices
+    combined: Pubkey, // seeds joined as "user_id-account_type"
+}
+
+struct SeedCollisionReport {
+    derivations: Vec<SeedDerivation>,
+    // index pairs of different inputs whose separate-seed PDAs are the same address
+    collisions: Vec<(usize, usize)>,
+}
+
+impl SeedCollisionReport {
+    fn is_distinct(&self) -> bool {
+        self.collisions.is_empty()
+    }
+}
+
+fn find_seed_collisions(program_id: &Pubkey, seed_pairs: &[(&str, &str)]) -> SeedCollisionReport {
+    let derivations: Vec<SeedDerivation> = seed_pairs
+        .iter()
+        .map(|(user_id, account_type)| {
+            let (separate, _) = Pubkey::find_program_address(
+                &[user_id.as_bytes(), account_type.as_bytes()],
+                program_id,
+            );
+            let combined_seed = format!("{}-{}", user_id, account_type);
+            let (combined, _) = Pubkey::find_program_address(&[combined_seed.as_bytes()], program_id);
+
+            SeedDerivation {
+                seeds: (user_id.to_string(), account_type.to_string()),
+                separate,
+                combined,
+            }
+        })
+        .collect();
+
+    let mut collisions = Vec::new();
+    for i in 0..derivations.len() {
+        for j in (i + 1)..derivations.len() {
+            if derivations[i].seeds != derivations[j].seeds && derivations[i].separate == derivations[j].separate {
+                collisions.push((i, j));
+            }
+        }
+    }
+
+    SeedCollisionReport { derivations, collisions }
+}
+
+// Reverse lookup: find which of the candidate seed sets derives `candidate` under
+// `program_id`, returning that seed set and its bump. Seed sets that can never
+// derive an address (a seed over 32 bytes, too many seeds) are skipped
+fn resolve_pda_seeds<'a>(
+    candidate: &Pubkey,
+    program_id: &Pubkey,
+    seed_sets: &'a [Vec<Vec<u8>>],
+) -> Option<(&'a [Vec<u8>], u8)> {
+    seed_sets.iter().find_map(|seed_set| {
+        let seeds: Vec<&[u8]> = seed_set.iter().map(|seed| seed.as_slice()).collect();
+        let (pda, bump) = Pubkey::try_find_program_address(&seeds, program_id)?;
+        (pda == *candidate).then_some((seed_set.as_slice(), bump))
+    })
 }
 
 fn demonstrate_basic_pda() {
@@ -73,3 +140,199 @@ fn demonstrate_ata_calculation() {
     println!("Calculated ATA Address: {}", ata_addr);
     println!("Addresses Match: {}", expected_ata == ata_addr.to_string());
 }
+
+fn demonstrate_seed_collisions() {
+    println!("=== Seed Collisions ===");
+
+    let program_id = Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap();
+
+    // seeds are hashed back to back with no separator, so "user1" + "23profile"
+    // produces the same bytes as "user123" + "profile"
+    let seed_pairs = [
+        ("user123", "profile"),
+        ("user1", "23profile"),
+        ("user123", "settings"),
+    ];
+
+    let report = find_seed_collisions(&program_id, &seed_pairs);
+
+    for derivation in &report.derivations {
+        println!("Seeds: {:?}", derivation.seeds);
+        println!("  Separate seeds PDA: {}", derivation.separate);
+        println!("  Combined seed PDA: {}", derivation.combined);
+        println!("  Combined differs from separate: {}", derivation.combined != derivation.separate);
+    }
+
+    println!("\nAll separate-seed PDAs distinct: {}", report.is_distinct());
+    for (i, j) in &report.collisions {
+        println!(
+            "Collision: {:?} and {:?} -> {}",
+            report.derivations[*i].seeds, report.derivations[*j].seeds, report.derivations[*i].separate
+        );
+    }
+}
+
+fn demonstrate_pda_resolver() {
+    println!("=== PDA Resolver ===");
+
+    let program_id = Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap();
+
+    // the profile PDA from demonstrate_basic_pda, looked up among a few guesses
+    let (profile_pda, _) = Pubkey::find_program_address(&[b"user123", b"profile"], &program_id);
+    let profile_guesses = vec![
+        vec![b"user123".to_vec(), b"settings".to_vec()],
+        vec![b"user123-profile".to_vec()],
+        vec![b"user123".to_vec(), b"profile".to_vec()],
+    ];
+
+    match resolve_pda_seeds(&profile_pda, &program_id, &profile_guesses) {
+        Some((seeds, bump)) => {
+            let seeds: Vec<String> = seeds.iter().map(|seed| String::from_utf8_lossy(seed).into_owned()).collect();
+            println!("Profile PDA {} resolved to seeds {:?}, bump {}", profile_pda, seeds, bump);
+        }
+        None => println!("Profile PDA {} not derived by any guess", profile_pda),
+    }
+
+    // the ATA from demonstrate_ata_calculation; swapping mint and token program must not match
+    let spl_token_addr = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+    let ata_program_addr = Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+    let sol_addr = Pubkey::from_str("5pWae6RxD3zrYzBmPTMYo1LZ5vef3vfWH6iV3s8n6ZRG").unwrap();
+    let token_addr = Pubkey::from_str("EUGfLUCBAMFvEDk1MZ2SbcZQ54mdczFyFkWVYvVVUcdF").unwrap();
+    let expected_ata = Pubkey::from_str("7X8RKbXhxGATEHwXPVWvZFDL5yZwgf9YyActE93wyhku").unwrap();
+
+    let ata_guesses = vec![
+        vec![sol_addr.to_bytes().to_vec(), token_addr.to_bytes().to_vec(), spl_token_addr.to_bytes().to_vec()],
+        vec![sol_addr.to_bytes().to_vec(), spl_token_addr.to_bytes().to_vec(), token_addr.to_bytes().to_vec()],
+    ];
+
+    match resolve_pda_seeds(&expected_ata, &ata_program_addr, &ata_guesses) {
+        Some((seeds, bump)) => {
+            let seeds: Vec<Pubkey> = seeds.iter().map(|seed| Pubkey::try_from(seed.as_slice()).unwrap()).collect();
+            println!("ATA {} resolved to seeds {:?}, bump {}", expected_ata, seeds, bump);
+        }
+        None => println!("ATA {} not derived by any guess", expected_ata),
+    }
+
+    // an address off every guess resolves to nothing
+    let unrelated = Pubkey::new_unique();
+    println!("Unrelated address resolved: {}", resolve_pda_seeds(&unrelated, &program_id, &profile_guesses).is_some());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program_id() -> Pubkey {
+        Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap()
+    }
+
+    #[test]
+    fn separate_and_combined_seeds_derive_different_pdas() {
+        let report = find_seed_collisions(&program_id(), &[("user123", "profile")]);
+
+        let derivation = &report.derivations[0];
+        assert_eq!(derivation.seeds, ("user123".to_string(), "profile".to_string()));
+        assert_ne!(derivation.separate, derivation.combined);
+        assert!(report.is_distinct());
+    }
+
+    #[test]
+    fn concatenated_seeds_collide() {
+        let report = find_seed_collisions(&program_id(), &[("ab", "c"), ("a", "bc")]);
+
+        assert_eq!(report.collisions, vec![(0, 1)]);
+        assert!(!report.is_distinct());
+        // the "-" separator in the combined seed keeps them apart
+        assert_ne!(report.derivations[0].combined, report.derivations[1].combined);
+    }
+
+    #[test]
+    fn demo_seed_pairs_report_the_user123_collision_only() {
+        let report = find_seed_collisions(
+            &program_id(),
+            &[("user123", "profile"), ("user1", "23profile"), ("user123", "settings")],
+        );
+
+        assert_eq!(report.collisions, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn repeated_identical_seeds_are_not_collisions() {
+        let report = find_seed_collisions(&program_id(), &[("user123", "profile"), ("user123", "profile")]);
+
+        assert_eq!(report.derivations[0].separate, report.derivations[1].separate);
+        assert!(report.is_distinct());
+    }
+
+    fn ata_example() -> (Pubkey, Vec<Vec<u8>>, Pubkey) {
+        let spl_token_addr = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let ata_program_addr = Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+        let sol_addr = Pubkey::from_str("5pWae6RxD3zrYzBmPTMYo1LZ5vef3vfWH6iV3s8n6ZRG").unwrap();
+        let token_addr = Pubkey::from_str("EUGfLUCBAMFvEDk1MZ2SbcZQ54mdczFyFkWVYvVVUcdF").unwrap();
+        let seeds = vec![sol_addr.to_bytes().to_vec(), spl_token_addr.to_bytes().to_vec(), token_addr.to_bytes().to_vec()];
+        (Pubkey::from_str("7X8RKbXhxGATEHwXPVWvZFDL5yZwgf9YyActE93wyhku").unwrap(), seeds, ata_program_addr)
+    }
+
+    #[test]
+    fn resolves_profile_pda_among_guesses() {
+        let (profile_pda, _) = Pubkey::find_program_address(&[b"user123", b"profile"], &program_id());
+        let guesses = vec![
+            vec![b"user123".to_vec(), b"settings".to_vec()],
+            vec![b"user123-profile".to_vec()],
+            vec![b"user123".to_vec(), b"profile".to_vec()],
+        ];
+
+        let (seeds, _) = resolve_pda_seeds(&profile_pda, &program_id(), &guesses).unwrap();
+        assert_eq!(seeds, guesses[2].as_slice());
+    }
+
+    #[test]
+    fn resolves_ata_only_with_correct_seed_order() {
+        let (expected_ata, seeds, ata_program_addr) = ata_example();
+        let swapped = vec![seeds[0].clone(), seeds[2].clone(), seeds[1].clone()];
+
+        assert!(resolve_pda_seeds(&expected_ata, &ata_program_addr, std::slice::from_ref(&swapped)).is_none());
+
+        let guesses = vec![swapped, seeds.clone()];
+        let (resolved, _) = resolve_pda_seeds(&expected_ata, &ata_program_addr, &guesses).unwrap();
+        assert_eq!(resolved, seeds.as_slice());
+    }
+
+    #[test]
+    fn unrelated_address_resolves_to_nothing() {
+        let guesses = vec![vec![b"user123".to_vec(), b"profile".to_vec()]];
+
+        assert!(resolve_pda_seeds(&Pubkey::new_unique(), &program_id(), &guesses).is_none());
+        assert!(resolve_pda_seeds(&Pubkey::new_unique(), &program_id(), &[]).is_none());
+    }
+
+    #[test]
+    fn invalid_seed_sets_are_skipped() {
+        let (profile_pda, _) = Pubkey::find_program_address(&[b"user123", b"profile"], &program_id());
+        let guesses = vec![
+            vec![vec![7u8; 33]],
+            vec![b"s".to_vec(); 17],
+            vec![b"user123".to_vec(), b"profile".to_vec()],
+        ];
+
+        assert!(resolve_pda_seeds(&profile_pda, &program_id(), &guesses[..2]).is_none());
+
+        let (seeds, _) = resolve_pda_seeds(&profile_pda, &program_id(), &guesses).unwrap();
+        assert_eq!(seeds, guesses[2].as_slice());
+    }
+
+    #[test]
+    fn resolved_bump_is_canonical() {
+        let (expected_ata, seeds, ata_program_addr) = ata_example();
+        let seed_refs: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+        let (_, canonical_bump) = Pubkey::find_program_address(&seed_refs, &ata_program_addr);
+
+        let (_, bump) = resolve_pda_seeds(&expected_ata, &ata_program_addr, std::slice::from_ref(&seeds)).unwrap();
+        assert_eq!(bump, canonical_bump);
+
+        let mut signer_seeds = seed_refs;
+        let bump_seed = [bump];
+        signer_seeds.push(&bump_seed);
+        assert_eq!(Pubkey::create_program_address(&signer_seeds, &ata_program_addr).unwrap(), expected_ata);
+    }
+}
@@ -0,0 +1,68 @@
+//! Rent helpers shared by the programs in this workshop that create or resize accounts
+
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+    rent::Rent,
+};
+
+/// Check that `account` holds enough lamports to be rent exempt at its current size
+///
+/// Call after creating or resizing an account to catch funding mistakes early.
+/// `error` is returned when the check fails, so each program keeps its own
+/// not-rent-exempt error code.
+pub fn assert_rent_exempt(
+    account: &AccountInfo,
+    rent: &Rent,
+    error: impl Into<ProgramError>,
+) -> ProgramResult {
+    if !rent.is_exempt(account.lamports(), account.data_len()) {
+        msg!(
+            "Account {} holds {} lamports, needs {} to be rent exempt",
+            account.key,
+            account.lamports(),
+            rent.minimum_balance(account.data_len())
+        );
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    fn check(lamports: u64, data_len: usize) -> ProgramResult {
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = lamports;
+        let mut data = vec![0u8; data_len];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        assert_rent_exempt(&account, &Rent::default(), ProgramError::AccountNotRentExempt)
+    }
+
+    #[test]
+    fn rent_exempt_account_passes() {
+        let rent = Rent::default();
+
+        assert_eq!(check(rent.minimum_balance(64), 64), Ok(()));
+        assert_eq!(check(rent.minimum_balance(64) + 1, 64), Ok(()));
+        assert_eq!(check(rent.minimum_balance(0), 0), Ok(()));
+    }
+
+    #[test]
+    fn underfunded_account_returns_the_callers_error() {
+        let rent = Rent::default();
+
+        assert_eq!(check(rent.minimum_balance(64) - 1, 64), Err(ProgramError::AccountNotRentExempt));
+        assert_eq!(check(0, 0), Err(ProgramError::AccountNotRentExempt));
+
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut lamports = 0;
+        let mut data = vec![0u8; 64];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(
+            assert_rent_exempt(&account, &rent, ProgramError::Custom(7)),
+            Err(ProgramError::Custom(7))
+        );
+    }
+}
@@ -4,11 +4,13 @@ use solana_program::{
     clock::{Clock, UnixTimestamp},
     entrypoint,
     entrypoint::ProgramResult,
+    hash::{hashv, Hash},
+    instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     pubkey::Pubkey,
-    rent::Rent,
+    rent::{Rent, ACCOUNT_STORAGE_OVERHEAD},
     system_instruction,
     sysvar::{
         self, clock, epoch_schedule::EpochSchedule, fees::Fees, instructions::Instructions,
@@ -16,6 +18,7 @@ use solana_program::{
         slot_history::SlotHistory, stake_history::StakeHistory, Sysvar,
     },
 };
+use solana_rent_utils::assert_rent_exempt;
 
 // Define instruction types
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -46,8 +49,68 @@ pub enum SysvarInstruction {
     
     // Get multiple sysvars
     ShowMultipleSysvars,
+    
+    // Check if a hypothetical account would be rent exempt
+    CheckRentExemptionForSize { size: u64, lamports: u64 },
+    
+    // Create a PDA timer that expires after a duration
+    CreateTimer { seed: String, duration_seconds: i64 },
+    
+    // Report remaining time on a PDA timer
+    CheckTimer { seed: String },
+    
+    // Probe which sysvars are available on this cluster
+    DetectClusterFeatures,
+    
+    // Create a PDA account only if it does not exist yet
+    GetOrCreatePda { seed: String, space: u64 },
+    
+    // Check a PDA against its seed without touching the account
+    VerifyPda { seed: String },
+    
+    // Estimate signature fees, falling back to a default if Fees is unavailable
+    EstimateTransactionCost { num_signatures: u64 },
+    
+    // Store the current slot and timestamp in a PDA
+    CaptureClockSnapshot { seed: String },
+    
+    // Compare a stored snapshot against the current Clock
+    DiffClockSnapshot { seed: String },
+    
+    // Return the program version
+    GetVersion,
+    
+    // Log every sysvar as one key=value line each
+    DumpSysvarsCompact,
+    
+    // Report whether the current slot is within `window` slots of an epoch boundary
+    CheckEpochBoundary { window: u64 },
+    
+    // Split the rent-exempt minimum for `size` into one year of rent and the threshold multiplier's share
+    ComputeRentBreakdown { size: u64 },
+    
+    // Return the ids of the well-known sysvars as concatenated 32-byte pubkeys
+    ListSysvarIds,
+    
+    // Mix the most recent slot hash with a nonce into a 32-byte pseudo-random seed (not secure randomness)
+    DeriveRandomSeed { nonce: u64 },
+    
+    // Sum the rent-exempt minimums for a batch of account sizes, returned as u64 LE
+    BudgetRentForSizes { sizes: Vec<u64> },
+    
+    // Report how deep in the CPI stack this program runs (1 = top level), returned as u64 LE
+    ShowStackHeight,
+    
+    // Lamports to prepay `years` of rent for `size` bytes, returned as u64 LE
+    CalculateRentForYears { size: u64, years: u64 },
+    
+    // Classify a PDA's balance against its exemption minimum, returned as one RENT_STATUS_* byte
+    MonitorRentStatus { account_seed: String, warn_buffer_lamports: u64 },
 }
 
+// Crate version, returned by GetVersion
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // Define program entrypoint
 entrypoint!(process_instruction);
 
@@ -79,7 +142,7 @@ pub fn process_instruction(
         
         // Create a PDA account
         SysvarInstruction::CreatePdaAccount { space, seed } => {
-            create_pda_account(program_id, accounts, space, &seed)
+            create_pda_account(program_id, accounts, space, PDA_SEED_PREFIX, &seed)
         }
         
         // Get account creation time
@@ -94,14 +157,99 @@ pub fn process_instruction(
         
         // Get multiple sysvars
         SysvarInstruction::ShowMultipleSysvars => show_multiple_sysvars(),
+        
+        // Check if a hypothetical account would be rent exempt
+        SysvarInstruction::CheckRentExemptionForSize { size, lamports } => {
+            check_rent_exemption_for_size(size, lamports)
+        }
+        
+        // Create a PDA timer that expires after a duration
+        SysvarInstruction::CreateTimer { seed, duration_seconds } => {
+            create_timer(program_id, accounts, &seed, duration_seconds)
+        }
+        
+        // Report remaining time on a PDA timer
+        SysvarInstruction::CheckTimer { seed } => check_timer(program_id, accounts, &seed),
+        
+        // Probe which sysvars are available on this cluster
+        SysvarInstruction::DetectClusterFeatures => detect_cluster_features(),
+        
+        // Create a PDA account only if it does not exist yet
+        SysvarInstruction::GetOrCreatePda { seed, space } => {
+            get_or_create_pda(program_id, accounts, &seed, space)
+        }
+        
+        // Check a PDA against its seed without touching the account
+        SysvarInstruction::VerifyPda { seed } => verify_pda(program_id, accounts, &seed),
+        
+        // Estimate signature fees, falling back to a default if Fees is unavailable
+        SysvarInstruction::EstimateTransactionCost { num_signatures } => {
+            estimate_transaction_cost(num_signatures)
+        }
+        
+        // Store the current slot and timestamp in a PDA
+        SysvarInstruction::CaptureClockSnapshot { seed } => {
+            capture_clock_snapshot(program_id, accounts, &seed)
+        }
+        
+        // Compare a stored snapshot against the current Clock
+        SysvarInstruction::DiffClockSnapshot { seed } => {
+            diff_clock_snapshot(program_id, accounts, &seed)
+        }
+        
+        // Return the program version
+        SysvarInstruction::GetVersion => get_version(),
+        
+        // Log every sysvar as one key=value line each
+        SysvarInstruction::DumpSysvarsCompact => dump_sysvars_compact(),
+        
+        // Report whether the current slot is within `window` slots of an epoch boundary
+        SysvarInstruction::CheckEpochBoundary { window } => check_epoch_boundary(window),
+        
+        // Split the rent-exempt minimum for `size` into one year of rent and the threshold multiplier's share
+        SysvarInstruction::ComputeRentBreakdown { size } => compute_rent_breakdown(size),
+        
+        // Return the ids of the well-known sysvars as concatenated 32-byte pubkeys
+        SysvarInstruction::ListSysvarIds => list_sysvar_ids(),
+        
+        // Mix the most recent slot hash with a nonce into a 32-byte pseudo-random seed (not secure randomness)
+        SysvarInstruction::DeriveRandomSeed { nonce } => derive_random_seed(accounts, nonce),
+        
+        // Sum the rent-exempt minimums for a batch of account sizes, returned as u64 LE
+        SysvarInstruction::BudgetRentForSizes { sizes } => budget_rent_for_sizes(&sizes),
+        
+        // Report how deep in the CPI stack this program runs (1 = top level), returned as u64 LE
+        SysvarInstruction::ShowStackHeight => show_stack_height(),
+        
+        // Lamports to prepay `years` of rent for `size` bytes, returned as u64 LE
+        SysvarInstruction::CalculateRentForYears { size, years } => calculate_rent_for_years(size, years),
+        
+        // Classify a PDA's balance against its exemption minimum, returned as one RENT_STATUS_* byte
+        SysvarInstruction::MonitorRentStatus { account_seed, warn_buffer_lamports } => {
+            monitor_rent_status(program_id, accounts, &account_seed, warn_buffer_lamports)
+        }
     }
 }
 
+// Log a section header shared by the show_* handlers
+fn log_section(title: &str) {
+    msg!("===== {} =====", title);
+}
+
+// Format one sysvar as "sysvar=<name> key=value ..." so logs can be split on spaces and '='
+fn format_compact(sysvar: &str, fields: &[(&str, String)]) -> String {
+    let mut line = format!("sysvar={}", sysvar);
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, value));
+    }
+    line
+}
+
 // Get Clock sysvar directly
 fn show_clock() -> ProgramResult {
     let clock = Clock::get()?;
     
-    msg!("===== Clock Sysvar (direct) =====");
+    log_section("Clock Sysvar (direct)");
     msg!("Slot: {}", clock.slot);
     msg!("Epoch: {}", clock.epoch);
     msg!("Unix Timestamp: {}", clock.unix_timestamp);
@@ -115,7 +263,7 @@ fn show_clock() -> ProgramResult {
 fn show_rent() -> ProgramResult {
     let rent = Rent::get()?;
     
-    msg!("===== Rent Sysvar (direct) =====");
+    log_section("Rent Sysvar (direct)");
     msg!("Lamports per byte year: {}", rent.lamports_per_byte_year);
     msg!("Exemption threshold: {}", rent.exemption_threshold);
     msg!("Burn percent: {}", rent.burn_percent);
@@ -127,7 +275,7 @@ fn show_rent() -> ProgramResult {
 fn show_epoch_schedule() -> ProgramResult {
     let epoch_schedule = EpochSchedule::get()?;
     
-    msg!("===== EpochSchedule Sysvar (direct) =====");
+    log_section("EpochSchedule Sysvar (direct)");
     msg!("Slots per epoch: {}", epoch_schedule.slots_per_epoch);
     msg!("Leader schedule slot offset: {}", epoch_schedule.leader_schedule_slot_offset);
     msg!("Warmup: {}", epoch_schedule.warmup);
@@ -141,7 +289,7 @@ fn show_epoch_schedule() -> ProgramResult {
 fn show_fees() -> ProgramResult {
     let fees = Fees::get()?;
     
-    msg!("===== Fees Sysvar (direct) =====");
+    log_section("Fees Sysvar (direct)");
     msg!("Lamports per signature: {}", fees.fee_calculator.lamports_per_signature);
     
     Ok(())
@@ -159,7 +307,7 @@ fn show_clock_from_account(accounts: &[AccountInfo]) -> ProgramResult {
     
     let clock = Clock::from_account_info(clock_sysvar_info)?;
     
-    msg!("===== Clock Sysvar (from account) =====");
+    log_section("Clock Sysvar (from account)");
     msg!("Slot: {}", clock.slot);
     msg!("Epoch: {}", clock.epoch);
     msg!("Unix Timestamp: {}", clock.unix_timestamp);
@@ -181,7 +329,7 @@ fn show_rent_from_account(accounts: &[AccountInfo]) -> ProgramResult {
     
     let rent = Rent::from_account_info(rent_sysvar_info)?;
     
-    msg!("===== Rent Sysvar (from account) =====");
+    log_section("Rent Sysvar (from account)");
     msg!("Lamports per byte year: {}", rent.lamports_per_byte_year);
     msg!("Exemption threshold: {}", rent.exemption_threshold);
     msg!("Burn percent: {}", rent.burn_percent);
@@ -201,7 +349,7 @@ fn show_epoch_schedule_from_account(accounts: &[AccountInfo]) -> ProgramResult {
     
     let epoch_schedule = EpochSchedule::from_account_info(epoch_schedule_sysvar_info)?;
     
-    msg!("===== EpochSchedule Sysvar (from account) =====");
+    log_section("EpochSchedule Sysvar (from account)");
     msg!("Slots per epoch: {}", epoch_schedule.slots_per_epoch);
     msg!("Leader schedule slot offset: {}", epoch_schedule.leader_schedule_slot_offset);
     msg!("Warmup: {}", epoch_schedule.warmup);
@@ -223,7 +371,7 @@ fn show_fees_from_account(accounts: &[AccountInfo]) -> ProgramResult {
     
     let fees = Fees::from_account_info(fees_sysvar_info)?;
     
-    msg!("===== Fees Sysvar (from account) =====");
+    log_section("Fees Sysvar (from account)");
     msg!("Lamports per signature: {}", fees.fee_calculator.lamports_per_signature);
     
     Ok(())
@@ -233,13 +381,20 @@ fn show_fees_from_account(accounts: &[AccountInfo]) -> ProgramResult {
 fn calculate_rent(size: u64) -> ProgramResult {
     let rent = Rent::get()?;
     
-    let minimum_balance = rent.minimum_balance(size as usize);
-    let yearly_rent = rent.lamports_per_byte_year * size;
+    // Huge sizes overflow u64 lamports; fail cleanly instead of wrapping or panicking.
+    // RentBreakdown checks the same (storage overhead + size) * rate product minimum_balance uses.
+    let breakdown = match RentBreakdown::new(&rent, size) {
+        Ok(breakdown) => breakdown,
+        Err(err) => {
+            msg!("Error: rent for {} bytes overflows u64", size);
+            return Err(err);
+        }
+    };
     
-    msg!("===== Rent Calculation =====");
+    log_section("Rent Calculation");
     msg!("Account size: {} bytes", size);
-    msg!("Minimum balance for rent exemption: {} lamports", minimum_balance);
-    msg!("Yearly rent: {} lamports", yearly_rent);
+    msg!("Minimum balance for rent exemption: {} lamports", breakdown.minimum_balance);
+    msg!("Yearly rent: {} lamports ({} billable bytes)", breakdown.base_lamports, breakdown.billable_bytes);
     
     // Calculate rent for different account sizes
     let sizes = [0, 10, 100, 1000, 10000];
@@ -253,11 +408,18 @@ fn calculate_rent(size: u64) -> ProgramResult {
     Ok(())
 }
 
-// Create PDA account
+// Seed prefixes for each kind of PDA, so a timer or snapshot can never be passed off as
+// another kind of account. Their first bytes differ, so no prefix + seed can collide.
+pub const PDA_SEED_PREFIX: &[u8] = b"pda";
+pub const TIMER_SEED_PREFIX: &[u8] = b"timer";
+pub const SNAPSHOT_SEED_PREFIX: &[u8] = b"snapshot";
+
+// Create PDA account at [prefix, seed]
 fn create_pda_account(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     space: u64,
+    prefix: &[u8],
     seed: &str,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -270,8 +432,14 @@ fn create_pda_account(
         return Err(ProgramError::IncorrectProgramId);
     }
     
+    // The creation timestamp below takes the first 8 bytes
+    if space < 8 {
+        msg!("Error: PDA account needs at least 8 bytes for the creation timestamp, got {}", space);
+        return Err(ProgramError::InvalidArgument);
+    }
+    
     // Calculate PDA and bump seed
-    let seeds = &[seed.as_bytes()];
+    let seeds = &[prefix, seed.as_bytes()];
     let (expected_pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
     
     // Verify provided PDA account matches calculated PDA
@@ -282,6 +450,14 @@ fn create_pda_account(
         return Err(ProgramError::InvalidArgument);
     }
     
+    // The system program refuses to create an account that already holds lamports or data,
+    // so fail here with a clear message instead of an opaque CPI error
+    if pda_account.lamports() > 0 || !pda_account.data_is_empty() {
+        msg!("Error: PDA account for seed \"{}\" already exists", seed);
+        msg!("Use a different seed, or GetOrCreatePda to reuse the existing account");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
     // Get Rent sysvar
     let rent = Rent::get()?;
     let lamports = rent.minimum_balance(space as usize);
@@ -293,7 +469,7 @@ fn create_pda_account(
     msg!("Lamports: {}", lamports);
     
     // Create PDA account
-    let seeds_with_bump = &[seed.as_bytes(), &[bump_seed]];
+    let seeds_with_bump = &[prefix, seed.as_bytes(), &[bump_seed]];
     
     invoke_signed(
         &system_instruction::create_account(
@@ -310,6 +486,7 @@ fn create_pda_account(
         ],
         &[seeds_with_bump],
     )?;
+    assert_rent_exempt(pda_account, &rent, ProgramError::AccountNotRentExempt)?;
     
     // Get current time and store in account data
     let clock = Clock::get()?;
@@ -335,7 +512,7 @@ fn get_account_creation_time(
     let pda_account = next_account_info(account_info_iter)?;
     
     // Calculate PDA
-    let seeds = &[account_seed.as_bytes()];
+    let seeds = &[PDA_SEED_PREFIX, account_seed.as_bytes()];
     let (expected_pda, _) = Pubkey::find_program_address(seeds, program_id);
     
     // Verify provided PDA account matches calculated PDA
@@ -343,6 +520,18 @@ fn get_account_creation_time(
         return Err(ProgramError::InvalidArgument);
     }
     
+    // A PDA that was never created has no data and is still owned by the system program
+    if pda_account.data_is_empty() {
+        msg!("Account {} does not exist", pda_account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+    
+    // Only accounts created by this program carry a timestamp in the first 8 bytes
+    if pda_account.owner != program_id {
+        msg!("Account {} is not owned by this program", pda_account.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
     // Read timestamp from account data
     let data = pda_account.try_borrow_data()?;
     if data.len() < 8 {
@@ -352,6 +541,14 @@ fn get_account_creation_time(
     let timestamp_bytes = [data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7]];
     let creation_timestamp = i64::from_le_bytes(timestamp_bytes);
     
+    // All-zero bytes mean nothing was stamped, so don't report an age since epoch 0
+    if creation_timestamp == 0 {
+        log_section("Account Creation Time");
+        msg!("Account: {}", pda_account.key);
+        msg!("Creation time not recorded");
+        return Ok(());
+    }
+    
     // Get current time
     let clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp;
@@ -360,7 +557,7 @@ fn get_account_creation_time(
     let account_age_seconds = current_timestamp - creation_timestamp;
     let account_age_days = account_age_seconds / (24 * 60 * 60);
     
-    msg!("===== Account Creation Time =====");
+    log_section("Account Creation Time");
     msg!("Account: {}", pda_account.key);
     msg!("Creation timestamp: {}", creation_timestamp);
     msg!("Current timestamp: {}", current_timestamp);
@@ -379,7 +576,7 @@ fn check_rent_exemption(
     let pda_account = next_account_info(account_info_iter)?;
     
     // Calculate PDA
-    let seeds = &[account_seed.as_bytes()];
+    let seeds = &[PDA_SEED_PREFIX, account_seed.as_bytes()];
     let (expected_pda, _) = Pubkey::find_program_address(seeds, program_id);
     
     // Verify provided PDA account matches calculated PDA
@@ -393,7 +590,7 @@ fn check_rent_exemption(
     // Check if account is exempt from rent
     let is_exempt = rent.is_exempt(pda_account.lamports(), pda_account.data_len());
     
-    msg!("===== Rent Exemption Check =====");
+    log_section("Rent Exemption Check");
     msg!("Account: {}", pda_account.key);
     msg!("Account size: {} bytes", pda_account.data_len());
     msg!("Account balance: {} lamports", pda_account.lamports());
@@ -432,7 +629,7 @@ fn show_multiple_sysvars() -> ProgramResult {
     let slot_history_result = SlotHistory::get();
     let stake_history_result = <StakeHistory as Sysvar>::get();
     
-    msg!("===== Multiple Sysvars =====");
+    log_section("Multiple Sysvars");
     
     // Clock info
     msg!("\nClock:");
@@ -457,4 +654,1328 @@ fn show_multiple_sysvars() -> ProgramResult {
     msg!("  StakeHistory: {}", if stake_history_result.is_ok() { "Available" } else { "Not available" });
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Whether an account of `size` bytes holding `lamports` is rent exempt, plus the minimum balance.
+// Goes through RentBreakdown so sizes whose rent overflows u64 fail instead of wrapping.
+fn rent_exemption_for_size(rent: &Rent, size: u64, lamports: u64) -> Result<(bool, u64), ProgramError> {
+    let minimum_balance = RentBreakdown::new(rent, size)?.minimum_balance;
+    Ok((lamports >= minimum_balance, minimum_balance))
+}
+
+// Check if an account of the given size and balance would be rent exempt
+fn check_rent_exemption_for_size(size: u64, lamports: u64) -> ProgramResult {
+    let rent = Rent::get()?;
+    
+    let (is_exempt, minimum_balance) = match rent_exemption_for_size(&rent, size, lamports) {
+        Ok(result) => result,
+        Err(err) => {
+            msg!("Error: rent for {} bytes overflows u64", size);
+            return Err(err);
+        }
+    };
+    
+    log_section("Rent Exemption Check (by size)");
+    msg!("Account size: {} bytes", size);
+    msg!("Account balance: {} lamports", lamports);
+    msg!("Minimum required for exemption: {} lamports", minimum_balance);
+    
+    if is_exempt {
+        msg!("Account WOULD BE exempt from rent");
+    } else {
+        msg!("Account would NOT be exempt from rent");
+        msg!("Additional lamports needed for exemption: {}", minimum_balance - lamports);
+    }
+    
+    // Return the result as a single byte (1 = exempt, 0 = not exempt)
+    set_return_data(&[is_exempt as u8]);
+    
+    Ok(())
+}
+
+// Timer account layout: created_at (i64) followed by expiry (i64)
+const TIMER_ACCOUNT_SIZE: u64 = 16;
+
+// Create a PDA timer
+fn create_timer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    seed: &str,
+    duration_seconds: i64,
+) -> ProgramResult {
+    if duration_seconds < 0 {
+        msg!("Error: Timer duration cannot be negative");
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Creates the PDA and stores the creation timestamp in the first 8 bytes
+    create_pda_account(program_id, accounts, TIMER_ACCOUNT_SIZE, TIMER_SEED_PREFIX, seed)?;
+    
+    let account_info_iter = &mut accounts.iter();
+    let _payer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    let mut data = pda_account.try_borrow_mut_data()?;
+    let created_at = UnixTimestamp::from_le_bytes(data[0..8].try_into().unwrap());
+    let expiry = created_at
+        .checked_add(duration_seconds)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    // Store expiry in the next 8 bytes
+    data[8..16].copy_from_slice(&expiry.to_le_bytes());
+    
+    msg!("Timer created, expires at timestamp: {}", expiry);
+    
+    Ok(())
+}
+
+// Report remaining seconds on a PDA timer
+fn check_timer(program_id: &Pubkey, accounts: &[AccountInfo], seed: &str) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    // Calculate PDA
+    let seeds = &[TIMER_SEED_PREFIX, seed.as_bytes()];
+    let (expected_pda, _) = Pubkey::find_program_address(seeds, program_id);
+    
+    // Verify provided PDA account matches calculated PDA
+    if expected_pda != *pda_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Read timestamps from account data
+    let data = pda_account.try_borrow_data()?;
+    if data.len() < TIMER_ACCOUNT_SIZE as usize {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let created_at = UnixTimestamp::from_le_bytes(data[0..8].try_into().unwrap());
+    let expiry = UnixTimestamp::from_le_bytes(data[8..16].try_into().unwrap());
+    
+    // Get current time
+    let clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp;
+    
+    log_section("Timer");
+    msg!("Account: {}", pda_account.key);
+    msg!("Created at: {}", created_at);
+    msg!("Expires at: {}", expiry);
+    msg!("Current timestamp: {}", current_timestamp);
+    
+    if current_timestamp >= expiry {
+        msg!("Timer expired");
+    } else {
+        msg!("Remaining: {} seconds", expiry - current_timestamp);
+    }
+    
+    Ok(())
+}
+
+// Bits returned by DetectClusterFeatures, one per sysvar
+pub const SYSVAR_CLOCK: u32 = 1 << 0;
+pub const SYSVAR_RENT: u32 = 1 << 1;
+pub const SYSVAR_EPOCH_SCHEDULE: u32 = 1 << 2;
+pub const SYSVAR_FEES: u32 = 1 << 3;
+pub const SYSVAR_SLOT_HASHES: u32 = 1 << 4;
+pub const SYSVAR_SLOT_HISTORY: u32 = 1 << 5;
+pub const SYSVAR_STAKE_HISTORY: u32 = 1 << 6;
+
+// Probe each sysvar and return a bitmask of the available ones
+#[allow(deprecated)]
+fn detect_cluster_features() -> ProgramResult {
+    let probes = [
+        ("Clock", SYSVAR_CLOCK, Clock::get().is_ok()),
+        ("Rent", SYSVAR_RENT, Rent::get().is_ok()),
+        ("EpochSchedule", SYSVAR_EPOCH_SCHEDULE, EpochSchedule::get().is_ok()),
+        ("Fees", SYSVAR_FEES, Fees::get().is_ok()),
+        ("SlotHashes", SYSVAR_SLOT_HASHES, <SlotHashes as Sysvar>::get().is_ok()),
+        ("SlotHistory", SYSVAR_SLOT_HISTORY, SlotHistory::get().is_ok()),
+        ("StakeHistory", SYSVAR_STAKE_HISTORY, <StakeHistory as Sysvar>::get().is_ok()),
+    ];
+    
+    let mut features: u32 = 0;
+    
+    log_section("Cluster Sysvar Features");
+    for (name, bit, available) in probes.iter() {
+        if *available {
+            features |= bit;
+        }
+        msg!("  {}: {}", name, if *available { "Available" } else { "Not available" });
+    }
+    msg!("Feature bitmask: {:#010b}", features);
+    
+    // Return the bitmask as little-endian u32
+    set_return_data(&features.to_le_bytes());
+    
+    Ok(())
+}
+
+// Create the PDA on first call, otherwise report the existing account
+fn get_or_create_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    seed: &str,
+    space: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let _payer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    let created = if pda_account.owner == program_id && !pda_account.data_is_empty() {
+        // Calculate PDA
+        let seeds = &[PDA_SEED_PREFIX, seed.as_bytes()];
+        let (expected_pda, _) = Pubkey::find_program_address(seeds, program_id);
+        
+        // Verify provided PDA account matches calculated PDA
+        if expected_pda != *pda_account.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        
+        let data = pda_account.try_borrow_data()?;
+        if data.len() < 8 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let created_at = UnixTimestamp::from_le_bytes(data[0..8].try_into().unwrap());
+        
+        msg!("PDA account {} already exists, created at timestamp: {}", pda_account.key, created_at);
+        false
+    } else {
+        // Verifies the PDA and system program, then stores the creation timestamp
+        create_pda_account(program_id, accounts, space, PDA_SEED_PREFIX, seed)?;
+        true
+    };
+    
+    // Return a single byte (1 = created, 0 = already existed)
+    set_return_data(&[created as u8]);
+    
+    Ok(())
+}
+
+// Derive the PDA for a seed and compare it with the provided account
+fn verify_pda(program_id: &Pubkey, accounts: &[AccountInfo], seed: &str) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    // Calculate PDA and bump seed
+    let seeds = &[PDA_SEED_PREFIX, seed.as_bytes()];
+    let (expected_pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
+    let matches = expected_pda == *pda_account.key;
+    
+    log_section("PDA Verification");
+    msg!("Seed: {}", seed);
+    msg!("Expected: {}", expected_pda);
+    msg!("Provided: {}", pda_account.key);
+    msg!("Bump seed: {}", bump_seed);
+    msg!("Match: {}", matches);
+    
+    // Return the bump seed followed by the match flag (1 = match, 0 = mismatch)
+    set_return_data(&[bump_seed, matches as u8]);
+    
+    Ok(())
+}
+
+// Used when the deprecated Fees sysvar is not available on the cluster
+pub const DEFAULT_LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+// Estimate the signature fee for a transaction
+// Fees is deprecated but still present on many clusters, so try it first
+#[allow(deprecated)]
+fn estimate_transaction_cost(num_signatures: u64) -> ProgramResult {
+    let (lamports_per_signature, source) = match Fees::get() {
+        Ok(fees) => (fees.fee_calculator.lamports_per_signature, "Fees sysvar"),
+        Err(_) => (DEFAULT_LAMPORTS_PER_SIGNATURE, "default"),
+    };
+    
+    let total_cost = num_signatures
+        .checked_mul(lamports_per_signature)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    log_section("Transaction Cost Estimate");
+    msg!("Lamports per signature: {} (source: {})", lamports_per_signature, source);
+    msg!("Signatures: {}", num_signatures);
+    msg!("Estimated cost: {} lamports", total_cost);
+    
+    // Return the total cost as little-endian u64
+    set_return_data(&total_cost.to_le_bytes());
+    
+    Ok(())
+}
+
+// Snapshot account layout: timestamp (i64) followed by slot (u64)
+const SNAPSHOT_ACCOUNT_SIZE: u64 = 16;
+
+// Create a PDA holding the current Clock slot and timestamp
+fn capture_clock_snapshot(program_id: &Pubkey, accounts: &[AccountInfo], seed: &str) -> ProgramResult {
+    // Creates the PDA and stores the current timestamp in the first 8 bytes
+    create_pda_account(program_id, accounts, SNAPSHOT_ACCOUNT_SIZE, SNAPSHOT_SEED_PREFIX, seed)?;
+    
+    let account_info_iter = &mut accounts.iter();
+    let _payer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    // Store the slot in the next 8 bytes
+    let slot = Clock::get()?.slot;
+    let mut data = pda_account.try_borrow_mut_data()?;
+    data[8..16].copy_from_slice(&slot.to_le_bytes());
+    
+    msg!("Clock snapshot captured at slot: {}", slot);
+    
+    Ok(())
+}
+
+// Report slots and seconds elapsed since a snapshot, and the implied slot time
+fn diff_clock_snapshot(program_id: &Pubkey, accounts: &[AccountInfo], seed: &str) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    // Calculate PDA
+    let seeds = &[SNAPSHOT_SEED_PREFIX, seed.as_bytes()];
+    let (expected_pda, _) = Pubkey::find_program_address(seeds, program_id);
+    
+    // Verify provided PDA account matches calculated PDA
+    if expected_pda != *pda_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Read snapshot from account data
+    let data = pda_account.try_borrow_data()?;
+    if data.len() < SNAPSHOT_ACCOUNT_SIZE as usize {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let snapshot_timestamp = UnixTimestamp::from_le_bytes(data[0..8].try_into().unwrap());
+    let snapshot_slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    
+    // Get current time
+    let clock = Clock::get()?;
+    let slots_elapsed = clock.slot.saturating_sub(snapshot_slot);
+    let seconds_elapsed = clock.unix_timestamp - snapshot_timestamp;
+    
+    log_section("Clock Snapshot Diff");
+    msg!("Snapshot: slot {}, timestamp {}", snapshot_slot, snapshot_timestamp);
+    msg!("Current: slot {}, timestamp {}", clock.slot, clock.unix_timestamp);
+    msg!("Slots elapsed: {}", slots_elapsed);
+    msg!("Seconds elapsed: {}", seconds_elapsed);
+    
+    if slots_elapsed > 0 {
+        msg!("Implied slot time: {} ms", seconds_elapsed * 1000 / slots_elapsed as i64);
+    } else {
+        msg!("No slots elapsed yet");
+    }
+    
+    // Return slots elapsed (u64) followed by seconds elapsed (i64), little-endian
+    let mut result = [0u8; 16];
+    result[0..8].copy_from_slice(&slots_elapsed.to_le_bytes());
+    result[8..16].copy_from_slice(&seconds_elapsed.to_le_bytes());
+    set_return_data(&result);
+    
+    Ok(())
+}
+
+// Return the program version as UTF-8 bytes
+fn get_version() -> ProgramResult {
+    msg!("Program version: {}", VERSION);
+    set_return_data(VERSION.as_bytes());
+    
+    Ok(())
+}
+
+// Log all sysvars in a compact, machine-parsable layout
+#[allow(deprecated)]
+fn dump_sysvars_compact() -> ProgramResult {
+    let clock = Clock::get()?;
+    let rent = Rent::get()?;
+    let epoch_schedule = EpochSchedule::get()?;
+    
+    log_section("Sysvars (compact)");
+    msg!("{}", format_compact("clock", &[
+        ("slot", clock.slot.to_string()),
+        ("epoch", clock.epoch.to_string()),
+        ("unix_timestamp", clock.unix_timestamp.to_string()),
+        ("epoch_start_timestamp", clock.epoch_start_timestamp.to_string()),
+        ("leader_schedule_epoch", clock.leader_schedule_epoch.to_string()),
+    ]));
+    msg!("{}", format_compact("rent", &[
+        ("lamports_per_byte_year", rent.lamports_per_byte_year.to_string()),
+        ("exemption_threshold", rent.exemption_threshold.to_string()),
+        ("burn_percent", rent.burn_percent.to_string()),
+    ]));
+    msg!("{}", format_compact("epoch_schedule", &[
+        ("slots_per_epoch", epoch_schedule.slots_per_epoch.to_string()),
+        ("leader_schedule_slot_offset", epoch_schedule.leader_schedule_slot_offset.to_string()),
+        ("warmup", epoch_schedule.warmup.to_string()),
+        ("first_normal_epoch", epoch_schedule.first_normal_epoch.to_string()),
+        ("first_normal_slot", epoch_schedule.first_normal_slot.to_string()),
+    ]));
+    
+    // Fees is deprecated and may be missing, so report availability instead of failing
+    match Fees::get() {
+        Ok(fees) => msg!("{}", format_compact("fees", &[
+            ("available", "true".to_string()),
+            ("lamports_per_signature", fees.fee_calculator.lamports_per_signature.to_string()),
+        ])),
+        Err(_) => msg!("{}", format_compact("fees", &[("available", "false".to_string())])),
+    }
+    
+    Ok(())
+}
+
+// Position of the current slot within its epoch, returned by CheckEpochBoundary
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EpochBoundaryStatus {
+    pub epoch: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub slots_until_next_epoch: u64,
+    pub in_first_window: bool,
+    pub in_last_window: bool,
+}
+
+// Classify the current slot as near the start or end of its epoch
+fn check_epoch_boundary(window: u64) -> ProgramResult {
+    let clock = Clock::get()?;
+    let epoch_schedule = EpochSchedule::get()?;
+    
+    let (epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(clock.slot);
+    let slots_in_epoch = epoch_schedule.get_slots_in_epoch(epoch);
+    // slot_index is always below slots_in_epoch, so this counts the current slot too
+    let slots_until_next_epoch = slots_in_epoch - slot_index;
+    
+    let status = EpochBoundaryStatus {
+        epoch,
+        slot_index,
+        slots_in_epoch,
+        slots_until_next_epoch,
+        in_first_window: slot_index < window,
+        in_last_window: slots_until_next_epoch <= window,
+    };
+    
+    log_section("Epoch Boundary");
+    msg!("Slot: {} (index {} of {} in epoch {})", clock.slot, slot_index, slots_in_epoch, epoch);
+    msg!("Slots until next epoch: {}", slots_until_next_epoch);
+    msg!("Within first {} slots: {}", window, status.in_first_window);
+    msg!("Within last {} slots: {}", window, status.in_last_window);
+    
+    set_return_data(&status.try_to_vec()?);
+    
+    Ok(())
+}
+
+// Components of the rent-exempt minimum balance, returned by ComputeRentBreakdown
+// base_lamports + threshold_lamports == minimum_balance
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RentBreakdown {
+    pub billable_bytes: u64,
+    pub base_lamports: u64,
+    pub threshold_lamports: u64,
+    pub minimum_balance: u64,
+}
+
+impl RentBreakdown {
+    // minimum_balance = (ACCOUNT_STORAGE_OVERHEAD + size) * lamports_per_byte_year * exemption_threshold
+    pub fn new(rent: &Rent, size: u64) -> Result<Self, ProgramError> {
+        let billable_bytes = ACCOUNT_STORAGE_OVERHEAD
+            .checked_add(size)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let base_lamports = billable_bytes
+            .checked_mul(rent.lamports_per_byte_year)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let minimum_balance = rent.minimum_balance(size as usize);
+        
+        Ok(Self {
+            billable_bytes,
+            base_lamports,
+            // whatever the multiplier adds on top of one year of rent
+            threshold_lamports: minimum_balance.saturating_sub(base_lamports),
+            minimum_balance,
+        })
+    }
+}
+
+// Show how the exemption threshold turns one year of rent into the rent-exempt minimum
+fn compute_rent_breakdown(size: u64) -> ProgramResult {
+    let rent = Rent::get()?;
+    let breakdown = RentBreakdown::new(&rent, size)?;
+    
+    log_section("Rent Breakdown");
+    msg!("Account size: {} bytes (+{} bytes storage overhead = {} billable)", size, ACCOUNT_STORAGE_OVERHEAD, breakdown.billable_bytes);
+    msg!("Base (one year): {} bytes * {} lamports/byte-year = {} lamports", breakdown.billable_bytes, rent.lamports_per_byte_year, breakdown.base_lamports);
+    msg!("Exemption threshold: {} years adds {} lamports", rent.exemption_threshold, breakdown.threshold_lamports);
+    msg!("Minimum balance: {} + {} = {} lamports", breakdown.base_lamports, breakdown.threshold_lamports, breakdown.minimum_balance);
+    
+    set_return_data(&breakdown.try_to_vec()?);
+    
+    Ok(())
+}
+
+// Log the well-known sysvar ids and return them in this order, clock first
+#[allow(deprecated)]
+fn list_sysvar_ids() -> ProgramResult {
+    let sysvar_ids = [
+        ("clock", sysvar::clock::id()),
+        ("rent", sysvar::rent::id()),
+        ("epoch_schedule", sysvar::epoch_schedule::id()),
+        ("fees", sysvar::fees::id()),
+        ("recent_blockhashes", sysvar::recent_blockhashes::id()),
+        ("slot_hashes", sysvar::slot_hashes::id()),
+        ("slot_history", sysvar::slot_history::id()),
+        ("stake_history", sysvar::stake_history::id()),
+        ("instructions", sysvar::instructions::id()),
+    ];
+    
+    log_section("Sysvar IDs");
+    let mut return_data = Vec::with_capacity(sysvar_ids.len() * 32);
+    for (name, id) in sysvar_ids.iter() {
+        msg!("{}: {}", name, id);
+        return_data.extend_from_slice(id.as_ref());
+    }
+    
+    set_return_data(&return_data);
+    
+    Ok(())
+}
+
+// Read the newest (slot, hash) entry from raw SlotHashes data.
+// Deserializing all ~512 entries is expensive, so read only the first one:
+// 8 bytes entry count, then (slot: u64, hash: [u8; 32]) entries, newest first.
+// The account is allocated at its full size, so the count, not the data length, says
+// whether any entry has been written yet.
+fn newest_slot_hash(data: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+    if data.len() < 8 + 8 + 32 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let entry_count = u64::from_le_bytes(data[0..8].try_into().unwrap());
+    if entry_count == 0 {
+        msg!("SlotHashes has no entries yet");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    Ok((slot, &data[16..48]))
+}
+
+// Mix a slot hash with a caller nonce; the same inputs always give the same seed
+fn mix_slot_hash(slot_hash: &[u8], nonce: u64) -> Hash {
+    hashv(&[slot_hash, &nonce.to_le_bytes()])
+}
+
+// Derive a per-slot pseudo-random seed from the SlotHashes sysvar
+//
+// NOT secure randomness: the slot hash is known to the leader producing the block and
+// to anyone simulating the transaction, so outcomes can be predicted or influenced.
+// This only shows how to pull per-slot entropy out of a sysvar.
+fn derive_random_seed(accounts: &[AccountInfo], nonce: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let slot_hashes_info = next_account_info(account_info_iter)?;
+    
+    // Verify account is SlotHashes sysvar account
+    if !sysvar::slot_hashes::check_id(slot_hashes_info.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let data = slot_hashes_info.try_borrow_data()?;
+    let (slot, slot_hash) = newest_slot_hash(&data)?;
+    let seed = mix_slot_hash(slot_hash, nonce);
+    
+    log_section("Random Seed");
+    msg!("Slot: {}", slot);
+    msg!("Nonce: {}", nonce);
+    msg!("Seed: {}", seed);
+    
+    set_return_data(seed.as_ref());
+    
+    Ok(())
+}
+
+// Most sizes BudgetRentForSizes accepts, keeping the per-size logs within the compute budget
+const MAX_BUDGET_SIZES: usize = 32;
+
+// Budget rent for creating several accounts at once
+fn budget_rent_for_sizes(sizes: &[u64]) -> ProgramResult {
+    if sizes.is_empty() || sizes.len() > MAX_BUDGET_SIZES {
+        msg!("Error: expected 1 to {} sizes, got {}", MAX_BUDGET_SIZES, sizes.len());
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    
+    let rent = Rent::get()?;
+    
+    log_section("Rent Budget");
+    let mut total: u64 = 0;
+    for (i, &size) in sizes.iter().enumerate() {
+        // RentBreakdown rejects sizes whose rent overflows u64
+        let minimum_balance = RentBreakdown::new(&rent, size)?.minimum_balance;
+        total = total
+            .checked_add(minimum_balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        msg!("Account {}: {} bytes, minimum balance {} lamports", i, size, minimum_balance);
+    }
+    msg!("Total for {} accounts: {} lamports", sizes.len(), total);
+    
+    set_return_data(&total.to_le_bytes());
+    
+    Ok(())
+}
+
+// Report the invocation stack height: 1 when called directly by a transaction,
+// one more for each CPI level above this program
+fn show_stack_height() -> ProgramResult {
+    let stack_height = get_stack_height();
+    
+    log_section("Stack Height");
+    msg!("Stack height: {}", stack_height);
+    msg!("Invoked via CPI: {}", stack_height > TRANSACTION_LEVEL_STACK_HEIGHT);
+    
+    set_return_data(&(stack_height as u64).to_le_bytes());
+    
+    Ok(())
+}
+
+// Rent for `size` bytes over `years`: size * lamports_per_byte_year * years
+fn rent_for_years(rent: &Rent, size: u64, years: u64) -> Result<u64, ProgramError> {
+    size
+        .checked_mul(rent.lamports_per_byte_year)
+        .and_then(|yearly| yearly.checked_mul(years))
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+// Prepay a chosen number of years of rent, for comparison with the exemption minimum
+fn calculate_rent_for_years(size: u64, years: u64) -> ProgramResult {
+    let rent = Rent::get()?;
+    
+    let prepay_lamports = rent_for_years(&rent, size, years)?;
+    let breakdown = RentBreakdown::new(&rent, size)?;
+    
+    log_section("Rent For Years");
+    msg!("Account size: {} bytes", size);
+    msg!("Prepay {} years: {} lamports", years, prepay_lamports);
+    msg!("Exemption minimum ({} years): {} lamports", rent.exemption_threshold, breakdown.minimum_balance);
+    
+    set_return_data(&prepay_lamports.to_le_bytes());
+    
+    Ok(())
+}
+
+// Status bytes returned by MonitorRentStatus
+pub const RENT_STATUS_EXEMPT: u8 = 0;
+pub const RENT_STATUS_NEAR_THRESHOLD: u8 = 1;
+pub const RENT_STATUS_BELOW: u8 = 2;
+
+// Flag a PDA whose balance is below, or within `warn_buffer_lamports` above, its exemption minimum
+fn monitor_rent_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_seed: &str,
+    warn_buffer_lamports: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pda_account = next_account_info(account_info_iter)?;
+    
+    let (expected_pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, account_seed.as_bytes()], program_id);
+    if expected_pda != *pda_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let rent = Rent::get()?;
+    let minimum_balance = rent.minimum_balance(pda_account.data_len());
+    let balance = pda_account.lamports();
+    
+    // At exactly minimum + buffer the account is still considered near the threshold
+    let status = if balance < minimum_balance {
+        RENT_STATUS_BELOW
+    } else if balance - minimum_balance <= warn_buffer_lamports {
+        RENT_STATUS_NEAR_THRESHOLD
+    } else {
+        RENT_STATUS_EXEMPT
+    };
+    
+    log_section("Rent Status");
+    msg!("Account: {}", pda_account.key);
+    msg!("Balance: {} lamports, exemption minimum: {} lamports", balance, minimum_balance);
+    match status {
+        RENT_STATUS_BELOW => msg!(
+            "WARNING: account is {} lamports below its exemption minimum",
+            minimum_balance - balance
+        ),
+        RENT_STATUS_NEAR_THRESHOLD => msg!(
+            "WARNING: account is only {} lamports above its exemption minimum (buffer {})",
+            balance - minimum_balance,
+            warn_buffer_lamports
+        ),
+        _ => msg!("Account is {} lamports above its exemption minimum", balance - minimum_balance),
+    }
+    
+    set_return_data(&[status]);
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        entrypoint::{deserialize, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        fee_calculator::FeeCalculator,
+        instruction::Instruction,
+        program::get_return_data,
+        program_error::UNSUPPORTED_SYSVAR,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+        system_program,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        sync::Once,
+    };
+
+    thread_local! {
+        // Clock sysvar reported in the current test
+        static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+        // lamports_per_signature in the Fees sysvar, or None when the cluster lacks it
+        static FEES: Cell<Option<u64>> = const { Cell::new(None) };
+        // Invocation stack height reported to the program
+        static STACK_HEIGHT: Cell<u64> = const { Cell::new(TRANSACTION_LEVEL_STACK_HEIGHT as u64) };
+        // Bytes passed to the last set_return_data call
+        static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        // Every msg! logged in the current test
+        static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Syscall stubs backed by thread-local state, so tests running in parallel can
+    // each set their own clock and read their own return data and logs
+    struct TestStubs;
+
+    impl SyscallStubs for TestStubs {
+        fn sol_log(&self, message: &str) {
+            LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+            println!("Program log: {}", message);
+        }
+
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Clock) = CLOCK.with(|clock| clock.borrow().clone()) };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut EpochSchedule) = EpochSchedule::without_warmup() };
+            SUCCESS
+        }
+
+        #[allow(deprecated)]
+        fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let Some(lamports_per_signature) = FEES.with(Cell::get) else {
+                return UNSUPPORTED_SYSVAR;
+            };
+            unsafe { *(var_addr as *mut Fees) = Fees::new(&FeeCalculator::new(lamports_per_signature)) };
+            SUCCESS
+        }
+
+        fn sol_get_stack_height(&self) -> u64 {
+            STACK_HEIGHT.with(Cell::get)
+        }
+
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|return_data| Some((Pubkey::default(), return_data.borrow().clone())))
+        }
+
+        // Stands in for the system program's CreateAccount, the only CPI this program makes
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let Ok(SystemInstruction::CreateAccount { lamports, space, owner }) =
+                limited_deserialize(&instruction.data, 1_232)
+            else {
+                return Err(ProgramError::InvalidInstructionData);
+            };
+            let find = |index: usize| {
+                account_infos
+                    .iter()
+                    .find(|account| *account.key == instruction.accounts[index].pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)
+            };
+            let (from, to) = (find(0)?, find(1)?);
+
+            **from.try_borrow_mut_lamports()? -= lamports;
+            **to.try_borrow_mut_lamports()? += lamports;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+            Ok(())
+        }
+    }
+
+    // Installs TestStubs once per test binary and sets this test's Clock
+    fn set_clock(clock: Clock) {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+        CLOCK.with(|current| *current.borrow_mut() = clock);
+    }
+
+    fn warp_to(unix_timestamp: UnixTimestamp) {
+        set_clock(Clock { unix_timestamp, ..Clock::default() });
+    }
+
+    fn take_logs() -> Vec<String> {
+        LOGS.with(|logs| logs.take())
+    }
+
+    fn return_data() -> Vec<u8> {
+        get_return_data().map(|(_, data)| data).unwrap_or_default()
+    }
+
+    fn process(program_id: &Pubkey, accounts: &[AccountInfo], instruction: SysvarInstruction) -> ProgramResult {
+        process_instruction(program_id, accounts, &instruction.try_to_vec().unwrap())
+    }
+
+    // Lays accounts out the way the runtime serializes program input, so the
+    // AccountInfos from entrypoint::deserialize can be resized and reassigned.
+    // Each account is (key, is_signer, lamports, data, owner), all writable.
+    fn serialize_input(accounts: &[(Pubkey, bool, u64, Vec<u8>, Pubkey)]) -> Vec<u64> {
+        let mut input = (accounts.len() as u64).to_le_bytes().to_vec();
+        for (key, is_signer, lamports, data, owner) in accounts {
+            input.extend_from_slice(&[NON_DUP_MARKER, *is_signer as u8, 1, 0]);
+            input.extend_from_slice(&[0; 4]); // original data length, set by deserialize
+            input.extend_from_slice(key.as_ref());
+            input.extend_from_slice(owner.as_ref());
+            input.extend_from_slice(&lamports.to_le_bytes());
+            input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            input.extend_from_slice(data);
+            input.resize((input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+        }
+        input.extend_from_slice(&0u64.to_le_bytes()); // no instruction data
+        input.extend_from_slice(Pubkey::default().as_ref()); // program id, unused
+
+        // Copy into a u64 buffer so the header fields are aligned
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        for (word, bytes) in aligned.iter_mut().zip(input.chunks(8)) {
+            let mut padded = [0u8; 8];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(padded);
+        }
+        aligned
+    }
+
+    // Payer, a not-yet-created PDA at `pda` and the system program, as create_pda_account expects
+    fn create_input(pda: &Pubkey) -> Vec<u64> {
+        serialize_input(&[
+            (Pubkey::new_unique(), true, 1_000_000_000, vec![], system_program::id()),
+            (*pda, false, 0, vec![], system_program::id()),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ])
+    }
+
+    #[test]
+    fn get_or_create_pda_creates_a_missing_account() {
+        warp_to(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"profile"], &program_id);
+        let mut input = create_input(&pda);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        process(&program_id, &accounts, SysvarInstruction::GetOrCreatePda { seed: "profile".to_string(), space: 16 }).unwrap();
+
+        assert_eq!(return_data(), vec![1]);
+        assert_eq!(accounts[1].owner, &program_id);
+        assert_eq!(accounts[1].lamports(), Rent::default().minimum_balance(16));
+        drop(accounts);
+        // The account grew, so parse the input again to see its new length
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let data = accounts[1].data.borrow();
+        assert_eq!(data.len(), 16);
+        assert_eq!(data[0..8], 1_700_000_000i64.to_le_bytes());
+    }
+
+    #[test]
+    fn get_or_create_pda_reports_an_existing_account() {
+        warp_to(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"profile"], &program_id);
+        let payer = Pubkey::new_unique();
+        let mut data = 1_600_000_000i64.to_le_bytes().to_vec();
+        data.resize(16, 0);
+        let minimum_balance = Rent::default().minimum_balance(16);
+        let mut input = serialize_input(&[
+            (payer, true, 1_000_000_000, vec![], system_program::id()),
+            (pda, false, minimum_balance, data.clone(), program_id),
+        ]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        process(&program_id, &accounts, SysvarInstruction::GetOrCreatePda { seed: "profile".to_string(), space: 16 }).unwrap();
+
+        assert_eq!(return_data(), vec![0]);
+        assert_eq!(accounts[0].lamports(), 1_000_000_000);
+        assert_eq!(accounts[1].lamports(), minimum_balance);
+        assert_eq!(*accounts[1].data.borrow(), &data[..]);
+    }
+
+    #[test]
+    fn create_pda_rejects_space_without_room_for_the_timestamp() {
+        warp_to(1_700_000_000);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"profile"], &program_id);
+
+        for space in [0, 7] {
+            let mut input = create_input(&pda);
+            let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+            assert_eq!(
+                process(&program_id, &accounts, SysvarInstruction::GetOrCreatePda { seed: "profile".to_string(), space }),
+                Err(ProgramError::InvalidArgument)
+            );
+            assert_eq!(accounts[1].lamports(), 0);
+            assert_eq!(accounts[1].owner, &system_program::id());
+        }
+    }
+
+    #[test]
+    fn exemption_boundary_lamports() {
+        let rent = Rent::default();
+        let minimum_balance = rent.minimum_balance(100);
+
+        assert_eq!(rent_exemption_for_size(&rent, 100, minimum_balance).unwrap(), (true, minimum_balance));
+        assert_eq!(rent_exemption_for_size(&rent, 100, minimum_balance - 1).unwrap(), (false, minimum_balance));
+        assert_eq!(rent_exemption_for_size(&rent, 100, u64::MAX).unwrap(), (true, minimum_balance));
+        assert!(rent_exemption_for_size(&rent, 0, rent.minimum_balance(0)).unwrap().0);
+    }
+
+    #[test]
+    fn exemption_check_rejects_overflowing_sizes() {
+        let rent = Rent::default();
+
+        assert_eq!(
+            rent_exemption_for_size(&rent, u64::MAX, u64::MAX),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+        assert_eq!(
+            rent_exemption_for_size(&rent, u64::MAX / rent.lamports_per_byte_year, 0),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn rent_breakdown_components_sum_to_minimum_balance() {
+        let custom = Rent {
+            lamports_per_byte_year: 1_000,
+            exemption_threshold: 3.0,
+            burn_percent: 50,
+        };
+        for rent in [Rent::default(), custom] {
+            for size in [0, 1, 165, 10_240, 10 * 1024 * 1024] {
+                let breakdown = RentBreakdown::new(&rent, size).unwrap();
+
+                assert_eq!(breakdown.minimum_balance, rent.minimum_balance(size as usize));
+                assert_eq!(breakdown.base_lamports + breakdown.threshold_lamports, breakdown.minimum_balance);
+                assert_eq!(breakdown.billable_bytes, ACCOUNT_STORAGE_OVERHEAD + size);
+            }
+        }
+    }
+
+    #[test]
+    fn timer_counts_down_until_the_clock_passes_expiry() {
+        warp_to(1_000);
+        let program_id = Pubkey::new_unique();
+        let (timer, _) = Pubkey::find_program_address(&[TIMER_SEED_PREFIX, b"tea"], &program_id);
+        let mut input = create_input(&timer);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        process(&program_id, &accounts, SysvarInstruction::CreateTimer { seed: "tea".to_string(), duration_seconds: 60 }).unwrap();
+        drop(accounts);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let check_timer = || process(&program_id, &accounts[1..2], SysvarInstruction::CheckTimer { seed: "tea".to_string() });
+
+        warp_to(1_030);
+        take_logs();
+        check_timer().unwrap();
+        assert!(take_logs().contains(&"Remaining: 30 seconds".to_string()));
+
+        warp_to(1_059);
+        check_timer().unwrap();
+        assert!(take_logs().contains(&"Remaining: 1 seconds".to_string()));
+
+        warp_to(1_060);
+        check_timer().unwrap();
+        assert!(take_logs().contains(&"Timer expired".to_string()));
+    }
+
+    #[test]
+    fn timer_seed_does_not_match_a_plain_pda() {
+        warp_to(1_000);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"tea"], &program_id);
+        let mut input = create_input(&pda);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        process(&program_id, &accounts, SysvarInstruction::CreatePdaAccount { space: 16, seed: "tea".to_string() }).unwrap();
+        drop(accounts);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        assert_eq!(
+            process(&program_id, &accounts[1..2], SysvarInstruction::CheckTimer { seed: "tea".to_string() }),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn diff_clock_snapshot_reports_slots_and_seconds_elapsed() {
+        set_clock(Clock { slot: 100, unix_timestamp: 1_000, ..Clock::default() });
+        let program_id = Pubkey::new_unique();
+        let (snapshot, _) = Pubkey::find_program_address(&[SNAPSHOT_SEED_PREFIX, b"run"], &program_id);
+        let mut input = create_input(&snapshot);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        process(&program_id, &accounts, SysvarInstruction::CaptureClockSnapshot { seed: "run".to_string() }).unwrap();
+        drop(accounts);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        set_clock(Clock { slot: 150, unix_timestamp: 1_020, ..Clock::default() });
+        process(&program_id, &accounts[1..2], SysvarInstruction::DiffClockSnapshot { seed: "run".to_string() }).unwrap();
+
+        let result = return_data();
+        assert_eq!(result.len(), 16);
+        assert_eq!(u64::from_le_bytes(result[0..8].try_into().unwrap()), 50);
+        assert_eq!(i64::from_le_bytes(result[8..16].try_into().unwrap()), 20);
+        assert!(take_logs().contains(&"Implied slot time: 400 ms".to_string()));
+    }
+
+    #[test]
+    fn diff_clock_snapshot_rejects_a_timer() {
+        warp_to(1_000);
+        let program_id = Pubkey::new_unique();
+        let (timer, _) = Pubkey::find_program_address(&[TIMER_SEED_PREFIX, b"run"], &program_id);
+        let mut input = create_input(&timer);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        process(&program_id, &accounts, SysvarInstruction::CreateTimer { seed: "run".to_string(), duration_seconds: 60 }).unwrap();
+        drop(accounts);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        assert_eq!(
+            process(&program_id, &accounts[1..2], SysvarInstruction::DiffClockSnapshot { seed: "run".to_string() }),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn diff_clock_snapshot_rejects_a_short_account() {
+        warp_to(1_000);
+        let program_id = Pubkey::new_unique();
+        let (snapshot, _) = Pubkey::find_program_address(&[SNAPSHOT_SEED_PREFIX, b"run"], &program_id);
+        let (mut lamports, mut data) = (0, vec![0u8; 8]);
+        let accounts = [AccountInfo::new(&snapshot, false, false, &mut lamports, &mut data, &program_id, false, 0)];
+
+        assert_eq!(
+            process(&program_id, &accounts, SysvarInstruction::DiffClockSnapshot { seed: "run".to_string() }),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn compact_line_parses_back_into_its_fields() {
+        let line = format_compact("clock", &[
+            ("slot", 42.to_string()),
+            ("unix_timestamp", (-7i64).to_string()),
+            ("available", "true".to_string()),
+        ]);
+
+        let pairs: Vec<(&str, &str)> = line
+            .split(' ')
+            .map(|pair| pair.split_once('=').unwrap())
+            .collect();
+        assert_eq!(pairs, vec![("sysvar", "clock"), ("slot", "42"), ("unix_timestamp", "-7"), ("available", "true")]);
+        assert_eq!(format_compact("fees", &[]), "sysvar=fees");
+    }
+
+    #[test]
+    fn rent_status_bands_split_at_minimum_and_buffer() {
+        warp_to(0);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"watch"], &program_id);
+        let minimum_balance = Rent::default().minimum_balance(16);
+        let status = |balance: u64, warn_buffer_lamports: u64| {
+            let (mut lamports, mut data) = (balance, vec![0u8; 16]);
+            let accounts = [AccountInfo::new(&pda, false, false, &mut lamports, &mut data, &program_id, false, 0)];
+            process(&program_id, &accounts, SysvarInstruction::MonitorRentStatus {
+                account_seed: "watch".to_string(),
+                warn_buffer_lamports,
+            })
+            .unwrap();
+            return_data()
+        };
+
+        assert_eq!(status(minimum_balance - 1, 100), vec![RENT_STATUS_BELOW]);
+        assert_eq!(status(minimum_balance, 100), vec![RENT_STATUS_NEAR_THRESHOLD]);
+        assert_eq!(status(minimum_balance + 100, 100), vec![RENT_STATUS_NEAR_THRESHOLD]);
+        assert_eq!(status(minimum_balance + 101, 100), vec![RENT_STATUS_EXEMPT]);
+        // With no buffer only an exact minimum balance is flagged
+        assert_eq!(status(minimum_balance, 0), vec![RENT_STATUS_NEAR_THRESHOLD]);
+        assert_eq!(status(minimum_balance + 1, 0), vec![RENT_STATUS_EXEMPT]);
+    }
+
+    #[test]
+    fn sysvar_ids_are_concatenated_pubkeys_clock_first() {
+        warp_to(0);
+        process(&Pubkey::new_unique(), &[], SysvarInstruction::ListSysvarIds).unwrap();
+
+        let ids = return_data();
+        assert_eq!(ids.len() % 32, 0);
+        assert_eq!(ids.len() / 32, 9);
+        assert_eq!(ids[0..32], sysvar::clock::id().to_bytes());
+        assert_eq!(ids[32 * 8..], sysvar::instructions::id().to_bytes());
+    }
+
+    #[test]
+    fn verify_pda_returns_bump_and_match_flag() {
+        warp_to(0);
+        let program_id = Pubkey::new_unique();
+        let (pda, bump) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"vault"], &program_id);
+        let verify = |key: &Pubkey| {
+            let (mut lamports, mut data) = (0, vec![]);
+            let owner = system_program::id();
+            let accounts = [AccountInfo::new(key, false, false, &mut lamports, &mut data, &owner, false, 0)];
+            process(&program_id, &accounts, SysvarInstruction::VerifyPda { seed: "vault".to_string() }).unwrap();
+            return_data()
+        };
+
+        assert_eq!(verify(&pda), vec![bump, 1]);
+        assert_eq!(verify(&Pubkey::new_unique()), vec![bump, 0]);
+        // Same seed under another kind's prefix is a different address
+        let (timer, _) = Pubkey::find_program_address(&[TIMER_SEED_PREFIX, b"vault"], &program_id);
+        assert_eq!(verify(&timer), vec![bump, 0]);
+    }
+
+    #[test]
+    fn transaction_cost_uses_fees_sysvar_when_available() {
+        warp_to(0);
+        FEES.with(|fees| fees.set(Some(10_000)));
+
+        process(&Pubkey::new_unique(), &[], SysvarInstruction::EstimateTransactionCost { num_signatures: 3 }).unwrap();
+
+        assert_eq!(return_data(), 30_000u64.to_le_bytes());
+        assert!(take_logs().contains(&"Lamports per signature: 10000 (source: Fees sysvar)".to_string()));
+    }
+
+    #[test]
+    fn transaction_cost_falls_back_without_fees_sysvar() {
+        warp_to(0);
+        FEES.with(|fees| fees.set(None));
+
+        process(&Pubkey::new_unique(), &[], SysvarInstruction::EstimateTransactionCost { num_signatures: 3 }).unwrap();
+
+        assert_eq!(return_data(), (3 * DEFAULT_LAMPORTS_PER_SIGNATURE).to_le_bytes());
+        assert!(take_logs().contains(&"Lamports per signature: 5000 (source: default)".to_string()));
+        assert_eq!(
+            process(&Pubkey::new_unique(), &[], SysvarInstruction::EstimateTransactionCost { num_signatures: u64::MAX }),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn creation_time_distinguishes_stamped_zero_foreign_and_missing_accounts() {
+        warp_to(1_000 + 2 * 24 * 60 * 60);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"born"], &program_id);
+        let creation_time = |data: Vec<u8>, owner: Pubkey| {
+            let (mut lamports, mut data) = (1, data);
+            let accounts = [AccountInfo::new(&pda, false, false, &mut lamports, &mut data, &owner, false, 0)];
+            let result = process(&program_id, &accounts, SysvarInstruction::GetAccountCreationTime {
+                account_seed: "born".to_string(),
+            });
+            (result, take_logs())
+        };
+
+        let (result, logs) = creation_time(1_000i64.to_le_bytes().to_vec(), program_id);
+        assert_eq!(result, Ok(()));
+        assert!(logs.contains(&"Account age: 172800 seconds (2 days)".to_string()));
+
+        let (result, logs) = creation_time(vec![0; 16], program_id);
+        assert_eq!(result, Ok(()));
+        assert!(logs.contains(&"Creation time not recorded".to_string()));
+        assert!(!logs.iter().any(|log| log.starts_with("Account age")));
+
+        let (result, _) = creation_time(1_000i64.to_le_bytes().to_vec(), Pubkey::new_unique());
+        assert_eq!(result, Err(ProgramError::IncorrectProgramId));
+
+        let (result, _) = creation_time(vec![], system_program::id());
+        assert_eq!(result, Err(ProgramError::UninitializedAccount));
+    }
+
+    #[test]
+    fn epoch_boundary_classifies_slots_near_either_edge() {
+        let slots_per_epoch = EpochSchedule::without_warmup().slots_per_epoch;
+        let check = |slot: u64| {
+            set_clock(Clock { slot, ..Clock::default() });
+            process(&Pubkey::new_unique(), &[], SysvarInstruction::CheckEpochBoundary { window: 10 }).unwrap();
+            EpochBoundaryStatus::try_from_slice(&return_data()).unwrap()
+        };
+
+        let status = check(3 * slots_per_epoch + 9);
+        assert_eq!((status.epoch, status.slot_index, status.slots_in_epoch), (3, 9, slots_per_epoch));
+        assert!(status.in_first_window && !status.in_last_window);
+
+        let status = check(3 * slots_per_epoch + 10);
+        assert!(!status.in_first_window && !status.in_last_window);
+
+        let status = check(4 * slots_per_epoch - 11);
+        assert_eq!(status.slots_until_next_epoch, 11);
+        assert!(!status.in_first_window && !status.in_last_window);
+
+        let status = check(4 * slots_per_epoch - 10);
+        assert_eq!(status.slots_until_next_epoch, 10);
+        assert!(!status.in_first_window && status.in_last_window);
+
+        let status = check(4 * slots_per_epoch - 1);
+        assert_eq!((status.epoch, status.slots_until_next_epoch), (3, 1));
+        assert!(status.in_last_window);
+
+        let status = check(4 * slots_per_epoch);
+        assert_eq!((status.epoch, status.slot_index), (4, 0));
+        assert!(status.in_first_window && !status.in_last_window);
+    }
+
+    #[test]
+    fn create_pda_twice_reports_the_existing_account() {
+        warp_to(1_000);
+        let program_id = Pubkey::new_unique();
+        let (pda, _) = Pubkey::find_program_address(&[PDA_SEED_PREFIX, b"once"], &program_id);
+        let create = SysvarInstruction::CreatePdaAccount { space: 16, seed: "once".to_string() };
+        let mut input = create_input(&pda);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        process(&program_id, &accounts, create).unwrap();
+        drop(accounts);
+
+        // Lay the created account out again, as the runtime does between transactions
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let created: Vec<_> = accounts[..2]
+            .iter()
+            .map(|account| (*account.key, account.is_signer, account.lamports(), account.data.borrow().to_vec(), *account.owner))
+            .collect();
+        drop(accounts);
+        let mut input = serialize_input(&[
+            created[0].clone(),
+            created[1].clone(),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let payer_lamports = accounts[0].lamports();
+
+        let create = SysvarInstruction::CreatePdaAccount { space: 16, seed: "once".to_string() };
+        assert_eq!(process(&program_id, &accounts, create), Err(ProgramError::AccountAlreadyInitialized));
+        assert_eq!(accounts[0].lamports(), payer_lamports);
+        assert_eq!(accounts[1].data.borrow()[0..8], 1_000i64.to_le_bytes());
+    }
+
+    #[test]
+    fn rent_budget_totals_each_minimum_balance() {
+        warp_to(0);
+        let rent = Rent::default();
+        let sizes = vec![0, 8, 165, 10_240];
+
+        process(&Pubkey::new_unique(), &[], SysvarInstruction::BudgetRentForSizes { sizes: sizes.clone() }).unwrap();
+
+        let total: u64 = sizes.iter().map(|&size| rent.minimum_balance(size as usize)).sum();
+        assert_eq!(return_data(), total.to_le_bytes());
+    }
+
+    #[test]
+    fn rent_budget_rejects_empty_and_oversized_batches() {
+        warp_to(0);
+        let budget = |sizes: Vec<u64>| process(&Pubkey::new_unique(), &[], SysvarInstruction::BudgetRentForSizes { sizes });
+
+        assert_eq!(budget(vec![]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(budget(vec![1; MAX_BUDGET_SIZES + 1]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(budget(vec![1; MAX_BUDGET_SIZES]), Ok(()));
+        assert_eq!(budget(vec![1, u64::MAX]), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn stack_height_reports_direct_and_cpi_invocations() {
+        warp_to(0);
+        let stack_height = |height: u64| {
+            STACK_HEIGHT.with(|stack_height| stack_height.set(height));
+            process(&Pubkey::new_unique(), &[], SysvarInstruction::ShowStackHeight).unwrap();
+            (u64::from_le_bytes(return_data().try_into().unwrap()), take_logs())
+        };
+
+        let (height, logs) = stack_height(1);
+        assert_eq!(height, 1);
+        assert!(logs.contains(&"Invoked via CPI: false".to_string()));
+
+        // Called through a relay program such as solana-cpi-invoke
+        let (height, logs) = stack_height(2);
+        assert_eq!(height, 2);
+        assert!(logs.contains(&"Invoked via CPI: true".to_string()));
+    }
+
+    #[test]
+    fn calculate_rent_rejects_overflowing_sizes() {
+        warp_to(0);
+        let rate = Rent::default().lamports_per_byte_year;
+
+        assert_eq!(calculate_rent(u64::MAX), Err(ProgramError::ArithmeticOverflow));
+        // Fits on its own, but not once the storage overhead is added
+        assert_eq!(calculate_rent(u64::MAX / rate), Err(ProgramError::ArithmeticOverflow));
+        assert_eq!(calculate_rent(u64::MAX / rate - ACCOUNT_STORAGE_OVERHEAD), Ok(()));
+        assert_eq!(calculate_rent(165), Ok(()));
+    }
+
+    #[test]
+    fn rent_for_years_scales_size_by_rate_and_years() {
+        let rent = Rent::default();
+
+        for years in [1, 2, 5] {
+            assert_eq!(rent_for_years(&rent, 100, years), Ok(100 * rent.lamports_per_byte_year * years));
+        }
+        assert_eq!(rent_for_years(&rent, 100, 2), Ok(2 * rent_for_years(&rent, 100, 1).unwrap()));
+        assert_eq!(rent_for_years(&rent, 100, 0), Ok(0));
+        assert_eq!(rent_for_years(&rent, u64::MAX, 1), Err(ProgramError::ArithmeticOverflow));
+        assert_eq!(rent_for_years(&rent, 100, u64::MAX), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    fn slot_hashes_data(entries: &[(u64, [u8; 32])]) -> Vec<u8> {
+        // Full-size account, zero-filled past the written entries like the real sysvar
+        let mut data = vec![0u8; SlotHashes::size_of()];
+        data[0..8].copy_from_slice(&(entries.len() as u64).to_le_bytes());
+        for (index, (slot, hash)) in entries.iter().enumerate() {
+            let offset = 8 + index * 40;
+            data[offset..offset + 8].copy_from_slice(&slot.to_le_bytes());
+            data[offset + 8..offset + 40].copy_from_slice(hash);
+        }
+        data
+    }
+
+    #[test]
+    fn random_seed_is_deterministic_for_slot_hash_and_nonce() {
+        let slot_hash = [7u8; 32];
+
+        assert_eq!(mix_slot_hash(&slot_hash, 42), mix_slot_hash(&slot_hash, 42));
+        assert_eq!(mix_slot_hash(&slot_hash, 42), hashv(&[&slot_hash, &42u64.to_le_bytes()]));
+        assert_ne!(mix_slot_hash(&slot_hash, 42), mix_slot_hash(&slot_hash, 43));
+        assert_ne!(mix_slot_hash(&slot_hash, 42), mix_slot_hash(&[8u8; 32], 42));
+    }
+
+    #[test]
+    fn newest_slot_hash_reads_first_entry() {
+        let data = slot_hashes_data(&[(100, [1u8; 32]), (99, [2u8; 32])]);
+
+        let (slot, slot_hash) = newest_slot_hash(&data).unwrap();
+        assert_eq!(slot, 100);
+        assert_eq!(slot_hash, &[1u8; 32]);
+    }
+
+    #[test]
+    fn newest_slot_hash_rejects_empty_sysvar() {
+        let data = slot_hashes_data(&[]);
+
+        assert_eq!(newest_slot_hash(&data), Err(ProgramError::InvalidAccountData));
+        assert_eq!(newest_slot_hash(&data[..40]), Err(ProgramError::InvalidAccountData));
+    }
+}
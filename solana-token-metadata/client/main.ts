@@ -44,10 +44,12 @@ const payer = Keypair.fromSecretKey(
  */
 class TokenMetadata {
     mint: Uint8Array;    // 32-byte public key of the token mint
+    authority: Uint8Array; // 32-byte public key allowed to update the metadata
     name: string;        // Human-readable name of the token
     symbol: string;      // Short symbol/ticker for the token
     icon: string;        // URL to the token's icon image
     home: string;        // URL to the token's homepage or project website
+    checksum: number;    // CRC-32 of the string fields, changes whenever any of them does
 
     /**
      * Constructor for TokenMetadata
@@ -55,16 +57,20 @@ class TokenMetadata {
      */
     constructor(props: {
         mint: Uint8Array;
+        authority: Uint8Array;
         name: string;
         symbol: string;
         icon: string;
         home: string;
+        checksum: number;
     }) {
         this.mint = props.mint;
+        this.authority = props.authority;
         this.name = props.name;
         this.symbol = props.symbol;
         this.icon = props.icon;
         this.home = props.home;
+        this.checksum = props.checksum;
     }
 
     /**
@@ -78,10 +84,12 @@ class TokenMetadata {
                 kind: 'struct',
                 fields: [
                     ['mint', [32]],      // Fixed 32-byte array for public key
+                    ['authority', [32]], // Fixed 32-byte array for public key
                     ['name', 'string'],   // Variable-length string
                     ['symbol', 'string'], // Variable-length string
                     ['icon', 'string'],   // Variable-length string
                     ['home', 'string'],   // Variable-length string
+                    ['checksum', 'u32'],  // CRC-32 of the string fields
                 ]
            }
         ]
@@ -101,9 +109,10 @@ class TokenMetadataInstructionData {
      * @param symbol - The symbol/ticker of the token
      * @param icon - URL to the token's icon image
      * @param home - URL to the token's homepage
+     * @param normalizeSymbol - Uppercase the symbol on-chain (ASCII alphanumeric only)
      * @returns Buffer containing the serialized instruction data
      */
-    static createRegisterMetadata(name: string, symbol: string, icon: string, home: string): Buffer {
+    static createRegisterMetadata(name: string, symbol: string, icon: string, home: string, normalizeSymbol = false): Buffer {
         const schema = new Map([
             [
                 Object,
@@ -115,6 +124,7 @@ class TokenMetadataInstructionData {
                         ['symbol', 'string'],   // Token symbol
                         ['icon', 'string'],     // Icon URL
                         ['home', 'string'],     // Homepage URL
+                        ['normalize_symbol', 'u8'], // bool: 1 to uppercase the symbol
                     ]
                 }
             ]
@@ -126,6 +136,7 @@ class TokenMetadataInstructionData {
             symbol,
             icon,
             home,
+            normalize_symbol: normalizeSymbol ? 1 : 0,
         });
 
         return Buffer.from(data);
@@ -138,9 +149,10 @@ class TokenMetadataInstructionData {
      * @param symbol - The new symbol/ticker of the token
      * @param icon - New URL to the token's icon image
      * @param home - New URL to the token's homepage
+     * @param normalizeSymbol - Uppercase the symbol on-chain (ASCII alphanumeric only)
      * @returns Buffer containing the serialized instruction data
      */
-    static createUpdateMetadata(name: string, symbol: string, icon: string, home: string): Buffer {
+    static createUpdateMetadata(name: string, symbol: string, icon: string, home: string, normalizeSymbol = false): Buffer {
         const schema = new Map([
             [
                 Object,
@@ -152,6 +164,7 @@ class TokenMetadataInstructionData {
                         ['symbol', 'string'],   // New token symbol
                         ['icon', 'string'],     // New icon URL
                         ['home', 'string'],     // New homepage URL
+                        ['normalize_symbol', 'u8'], // bool: 1 to uppercase the symbol
                     ]
                 }
             ]
@@ -163,6 +176,7 @@ class TokenMetadataInstructionData {
             symbol,
             icon,
             home,
+            normalize_symbol: normalizeSymbol ? 1 : 0,
         });
 
         return Buffer.from(data);
@@ -416,6 +430,7 @@ async function readMetadata(mint: PublicKey): Promise<TokenMetadata | null> {
 
         console.log("Token Metadata:");
         console.log(`  Mint: ${new PublicKey(metadata.mint).toBase58()}`);
+        console.log(`  Authority: ${new PublicKey(metadata.authority).toBase58()}`);
         console.log(`  Name: ${metadata.name}`);
         console.log(`  Symbol: ${metadata.symbol}`);
         console.log(`  Icon: ${metadata.icon}`);
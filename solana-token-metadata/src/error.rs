@@ -0,0 +1,32 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+/// Custom errors returned by the Token Metadata program
+#[derive(Error, Debug, Copy, Clone)]
+pub enum TokenMetadataError {
+    /// An icon or home URL is too long or contains whitespace / control characters
+    #[error("Invalid URL")]
+    InvalidUrl,
+
+    /// Symbol normalization was requested but the symbol is not ASCII alphanumeric
+    #[error("Invalid symbol")]
+    InvalidSymbol,
+
+    /// The mint account is not owned by the SPL Token program or is not sized like a Mint
+    #[error("Invalid mint")]
+    InvalidMint,
+
+    /// The signer is not the authority stored in the metadata account
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    /// A resized metadata account would hold too few lamports to stay rent exempt
+    #[error("Not rent exempt")]
+    NotRentExempt,
+}
+
+impl From<TokenMetadataError> for ProgramError {
+    fn from(e: TokenMetadataError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -1,4 +1,21 @@
 use borsh::{BorshDeserialize, BorshSerialize, BorshSchema};
+use solana_program::pubkey::Pubkey;
+
+/// Largest number of tokens a single RegisterMany instruction may register
+pub const MAX_REGISTER_MANY: usize = 5;
+
+/// Optional byte appended after any serialized instruction to log compute units
+/// around its handler
+pub const VERBOSE_FLAG: u8 = 1;
+
+/// Metadata fields for one token in a RegisterMany batch
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, BorshSchema)]
+pub struct MetadataFields {
+    pub name: String,    // Human-readable name of the token
+    pub symbol: String,  // Short symbol/ticker
+    pub icon: String,    // URL pointing to the token's icon image
+    pub home: String,    // URL pointing to the token's homepage
+}
 
 /// Instruction enum for the Token Metadata program
 ///
@@ -21,11 +38,13 @@ pub enum TokenMetadataInstruction {
     /// 2. `[]` The mint account - the SPL token mint this metadata is for
     /// 3. `[]` The SPL Token program - used for PDA derivation
     /// 4. `[]` The system program - used for account creation
+    /// 5. `[writable]` (optional) The metadata registry (PDA) - records the new metadata address
     RegisterMetadata {
         name: String,    // Human-readable name of the token
         symbol: String,  // Short symbol/ticker (e.g., "BTC", "ETH")
         icon: String,    // URL pointing to the token's icon image
         home: String,    // URL pointing to the token's homepage
+        normalize_symbol: bool, // Uppercase the symbol before storing (ASCII alphanumeric only)
     },
 
     /// Updates existing metadata for a token mint
@@ -45,7 +64,152 @@ pub enum TokenMetadataInstruction {
         symbol: String,  // New short symbol/ticker
         icon: String,    // New URL pointing to the token's icon image
         home: String,    // New URL pointing to the token's homepage
+        normalize_symbol: bool, // Uppercase the symbol before storing (ASCII alphanumeric only)
     },
-}
 
+    /// Previews a RegisterMetadata call without creating anything
+    ///
+    /// Derives the metadata PDA, computes the serialized size of the metadata and the
+    /// rent-exempt balance it would need, and returns a `RegistrationInfo` via return data.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The mint account - the SPL token mint this metadata is for
+    /// 1. `[]` The SPL Token program - used for PDA derivation
+    GetRegistrationInfo {
+        name: String,    // Human-readable name of the token
+        symbol: String,  // Short symbol/ticker
+        icon: String,    // URL pointing to the token's icon image
+        home: String,    // URL pointing to the token's homepage
+    },
 
+    /// Updates only the provided metadata fields, keeping the rest unchanged
+    ///
+    /// Each field is optional; `None` leaves the stored value as is. The account
+    /// is resized the same way as UpdateMetadata if the serialized size changes.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The authority account
+    /// 1. `[writable]` The metadata account (PDA) - existing metadata account to patch
+    /// 2. `[]` The mint account - the SPL token mint this metadata is for
+    /// 3. `[]` The SPL Token program - used for PDA derivation and validation
+    /// 4. `[]` The system program - used for account reallocation if needed
+    PatchMetadata {
+        name: Option<String>,    // New name, if changing
+        symbol: Option<String>,  // New symbol, if changing
+        icon: Option<String>,    // New icon URL, if changing
+        home: Option<String>,    // New homepage URL, if changing
+    },
+
+    /// Reports the serialized size of the given metadata
+    ///
+    /// Builds the TokenMetadata that RegisterMetadata would store and returns
+    /// `try_to_vec().len()` as a little-endian u64 via return data, so clients can
+    /// pre-fund the account accurately. The mint is always 32 bytes, so none is needed.
+    ///
+    /// Accounts expected: none
+    CheckSize {
+        name: String,    // Human-readable name of the token
+        symbol: String,  // Short symbol/ticker
+        icon: String,    // URL pointing to the token's icon image
+        home: String,    // URL pointing to the token's homepage
+    },
+
+    /// Creates the global metadata registry
+    ///
+    /// The registry is a PDA derived from `"registry"` that RegisterMetadata appends
+    /// each new metadata address to when it is passed in, keeping the latest 256.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The payer account
+    /// 1. `[writable]` The metadata registry (PDA) - will be created by this instruction
+    /// 2. `[]` The system program - used for account creation
+    InitializeRegistry,
+
+    /// Reads a page of the metadata registry
+    ///
+    /// Returns a `RegistryPage` via return data holding up to `limit` metadata
+    /// addresses (at most 31), starting `offset` entries after the oldest one.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The metadata registry (PDA)
+    ReadRegistryPage {
+        offset: u32,     // Number of entries to skip, oldest first
+        limit: u32,      // Maximum number of entries to return
+    },
+
+    /// Registers metadata for several mints in one instruction
+    ///
+    /// Works like RegisterMetadata for each entry, fetching the Rent sysvar once for
+    /// the whole batch. At most `MAX_REGISTER_MANY` entries are accepted.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The authority account (payer) - must sign the transaction
+    /// 1. `[]` The SPL Token program - used for PDA derivation
+    /// 2. `[]` The system program - used for account creation
+    /// 3. `[]` The mint account for the first entry
+    /// 4. `[writable]` The metadata account (PDA) for the first entry
+    /// 5. ... further (mint, metadata account) pairs, one per entry
+    /// 6. `[writable]` (optional) The metadata registry (PDA), after the last pair - records each new metadata address
+    RegisterMany {
+        entries: Vec<MetadataFields>, // One set of fields per (mint, metadata account) pair
+    },
+
+    /// Returns the program version
+    ///
+    /// Sets the crate version string (UTF-8) as return data so clients can confirm
+    /// which build is deployed.
+    ///
+    /// Accounts expected: none
+    GetVersion,
+
+    /// Updates the provided metadata fields and hands the metadata to a new authority
+    ///
+    /// Works like PatchMetadata, then stores `new_authority`. Both changes are written
+    /// together after any resize, so the account never holds only one of them.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The current authority account
+    /// 1. `[writable]` The metadata account (PDA) - existing metadata account to update
+    /// 2. `[]` The mint account - the SPL token mint this metadata is for
+    /// 3. `[]` The SPL Token program - used for PDA derivation and validation
+    /// 4. `[]` The system program - used for account reallocation if needed
+    UpdateAndTransfer {
+        name: Option<String>,    // New name, if changing
+        symbol: Option<String>,  // New symbol, if changing
+        icon: Option<String>,    // New icon URL, if changing
+        home: Option<String>,    // New homepage URL, if changing
+        new_authority: Pubkey,   // Authority allowed to update the metadata afterwards
+    },
+
+    /// Finds the first of the passed metadata accounts with the given symbol
+    ///
+    /// Returns the matching metadata account's address (32 bytes) via return data,
+    /// or empty return data if none of the accounts match.
+    ///
+    /// Accounts expected:
+    /// 0. ... `[]` The metadata accounts (PDAs) to search, in order
+    FindBySymbol {
+        symbol: String,  // Symbol to match exactly
+    },
+
+    /// Logs each seed used to derive the metadata PDA for a mint, plus the resulting
+    /// address and bump
+    ///
+    /// A debugging aid that shows exactly what goes into `find_program_address`.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The SPL Token program - the second seed
+    DebugDerivePda {
+        mint: Pubkey,  // Mint whose metadata PDA to derive
+    },
+
+    /// Reports how much of a metadata account's data the stored metadata uses
+    ///
+    /// Returns a `MetadataAccountInfo` (account data length, serialized metadata size
+    /// and the unused slack between them) via return data, so clients can tell whether
+    /// an update will need a resize.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The metadata account (PDA)
+    GetMetadataAccountInfo,
+}
@@ -5,9 +5,14 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+pub mod error;
 pub mod processor;
 pub mod instruction;
 pub mod state;
+pub mod utils;
+
+/// Crate version, returned by the GetVersion instruction
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 entrypoint!(process_instruction);
 
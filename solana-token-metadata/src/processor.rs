@@ -2,18 +2,23 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    log::sol_log_compute_units,
     msg,
-    program::invoke_signed,
+    program::{invoke_signed, set_return_data},
     program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction,
     sysvar::Sysvar
 };
+use solana_rent_utils::assert_rent_exempt;
 
 use crate::{
-    instruction::TokenMetadataInstruction,
-    state::TokenMetadata,
+    error::TokenMetadataError,
+    instruction::{MetadataFields, TokenMetadataInstruction, MAX_REGISTER_MANY, VERBOSE_FLAG},
+    state::{MetadataAccountInfo, MetadataRegistry, RegistrationInfo, RegistryPage, TokenMetadata},
+    utils::{adjust_rent_for_resize, is_safe_url, metadata_address, normalize_symbol, METADATA_SEED},
+    VERSION,
 };
 
 /// Main processor for handling token metadata instructions
@@ -25,7 +30,8 @@ impl Processor {
     /// # Arguments
     /// * `program_id` - The program ID of this token metadata program
     /// * `accounts` - Array of account infos required for the instruction
-    /// * `instruction_data` - Serialized instruction data containing the instruction type and parameters
+    /// * `instruction_data` - Serialized instruction data containing the instruction type and parameters,
+    ///   optionally followed by `VERBOSE_FLAG` to log compute units around the handler
     ///
     /// # Returns
     /// * `ProgramResult` - Success or error result of the instruction processing
@@ -35,17 +41,95 @@ impl Processor {
         instruction_data: &[u8],
     ) -> ProgramResult {
         // Deserialize the instruction data to determine which operation to perform
-        let instruction = TokenMetadataInstruction::try_from_slice(instruction_data)?;
+        let mut remaining = instruction_data;
+        let instruction = TokenMetadataInstruction::deserialize(&mut remaining)?;
 
+        // Anything left over must be the verbose flag; other trailing bytes are rejected
+        // just as try_from_slice would
+        let verbose = match remaining {
+            [] => false,
+            [VERBOSE_FLAG] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        if !verbose {
+            return Self::process_instruction(program_id, accounts, instruction);
+        }
+
+        // The difference between the two logged values is the handler's cost
+        sol_log_compute_units();
+        let result = Self::process_instruction(program_id, accounts, instruction);
+        sol_log_compute_units();
+        result
+    }
+
+    /// Routes a deserialized instruction to its handler
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos required for the instruction
+    /// * `instruction` - The deserialized instruction
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the instruction handler
+    fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction: TokenMetadataInstruction,
+    ) -> ProgramResult {
         // Route to the appropriate instruction handler based on the instruction type
         match instruction {
-            TokenMetadataInstruction::RegisterMetadata { name, symbol, icon, home } => {
+            TokenMetadataInstruction::RegisterMetadata { name, symbol, icon, home, normalize_symbol } => {
+                let symbol = Self::prepare_symbol(symbol, normalize_symbol)?;
                 Self::process_register_metadata(program_id, accounts, name, symbol, icon, home)
             }
 
-            TokenMetadataInstruction::UpdateMetadata { name, symbol, icon, home } => {
+            TokenMetadataInstruction::UpdateMetadata { name, symbol, icon, home, normalize_symbol } => {
+                let symbol = Self::prepare_symbol(symbol, normalize_symbol)?;
                 Self::process_update_metadata(program_id, accounts, name, symbol, icon, home)
             }
+
+            TokenMetadataInstruction::PatchMetadata { name, symbol, icon, home } => {
+                Self::process_patch_metadata(program_id, accounts, name, symbol, icon, home, None)
+            }
+
+            TokenMetadataInstruction::GetRegistrationInfo { name, symbol, icon, home } => {
+                Self::process_get_registration_info(program_id, accounts, name, symbol, icon, home)
+            }
+
+            TokenMetadataInstruction::CheckSize { name, symbol, icon, home } => {
+                Self::process_check_size(name, symbol, icon, home)
+            }
+
+            TokenMetadataInstruction::InitializeRegistry => {
+                Self::process_initialize_registry(program_id, accounts)
+            }
+
+            TokenMetadataInstruction::ReadRegistryPage { offset, limit } => {
+                Self::process_read_registry_page(program_id, accounts, offset, limit)
+            }
+
+            TokenMetadataInstruction::RegisterMany { entries } => {
+                Self::process_register_many(program_id, accounts, entries)
+            }
+
+            TokenMetadataInstruction::GetVersion => Self::process_get_version(),
+
+            TokenMetadataInstruction::UpdateAndTransfer { name, symbol, icon, home, new_authority } => {
+                Self::process_patch_metadata(program_id, accounts, name, symbol, icon, home, Some(new_authority))
+            }
+
+            TokenMetadataInstruction::FindBySymbol { symbol } => {
+                Self::process_find_by_symbol(program_id, accounts, symbol)
+            }
+
+            TokenMetadataInstruction::DebugDerivePda { mint } => {
+                Self::process_debug_derive_pda(program_id, accounts, mint)
+            }
+
+            TokenMetadataInstruction::GetMetadataAccountInfo => {
+                Self::process_get_metadata_account_info(program_id, accounts)
+            }
         }
     }
 
@@ -59,6 +143,7 @@ impl Processor {
     ///   - [2] mint_account_info: [] The mint account
     ///   - [3] spl_token_program_info: [] The SPL Token program
     ///   - [4] system_program_info: [] The system program
+    ///   - [5] registry_info: [writable] (optional) The metadata registry (PDA)
     /// * `name` - The name of the token
     /// * `symbol` - The symbol of the token
     /// * `icon` - The icon URL of the token
@@ -86,60 +171,123 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
     
-        let (expected_metadata_key, bump_seed) = Pubkey::find_program_address(
-            &[
-                b"metadata",
-                spl_token_program_info.key.as_ref(),
-                mint_account_info.key.as_ref(),
-            ],
-            program_id,
-        );
-    
-        if expected_metadata_key != *metadata_account_info.key {
-            msg!("Metadata account does not match the derived address");
-            return Err(ProgramError::InvalidArgument);
-        }
+        // Reject URLs that are too long or contain whitespace / control characters
+        Self::check_url(&icon)?;
+        Self::check_url(&home)?;
     
-        let token_metadata = TokenMetadata {
-            mint: *mint_account_info.key,
+        let token_metadata = TokenMetadata::new(
+            *mint_account_info.key,
+            *authority_info.key,
             name,
             symbol,
             icon,
             home,
-        };
-    
-        let metadata_serialized_size = token_metadata.try_to_vec()?.len();
+        );
     
         let rent = Rent::get()?;
-        let rent_lamports = rent.minimum_balance(metadata_serialized_size);
-    
-        invoke_signed(
-            &system_instruction::create_account(
-                authority_info.key,
-                metadata_account_info.key,
-                rent_lamports,
-                metadata_serialized_size as u64,
-                program_id,
-            ),
-            &[
-                authority_info.clone(),
-                metadata_account_info.clone(),
-                system_program_info.clone(),
-            ],
-            &[&[
-                b"metadata",
-                spl_token_program_info.key.as_ref(),
-                mint_account_info.key.as_ref(),
-                &[bump_seed],
-            ]],
+        Self::create_metadata_account(
+            program_id,
+            authority_info,
+            metadata_account_info,
+            mint_account_info,
+            spl_token_program_info,
+            system_program_info,
+            &token_metadata,
+            &rent,
         )?;
     
-        token_metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+        // Record the new metadata address in the registry when one is supplied
+        if let Some(registry_info) = account_info_iter.next() {
+            let registry = Self::record_in_registry(program_id, registry_info, &[*metadata_account_info.key])?;
+    
+            msg!("Metadata registered as entry {} in the registry", registry.total_registered);
+        }
     
         msg!("Metadata account created successfully");
         Ok(())
     }
     
+    /// Processes the RegisterMany instruction to create several metadata accounts at once
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] authority_info: [signer] The authority account (payer)
+    ///   - [1] spl_token_program_info: [] The SPL Token program
+    ///   - [2] system_program_info: [] The system program
+    ///   - then for each entry: [] mint account, [writable] metadata account (PDA)
+    ///   - then [writable] (optional) The metadata registry (PDA)
+    /// * `entries` - The metadata fields for each mint, in the same order as the account pairs
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the batch registration
+    fn process_register_many(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        entries: Vec<MetadataFields>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;           // [0] Authority (payer)
+        let spl_token_program_info = next_account_info(account_info_iter)?;   // [1] SPL Token program
+        let system_program_info = next_account_info(account_info_iter)?;      // [2] System program
+
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Keep the batch small enough to stay within the compute budget
+        if entries.is_empty() || entries.len() > MAX_REGISTER_MANY {
+            msg!("RegisterMany accepts 1 to {} entries, got {}", MAX_REGISTER_MANY, entries.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Fetch rent once for the whole batch
+        let rent = Rent::get()?;
+        let mut metadata_addresses = Vec::with_capacity(entries.len());
+
+        for (index, entry) in entries.into_iter().enumerate() {
+            let mint_account_info = next_account_info(account_info_iter)?;     // Mint account
+            let metadata_account_info = next_account_info(account_info_iter)?; // Metadata PDA
+
+            // Reject URLs that are too long or contain whitespace / control characters
+            Self::check_url(&entry.icon)?;
+            Self::check_url(&entry.home)?;
+
+            let token_metadata = TokenMetadata::new(
+                *mint_account_info.key,
+                *authority_info.key,
+                entry.name,
+                entry.symbol,
+                entry.icon,
+                entry.home,
+            );
+
+            Self::create_metadata_account(
+                program_id,
+                authority_info,
+                metadata_account_info,
+                mint_account_info,
+                spl_token_program_info,
+                system_program_info,
+                &token_metadata,
+                &rent,
+            )?;
+
+            msg!("Metadata {} created for mint {}", index, mint_account_info.key);
+            metadata_addresses.push(*metadata_account_info.key);
+        }
+
+        // Record the whole batch in the registry when one follows the last pair
+        if let Some(registry_info) = account_info_iter.next() {
+            let registry = Self::record_in_registry(program_id, registry_info, &metadata_addresses)?;
+
+            msg!("Batch registered up to entry {} in the registry", registry.total_registered);
+        }
+
+        msg!("Batch registration completed successfully");
+        Ok(())
+    }
+
     /// Processes the UpdateMetadata instruction to update existing metadata for a token
     /// This function handles account resizing if the new metadata requires different storage space
     ///
@@ -179,15 +327,12 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        // Reject URLs that are too long or contain whitespace / control characters
+        Self::check_url(&icon)?;
+        Self::check_url(&home)?;
+
         // Derive the expected metadata account address and verify it matches
-        let (expected_metadata_key, _bump_seed) = Pubkey::find_program_address(
-            &[
-                b"metadata",
-                spl_token_program_info.key.as_ref(),
-                mint_account_info.key.as_ref(),
-            ],
-            program_id,
-        );
+        let (expected_metadata_key, _bump_seed) = metadata_address(program_id, spl_token_program_info.key, mint_account_info.key);
 
         if expected_metadata_key != *metadata_account_info.key {
             msg!("Metadata account does not match the derived address");
@@ -199,78 +344,1334 @@ impl Processor {
             return Err(ProgramError::IncorrectProgramId);
         }
 
+        // Only the stored authority may replace the metadata
+        let current_metadata = TokenMetadata::try_from_slice(&metadata_account_info.data.borrow())?;
+        Self::check_authority(&current_metadata, authority_info)?;
+
         // Create the new metadata structure
-        let new_token_metadata = TokenMetadata {
-            mint: *mint_account_info.key,
+        let new_token_metadata = TokenMetadata::new(
+            *mint_account_info.key,
+            current_metadata.authority,
             name,
             symbol,
             icon,
             home,
-        };
+        );
 
-        // Calculate the required size for the new metadata
-        let new_metadata_size = new_token_metadata.try_to_vec()?.len();
-        let current_account_size = metadata_account_info.data_len();
+        // Resize if needed and write the new metadata
+        Self::store_metadata(
+            metadata_account_info,
+            authority_info,
+            system_program_info,
+            &new_token_metadata,
+        )?;
 
-        // Handle account resizing if needed
-        if new_metadata_size != current_account_size {
-            msg!("Resizing metadata account from {} to {} bytes", current_account_size, new_metadata_size);
-
-            // Calculate the new rent requirement
-            let rent = Rent::get()?;
-            let new_rent_lamports = rent.minimum_balance(new_metadata_size);
-            let current_lamports = metadata_account_info.lamports();
-
-            // Handle lamport adjustments based on size change
-            if new_rent_lamports > current_lamports {
-                // Account is growing - need to add more lamports
-                let lamports_diff = new_rent_lamports - current_lamports;
-
-                // Transfer additional lamports from authority to metadata account
-                let transfer_instruction = system_instruction::transfer(
-                    authority_info.key,
-                    metadata_account_info.key,
-                    lamports_diff,
-                );
-
-                solana_program::program::invoke(
-                    &transfer_instruction,
-                    &[
-                        authority_info.clone(),
-                        metadata_account_info.clone(),
-                        system_program_info.clone(),
-                    ],
-                )?;
-
-                msg!("Transferred {} lamports for account expansion", lamports_diff);
-            } else if new_rent_lamports < current_lamports {
-                // Account is shrinking - return excess lamports to authority
-                let lamports_diff = current_lamports - new_rent_lamports;
-
-                // Safely transfer excess lamports back to authority
-                **metadata_account_info.try_borrow_mut_lamports()? -= lamports_diff;
-                **authority_info.try_borrow_mut_lamports()? += lamports_diff;
-
-                msg!("Returned {} excess lamports to authority", lamports_diff);
-            }
+        msg!("Token metadata updated successfully");
+        Ok(())
+    }
+
+    /// Processes the PatchMetadata and UpdateAndTransfer instructions to update only the provided fields
+    /// Fields passed as `None` keep their current on-chain value
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] authority_info: [signer] The authority account
+    ///   - [1] metadata_account_info: [writable] The metadata account (PDA)
+    ///   - [2] mint_account_info: [] The mint account
+    ///   - [3] spl_token_program_info: [] The SPL Token program
+    ///   - [4] system_program_info: [] The system program (required for reallocation)
+    /// * `name` - The new name of the token, if changing
+    /// * `symbol` - The new symbol of the token, if changing
+    /// * `icon` - The new icon URL of the token, if changing
+    /// * `home` - The new home URL of the token, if changing
+    /// * `new_authority` - The authority to hand the metadata to, if transferring
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the metadata patch
+    fn process_patch_metadata(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        name: Option<String>,
+        symbol: Option<String>,
+        icon: Option<String>,
+        home: Option<String>,
+        new_authority: Option<Pubkey>,
+    ) -> ProgramResult {
+        // Parse the accounts in the expected order
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;           // [0] Authority (must be signer)
+        let metadata_account_info = next_account_info(account_info_iter)?;    // [1] Metadata PDA
+        let mint_account_info = next_account_info(account_info_iter)?;        // [2] Mint account
+        let spl_token_program_info = next_account_info(account_info_iter)?;   // [3] SPL Token program
+        let system_program_info = next_account_info(account_info_iter)?;      // [4] System program
 
-            // Reallocate the account to the exact new size
-            metadata_account_info.realloc(new_metadata_size, false)?;
+        // Verify that the authority is a signer
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Clear the account data to ensure no leftover bytes
-        {
-            let mut data = metadata_account_info.data.borrow_mut();
-            for byte in data.iter_mut() {
-                *byte = 0;
-            }
+        // Validate only the URLs that are being changed
+        if let Some(icon) = &icon {
+            Self::check_url(icon)?;
+        }
+        if let Some(home) = &home {
+            Self::check_url(home)?;
         }
 
-        // Serialize the new metadata into the clean account
-        new_token_metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+        // Derive the expected metadata account address and verify it matches
+        let (expected_metadata_key, _bump_seed) = metadata_address(program_id, spl_token_program_info.key, mint_account_info.key);
 
-        msg!("Token metadata updated successfully");
+        if expected_metadata_key != *metadata_account_info.key {
+            msg!("Metadata account does not match the derived address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Verify that the metadata account is owned by this program
+        if metadata_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Load the current metadata and apply only the provided fields
+        let mut token_metadata = TokenMetadata::try_from_slice(&metadata_account_info.data.borrow())?;
+        Self::check_authority(&token_metadata, authority_info)?;
+
+        if let Some(name) = name {
+            token_metadata.name = name;
+        }
+        if let Some(symbol) = symbol {
+            token_metadata.symbol = symbol;
+        }
+        if let Some(icon) = icon {
+            token_metadata.icon = icon;
+        }
+        if let Some(home) = home {
+            token_metadata.home = home;
+        }
+        // Set the new authority on the in-memory copy so it is written in the same
+        // store as the field changes, after any resize
+        if let Some(new_authority) = new_authority {
+            msg!("Transferring metadata authority to {}", new_authority);
+            token_metadata.authority = new_authority;
+        }
+        token_metadata.update_checksum();
+
+        // Resize if needed and write the patched metadata
+        Self::store_metadata(
+            metadata_account_info,
+            authority_info,
+            system_program_info,
+            &token_metadata,
+        )?;
+
+        msg!("Token metadata patched successfully");
         Ok(())
     }
-}
 
+    /// Processes the GetRegistrationInfo instruction to preview a registration
+    /// Nothing is created; the result is returned via set_return_data
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] mint_account_info: [] The mint account
+    ///   - [1] spl_token_program_info: [] The SPL Token program
+    /// * `name` - The name of the token
+    /// * `symbol` - The symbol of the token
+    /// * `icon` - The icon URL of the token
+    /// * `home` - The home URL of the token
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the simulation
+    fn process_get_registration_info(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        name: String,
+        symbol: String,
+        icon: String,
+        home: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_account_info = next_account_info(account_info_iter)?;        // [0] Mint account
+        let spl_token_program_info = next_account_info(account_info_iter)?;   // [1] SPL Token program
+
+        let (metadata_address, _bump_seed) = metadata_address(program_id, spl_token_program_info.key, mint_account_info.key);
+
+        // Build the same struct the register path would store so the size matches exactly
+        // The authority doesn't affect the size, so the default key stands in for it
+        let token_metadata = TokenMetadata::new(
+            *mint_account_info.key,
+            Pubkey::default(),
+            name,
+            symbol,
+            icon,
+            home,
+        );
+
+        let (size, required_lamports) = Self::metadata_account_size(&token_metadata, &Rent::get()?)?;
+
+        let info = RegistrationInfo {
+            metadata_address,
+            required_lamports,
+            size: size as u64,
+        };
+
+        msg!("Metadata address: {}", info.metadata_address);
+        msg!("Required size: {} bytes, rent: {} lamports", info.size, info.required_lamports);
+
+        set_return_data(&info.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Processes the CheckSize instruction, returning the serialized metadata size
+    ///
+    /// # Arguments
+    /// * `name` - The name of the token
+    /// * `symbol` - The symbol of the token
+    /// * `icon` - The icon URL of the token
+    /// * `home` - The home URL of the token
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the size check
+    fn process_check_size(
+        name: String,
+        symbol: String,
+        icon: String,
+        home: String,
+    ) -> ProgramResult {
+        // The mint is a fixed 32 bytes, so any key gives the same size
+        let token_metadata = TokenMetadata::new(
+            Pubkey::default(),
+            Pubkey::default(),
+            name,
+            symbol,
+            icon,
+            home,
+        );
+
+        let size = token_metadata.try_to_vec()?.len() as u64;
+        msg!("Serialized metadata size: {} bytes", size);
+
+        set_return_data(&size.to_le_bytes());
+        Ok(())
+    }
+
+    /// Processes the InitializeRegistry instruction to create the global metadata registry
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] payer_info: [signer, writable] The payer account
+    ///   - [1] registry_info: [writable] The metadata registry (PDA)
+    ///   - [2] system_program_info: [] The system program
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the registry creation
+    fn process_initialize_registry(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;             // [0] Payer
+        let registry_info = next_account_info(account_info_iter)?;          // [1] Registry PDA
+        let system_program_info = next_account_info(account_info_iter)?;    // [2] System program
+
+        if !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (expected_registry_key, bump_seed) = MetadataRegistry::find_address(program_id);
+        if expected_registry_key != *registry_info.key {
+            msg!("Registry account does not match the derived address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                registry_info.key,
+                rent.minimum_balance(MetadataRegistry::LEN),
+                MetadataRegistry::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                registry_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[MetadataRegistry::SEED, &[bump_seed]]],
+        )?;
+
+        MetadataRegistry::new().serialize(&mut *registry_info.data.borrow_mut())?;
+
+        msg!("Metadata registry created at {}", registry_info.key);
+        Ok(())
+    }
+
+    /// Processes the ReadRegistryPage instruction, returning a `RegistryPage` via return data
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] registry_info: [] The metadata registry (PDA)
+    /// * `offset` - Number of entries to skip, oldest first
+    /// * `limit` - Maximum number of entries to return, capped at `MetadataRegistry::MAX_PAGE_SIZE`
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the registry read
+    fn process_read_registry_page(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        offset: u32,
+        limit: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let registry_info = next_account_info(account_info_iter)?;          // [0] Registry PDA
+
+        let registry = Self::load_registry(program_id, registry_info)?;
+        let limit = (limit as usize).min(MetadataRegistry::MAX_PAGE_SIZE);
+
+        let page = RegistryPage {
+            total_registered: registry.total_registered,
+            entries: registry.page(offset as usize, limit),
+        };
+
+        msg!(
+            "Registry page: {} entries from offset {} ({} registered in total)",
+            page.entries.len(),
+            offset,
+            page.total_registered
+        );
+
+        set_return_data(&page.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Loads the metadata registry after checking its owner and address
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `registry_info` - The metadata registry (PDA)
+    ///
+    /// # Returns
+    /// * `Result<MetadataRegistry, ProgramError>` - The deserialized registry
+    fn load_registry(program_id: &Pubkey, registry_info: &AccountInfo) -> Result<MetadataRegistry, ProgramError> {
+        if registry_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let (expected_registry_key, _bump_seed) = MetadataRegistry::find_address(program_id);
+        if expected_registry_key != *registry_info.key {
+            msg!("Registry account does not match the derived address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The account is sized for a full registry, so ignore the unused tail
+        let registry = MetadataRegistry::deserialize(&mut &registry_info.data.borrow()[..])?;
+        Ok(registry)
+    }
+
+    /// Appends metadata addresses to the registry, oldest first, and writes it back
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `registry_info` - [writable] The metadata registry (PDA)
+    /// * `metadata_addresses` - The newly created metadata accounts, in creation order
+    ///
+    /// # Returns
+    /// * `Result<MetadataRegistry, ProgramError>` - The registry as written
+    fn record_in_registry(
+        program_id: &Pubkey,
+        registry_info: &AccountInfo,
+        metadata_addresses: &[Pubkey],
+    ) -> Result<MetadataRegistry, ProgramError> {
+        let mut registry = Self::load_registry(program_id, registry_info)?;
+        for metadata_address in metadata_addresses {
+            registry.push(*metadata_address);
+        }
+        registry.serialize(&mut &mut registry_info.data.borrow_mut()[..])?;
+        Ok(registry)
+    }
+
+    /// Processes the GetVersion instruction, returning the crate version via return data
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success result; the version string is set as return data
+    fn process_get_version() -> ProgramResult {
+        msg!("Program version: {}", VERSION);
+        set_return_data(VERSION.as_bytes());
+        Ok(())
+    }
+
+    /// Processes the FindBySymbol instruction, returning the first matching metadata address
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - The metadata accounts (PDAs) to search, in order
+    /// * `symbol` - The symbol to match exactly
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result; the matching address (or nothing) is set as return data
+    fn process_find_by_symbol(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        symbol: String,
+    ) -> ProgramResult {
+        for metadata_account_info in accounts {
+            // Only deserialize accounts this program wrote
+            if metadata_account_info.owner != program_id {
+                msg!("Account {} is not owned by this program", metadata_account_info.key);
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            let token_metadata = TokenMetadata::try_from_slice(&metadata_account_info.data.borrow())?;
+            if token_metadata.symbol == symbol {
+                msg!("Symbol {} found at {}", symbol, metadata_account_info.key);
+                set_return_data(metadata_account_info.key.as_ref());
+                return Ok(());
+            }
+        }
+
+        msg!("Symbol {} not found in {} accounts", symbol, accounts.len());
+        set_return_data(&[]);
+        Ok(())
+    }
+
+    /// Processes the DebugDerivePda instruction, logging every seed of the metadata PDA
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] spl_token_program_info: [] The SPL Token program
+    /// * `mint` - The mint whose metadata PDA to derive
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the derivation
+    fn process_debug_derive_pda(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        mint: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let spl_token_program_info = next_account_info(account_info_iter)?;   // [0] SPL Token program
+
+        let (metadata_address, bump_seed) = metadata_address(program_id, spl_token_program_info.key, &mint);
+
+        msg!("Seed 0 (literal): \"metadata\" = {:?}", METADATA_SEED);
+        msg!("Seed 1 (token program): {}", spl_token_program_info.key);
+        msg!("Seed 2 (mint): {}", mint);
+        msg!("Program id: {}", program_id);
+        msg!("Metadata address: {}", metadata_address);
+        msg!("Bump: {}", bump_seed);
+
+        Ok(())
+    }
+
+    /// Processes the GetMetadataAccountInfo instruction, returning a `MetadataAccountInfo` via return data
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `accounts` - Array of account infos in the following order:
+    ///   - [0] metadata_account_info: [] The metadata account (PDA)
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the size report
+    fn process_get_metadata_account_info(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let metadata_account_info = next_account_info(account_info_iter)?;    // [0] Metadata account
+
+        if metadata_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Read only the metadata prefix so any unused tail doesn't fail deserialization
+        let data_len = metadata_account_info.data_len();
+        let token_metadata = TokenMetadata::deserialize(&mut &metadata_account_info.data.borrow()[..])?;
+        let serialized_size = token_metadata.try_to_vec()?.len();
+
+        let info = MetadataAccountInfo {
+            data_len: data_len as u64,
+            serialized_size: serialized_size as u64,
+            slack: data_len.saturating_sub(serialized_size) as u64,
+        };
+
+        msg!("Metadata account: {}", metadata_account_info.key);
+        msg!(
+            "Data length: {} bytes, serialized metadata: {} bytes, slack: {} bytes",
+            info.data_len,
+            info.serialized_size,
+            info.slack
+        );
+
+        set_return_data(&info.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Uppercases the symbol when normalization is requested, otherwise returns it unchanged
+    ///
+    /// # Arguments
+    /// * `symbol` - The symbol from the instruction
+    /// * `normalize` - Whether to normalize the symbol
+    ///
+    /// # Returns
+    /// * `Result<String, ProgramError>` - The symbol to store, or `TokenMetadataError::InvalidSymbol`
+    fn prepare_symbol(symbol: String, normalize: bool) -> Result<String, ProgramError> {
+        if !normalize {
+            return Ok(symbol);
+        }
+
+        let normalized = normalize_symbol(&symbol).ok_or_else(|| {
+            msg!("Symbol {:?} must be ASCII alphanumeric to normalize", symbol);
+            ProgramError::from(TokenMetadataError::InvalidSymbol)
+        })?;
+
+        msg!("Normalized symbol {} -> {}", symbol, normalized);
+        Ok(normalized)
+    }
+
+    /// Rejects signers other than the authority stored in the metadata
+    ///
+    /// # Arguments
+    /// * `token_metadata` - The metadata currently stored on-chain
+    /// * `authority_info` - The signing authority account
+    ///
+    /// # Returns
+    /// * `ProgramResult` - `TokenMetadataError::Unauthorized` if the keys differ
+    fn check_authority(token_metadata: &TokenMetadata, authority_info: &AccountInfo) -> ProgramResult {
+        if token_metadata.authority != *authority_info.key {
+            msg!("Signer {} is not the metadata authority", authority_info.key);
+            return Err(TokenMetadataError::Unauthorized.into());
+        }
+        Ok(())
+    }
+
+    /// Rejects icon/home URLs that fail `is_safe_url`
+    ///
+    /// # Arguments
+    /// * `url` - The URL to validate
+    ///
+    /// # Returns
+    /// * `ProgramResult` - `TokenMetadataError::InvalidUrl` if the URL is unsafe
+    fn check_url(url: &str) -> ProgramResult {
+        if !is_safe_url(url) {
+            msg!("Invalid URL: {:?}", url);
+            return Err(TokenMetadataError::InvalidUrl.into());
+        }
+        Ok(())
+    }
+
+    /// Rejects mint accounts that are not owned by the SPL Token program or are not Mint-sized
+    ///
+    /// # Arguments
+    /// * `mint_account_info` - The account passed as the mint
+    /// * `spl_token_program_info` - The SPL Token program passed in the same instruction
+    ///
+    /// # Returns
+    /// * `ProgramResult` - `TokenMetadataError::InvalidMint` if the account is not a mint
+    #[cfg(feature = "verify-mint")]
+    fn check_mint(mint_account_info: &AccountInfo, spl_token_program_info: &AccountInfo) -> ProgramResult {
+        use solana_program::program_pack::Pack;
+
+        if mint_account_info.owner != spl_token_program_info.key
+            || mint_account_info.data_len() != spl_token::state::Mint::LEN
+        {
+            msg!("Account {} is not an SPL Token mint", mint_account_info.key);
+            return Err(TokenMetadataError::InvalidMint.into());
+        }
+        Ok(())
+    }
+
+    /// Computes the size of the account that holds `token_metadata` and its rent-exempt balance
+    ///
+    /// Shared by the create path and GetRegistrationInfo so the preview always matches
+    /// what registering would allocate.
+    ///
+    /// # Arguments
+    /// * `token_metadata` - The metadata to store
+    /// * `rent` - The current Rent sysvar
+    ///
+    /// # Returns
+    /// * `Result<(usize, u64), ProgramError>` - The account size in bytes and its rent-exempt minimum
+    fn metadata_account_size(token_metadata: &TokenMetadata, rent: &Rent) -> Result<(usize, u64), ProgramError> {
+        let size = token_metadata.try_to_vec()?.len();
+        Ok((size, rent.minimum_balance(size)))
+    }
+
+    /// Creates a metadata account (PDA) sized for `token_metadata` and writes it
+    ///
+    /// # Arguments
+    /// * `program_id` - The program ID of this token metadata program
+    /// * `authority_info` - [signer] Pays for the new account
+    /// * `metadata_account_info` - [writable] The metadata account (PDA) to create
+    /// * `mint_account_info` - The mint account, used for PDA derivation
+    /// * `spl_token_program_info` - The SPL Token program, used for PDA derivation
+    /// * `system_program_info` - The system program
+    /// * `token_metadata` - The metadata to store
+    /// * `rent` - The current Rent sysvar
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the account creation
+    #[allow(clippy::too_many_arguments)]
+    fn create_metadata_account<'a>(
+        program_id: &Pubkey,
+        authority_info: &AccountInfo<'a>,
+        metadata_account_info: &AccountInfo<'a>,
+        mint_account_info: &AccountInfo<'a>,
+        spl_token_program_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        token_metadata: &TokenMetadata,
+        rent: &Rent,
+    ) -> ProgramResult {
+        #[cfg(feature = "verify-mint")]
+        Self::check_mint(mint_account_info, spl_token_program_info)?;
+
+        let (expected_metadata_key, bump_seed) = metadata_address(program_id, spl_token_program_info.key, mint_account_info.key);
+
+        if expected_metadata_key != *metadata_account_info.key {
+            msg!("Metadata account does not match the derived address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (metadata_serialized_size, rent_lamports) = Self::metadata_account_size(token_metadata, rent)?;
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority_info.key,
+                metadata_account_info.key,
+                rent_lamports,
+                metadata_serialized_size as u64,
+                program_id,
+            ),
+            &[
+                authority_info.clone(),
+                metadata_account_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                METADATA_SEED,
+                spl_token_program_info.key.as_ref(),
+                mint_account_info.key.as_ref(),
+                &[bump_seed],
+            ]],
+        )?;
+        assert_rent_exempt(metadata_account_info, rent, TokenMetadataError::NotRentExempt)?;
+
+        // Write through a copy of the slice so the account's data_len() isn't shortened
+        token_metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    /// Writes metadata into an existing metadata account, resizing it first if the
+    /// serialized size changed
+    ///
+    /// # Arguments
+    /// * `metadata_account_info` - [writable] The metadata account (PDA)
+    /// * `authority_info` - [signer] Pays for growth and receives refunds on shrink
+    /// * `system_program_info` - The system program
+    /// * `token_metadata` - The metadata to store
+    ///
+    /// # Returns
+    /// * `ProgramResult` - Success or error result of the write
+    fn store_metadata<'a>(
+        metadata_account_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        token_metadata: &TokenMetadata,
+    ) -> ProgramResult {
+        // Calculate the required size for the new metadata
+        let serialized_metadata = token_metadata.try_to_vec()?;
+        let new_metadata_size = serialized_metadata.len();
+        let current_account_size = metadata_account_info.data_len();
+
+        // Fast path: the new bytes overwrite every existing byte, so no lamport
+        // adjustment, realloc or zero-fill is needed
+        if new_metadata_size == current_account_size {
+            metadata_account_info
+                .data
+                .borrow_mut()
+                .copy_from_slice(&serialized_metadata);
+            return Ok(());
+        }
+
+        // The size changed: adjust rent and resize the account
+        msg!("Resizing metadata account from {} to {} bytes", current_account_size, new_metadata_size);
+
+        // Top up or refund lamports so the account stays exactly rent exempt
+        let rent = Rent::get()?;
+        adjust_rent_for_resize(
+            metadata_account_info,
+            authority_info,
+            system_program_info,
+            new_metadata_size,
+            &rent,
+        )?;
+
+        // Reallocate the account to the exact new size
+        metadata_account_info.realloc(new_metadata_size, false)?;
+
+        // The refund on shrink leaves exactly the new minimum; check it anyway so a
+        // change to the rent adjustment can't silently leave the account below it
+        assert_rent_exempt(metadata_account_info, &rent, TokenMetadataError::NotRentExempt)?;
+
+        // Clear the account data to ensure no leftover bytes
+        {
+            let mut data = metadata_account_info.data.borrow_mut();
+            for byte in data.iter_mut() {
+                *byte = 0;
+            }
+        }
+
+        // Serialize the new metadata into the clean account
+        token_metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::metadata_checksum;
+    use solana_program::{
+        entrypoint::{deserialize, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        instruction::Instruction,
+        program::get_return_data,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        pubkey,
+        system_instruction::SystemInstruction,
+        system_program,
+    };
+    use std::{cell::RefCell, sync::Once};
+    #[cfg(feature = "verify-mint")]
+    use solana_program::program_pack::Pack;
+
+    thread_local! {
+        // Bytes passed to the last set_return_data call in the current test
+        static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        // Every msg! logged in the current test
+        static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // SPL Token program id and Mint account length, so mints pass check_mint
+    // whether or not the verify-mint feature pulls in spl-token
+    const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+    const MINT_LEN: usize = 82;
+
+    // Stubs for logs, the Rent sysvar, return data and the system program instructions
+    // this program invokes, with per-thread state so parallel tests don't interfere
+    struct TestStubs;
+
+    impl SyscallStubs for TestStubs {
+        fn sol_log(&self, message: &str) {
+            LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+            println!("Program log: {}", message);
+        }
+
+        fn sol_log_compute_units(&self) {
+            self.sol_log("Program consumption: 200000 units remaining");
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|return_data| Some((Pubkey::default(), return_data.borrow().clone())))
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let find = |index: usize| {
+                account_infos
+                    .iter()
+                    .find(|account| *account.key == instruction.accounts[index].pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)
+            };
+            let (from, to) = (find(0)?, find(1)?);
+
+            match limited_deserialize(&instruction.data, 1_232) {
+                Ok(SystemInstruction::Transfer { lamports }) => {
+                    **from.try_borrow_mut_lamports()? -= lamports;
+                    **to.try_borrow_mut_lamports()? += lamports;
+                }
+                Ok(SystemInstruction::CreateAccount { lamports, space, owner }) => {
+                    **from.try_borrow_mut_lamports()? -= lamports;
+                    **to.try_borrow_mut_lamports()? += lamports;
+                    to.realloc(space as usize, true)?;
+                    to.assign(&owner);
+                }
+                _ => return Err(ProgramError::InvalidInstructionData),
+            }
+            Ok(())
+        }
+    }
+
+    fn install_stubs() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+    }
+
+    fn take_logs() -> Vec<String> {
+        LOGS.with(|logs| logs.take())
+    }
+
+    fn return_data() -> Vec<u8> {
+        get_return_data().map(|(_, data)| data).unwrap_or_default()
+    }
+
+    fn process(program_id: &Pubkey, accounts: &[AccountInfo], instruction: TokenMetadataInstruction) -> ProgramResult {
+        Processor::process(program_id, accounts, &instruction.try_to_vec().unwrap())
+    }
+
+    // Lays accounts out the way the runtime serializes program input, so the
+    // AccountInfos from entrypoint::deserialize can be resized and reassigned.
+    // Each account is (key, is_signer, lamports, data, owner), all writable.
+    fn serialize_input(accounts: &[(Pubkey, bool, u64, Vec<u8>, Pubkey)]) -> Vec<u64> {
+        let mut input = (accounts.len() as u64).to_le_bytes().to_vec();
+        for (key, is_signer, lamports, data, owner) in accounts {
+            input.extend_from_slice(&[NON_DUP_MARKER, *is_signer as u8, 1, 0]);
+            input.extend_from_slice(&[0; 4]); // original data length, set by deserialize
+            input.extend_from_slice(key.as_ref());
+            input.extend_from_slice(owner.as_ref());
+            input.extend_from_slice(&lamports.to_le_bytes());
+            input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            input.extend_from_slice(data);
+            input.resize((input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+        }
+        input.extend_from_slice(&0u64.to_le_bytes()); // no instruction data
+        input.extend_from_slice(Pubkey::default().as_ref()); // program id, unused
+
+        // Copy into a u64 buffer so the header fields are aligned
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        for (word, bytes) in aligned.iter_mut().zip(input.chunks(8)) {
+            let mut padded = [0u8; 8];
+            padded[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(padded);
+        }
+        aligned
+    }
+
+    fn metadata_key(program_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let seeds: [&[u8]; 3] = [b"metadata", TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()];
+        Pubkey::find_program_address(&seeds, program_id).0
+    }
+
+    // Accounts for RegisterMetadata: a funded signing authority, the not-yet-created
+    // metadata PDA for `mint`, the mint, the SPL Token program and the system program
+    fn register_input(program_id: &Pubkey, authority: &Pubkey, mint: &Pubkey) -> Vec<u64> {
+        serialize_input(&[
+            (*authority, true, 1_000_000_000, vec![], system_program::id()),
+            (metadata_key(program_id, mint), false, 0, vec![], system_program::id()),
+            (*mint, false, 1, vec![0; MINT_LEN], TOKEN_PROGRAM_ID),
+            (TOKEN_PROGRAM_ID, false, 1, vec![], Pubkey::default()),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ])
+    }
+
+    fn register_instruction(name: &str, symbol: &str) -> TokenMetadataInstruction {
+        TokenMetadataInstruction::RegisterMetadata {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            icon: "https://example.com/icon.png".to_string(),
+            home: "https://example.com".to_string(),
+            normalize_symbol: false,
+        }
+    }
+
+    fn awesome_token(mint: Pubkey, authority: Pubkey) -> TokenMetadata {
+        TokenMetadata::new(
+            mint,
+            authority,
+            "Awesome Token".to_string(),
+            "AWE".to_string(),
+            "https://example.com/icon.png".to_string(),
+            "https://example.com".to_string(),
+        )
+    }
+
+    // Accounts for the update instructions: `signer` (signing), an existing rent-exempt
+    // metadata account holding `token_metadata`, its mint, the SPL Token program and
+    // the system program
+    fn update_input(program_id: &Pubkey, signer: &Pubkey, token_metadata: &TokenMetadata) -> Vec<u64> {
+        let data = token_metadata.try_to_vec().unwrap();
+        let lamports = Rent::default().minimum_balance(data.len());
+        serialize_input(&[
+            (*signer, true, 1_000_000_000, vec![], system_program::id()),
+            (metadata_key(program_id, &token_metadata.mint), false, lamports, data, *program_id),
+            (token_metadata.mint, false, 1, vec![0; MINT_LEN], TOKEN_PROGRAM_ID),
+            (TOKEN_PROGRAM_ID, false, 1, vec![], Pubkey::default()),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+        ])
+    }
+
+    fn stored_metadata(account: &AccountInfo) -> TokenMetadata {
+        TokenMetadata::try_from_slice(&account.data.borrow()).unwrap()
+    }
+
+    // Registry account data holding `count` fresh addresses, padded to its allocated length
+    fn registry_data(count: usize) -> Vec<u8> {
+        let mut registry = MetadataRegistry::new();
+        for _ in 0..count {
+            registry.push(Pubkey::new_unique());
+        }
+        let mut data = registry.try_to_vec().unwrap();
+        data.resize(MetadataRegistry::LEN, 0);
+        data
+    }
+
+    #[test]
+    fn read_registry_page_clamps_limit_to_max_page_size() {
+        install_stubs();
+        let program_id = Pubkey::new_unique();
+        let (registry_key, _) = MetadataRegistry::find_address(&program_id);
+        let mut lamports = 0;
+        let mut data = registry_data(MetadataRegistry::CAPACITY + 10);
+        let registry = MetadataRegistry::deserialize(&mut &data[..]).unwrap();
+        let accounts = [AccountInfo::new(&registry_key, false, false, &mut lamports, &mut data, &program_id, false, 0)];
+        let read = |offset: u32, limit: u32| {
+            process(&program_id, &accounts, TokenMetadataInstruction::ReadRegistryPage { offset, limit }).unwrap();
+            RegistryPage::try_from_slice(&return_data()).unwrap()
+        };
+
+        let page = read(0, u32::MAX);
+        assert_eq!(page.total_registered, (MetadataRegistry::CAPACITY + 10) as u64);
+        assert_eq!(page.entries, registry.page(0, MetadataRegistry::MAX_PAGE_SIZE));
+        assert!(page.try_to_vec().unwrap().len() <= 1024);
+
+        assert_eq!(read(5, 2).entries, registry.page(5, 2));
+        assert_eq!(read(MetadataRegistry::CAPACITY as u32 - 1, 31).entries.len(), 1);
+        assert_eq!(read(u32::MAX, u32::MAX).entries, vec![]);
+    }
+
+    #[test]
+    fn registration_info_matches_the_created_account() {
+        install_stubs();
+        let (program_id, authority, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut input = register_input(&program_id, &authority, &mint);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        let preview = TokenMetadataInstruction::GetRegistrationInfo {
+            name: "Awesome Token".to_string(),
+            symbol: "AWE".to_string(),
+            icon: "https://example.com/icon.png".to_string(),
+            home: "https://example.com".to_string(),
+        };
+        process(&program_id, &[accounts[2].clone(), accounts[3].clone()], preview).unwrap();
+        let info = RegistrationInfo::try_from_slice(&return_data()).unwrap();
+        process(&program_id, &accounts, register_instruction("Awesome Token", "AWE")).unwrap();
+        drop(accounts);
+
+        // The account grew, so parse the input again to see its new length
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        assert_eq!(info.metadata_address, *accounts[1].key);
+        assert_eq!(info.size, accounts[1].data_len() as u64);
+        assert_eq!(info.required_lamports, accounts[1].lamports());
+    }
+
+    #[test]
+    fn patch_icon_keeps_other_fields_and_refreshes_checksum() {
+        install_stubs();
+        let (program_id, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let original = awesome_token(Pubkey::new_unique(), authority);
+        let mut input = update_input(&program_id, &authority, &original);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        let patch = TokenMetadataInstruction::PatchMetadata {
+            name: None,
+            symbol: None,
+            icon: Some("https://cdn.example.com/new-icon.png".to_string()),
+            home: None,
+        };
+        process(&program_id, &accounts, patch).unwrap();
+        drop(accounts);
+
+        // The account grew, so parse the input again to see its new length
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let patched = stored_metadata(&accounts[1]);
+        assert_eq!(patched.icon, "https://cdn.example.com/new-icon.png");
+        assert_eq!(patched.mint, original.mint);
+        assert_eq!(patched.authority, original.authority);
+        assert_eq!(patched.name, original.name);
+        assert_eq!(patched.symbol, original.symbol);
+        assert_eq!(patched.home, original.home);
+        assert_ne!(patched.checksum, original.checksum);
+        assert_eq!(patched.checksum, metadata_checksum(&[&original.name, &original.symbol, &patched.icon, &original.home]));
+        assert_eq!(accounts[1].data_len(), patched.try_to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn update_and_transfer_renames_and_hands_over_authority() {
+        install_stubs();
+        let (program_id, old_authority, new_authority) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let original = awesome_token(Pubkey::new_unique(), old_authority);
+        let mut input = update_input(&program_id, &old_authority, &original);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        let update_and_transfer = TokenMetadataInstruction::UpdateAndTransfer {
+            name: Some("Renamed Token".to_string()),
+            symbol: None,
+            icon: None,
+            home: None,
+            new_authority,
+        };
+        process(&program_id, &accounts, update_and_transfer).unwrap();
+
+        let updated = stored_metadata(&accounts[1]);
+        assert_eq!(updated.name, "Renamed Token");
+        assert_eq!(updated.authority, new_authority);
+        assert_eq!(updated.symbol, original.symbol);
+
+        // The old authority can no longer change anything
+        let rename = |name: &str| TokenMetadataInstruction::PatchMetadata {
+            name: Some(name.to_string()),
+            symbol: None,
+            icon: None,
+            home: None,
+        };
+        assert_eq!(
+            process(&program_id, &accounts, rename("Stolen Token!")),
+            Err(TokenMetadataError::Unauthorized.into())
+        );
+        assert_eq!(stored_metadata(&accounts[1]).name, "Renamed Token");
+
+        // The new authority can
+        let mut new_authority_info = accounts[0].clone();
+        new_authority_info.key = &new_authority;
+        let mut new_accounts = accounts.clone();
+        new_accounts[0] = new_authority_info;
+        process(&program_id, &new_accounts, rename("Updated Token")).unwrap();
+        assert_eq!(stored_metadata(&accounts[1]).name, "Updated Token");
+    }
+
+    #[test]
+    fn equal_length_update_rewrites_in_place() {
+        install_stubs();
+        let (program_id, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let original = awesome_token(Pubkey::new_unique(), authority);
+        let mut input = update_input(&program_id, &authority, &original);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let data_before = accounts[1].data.borrow().to_vec();
+        let (data_len, lamports, authority_lamports) = (accounts[1].data_len(), accounts[1].lamports(), accounts[0].lamports());
+
+        // Every field keeps its length, so the serialized size is unchanged
+        let update = TokenMetadataInstruction::UpdateMetadata {
+            name: "Another Token".to_string(),
+            symbol: "ANT".to_string(),
+            icon: "https://example.org/icon.png".to_string(),
+            home: "https://example.org".to_string(),
+            normalize_symbol: false,
+        };
+        process(&program_id, &accounts, update).unwrap();
+
+        assert_ne!(*accounts[1].data.borrow(), &data_before[..]);
+        assert_eq!(accounts[1].data_len(), data_len);
+        assert_eq!(accounts[1].lamports(), lamports);
+        assert_eq!(accounts[0].lamports(), authority_lamports);
+        let updated = stored_metadata(&accounts[1]);
+        assert_eq!((updated.name.as_str(), updated.symbol.as_str()), ("Another Token", "ANT"));
+        assert_eq!(updated.icon, "https://example.org/icon.png");
+        assert_eq!(updated.home, "https://example.org");
+    }
+
+    #[test]
+    fn register_many_creates_every_account_and_records_them() {
+        install_stubs();
+        let (program_id, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut input = serialize_input(&[
+            (authority, true, 1_000_000_000, vec![], system_program::id()),
+            (TOKEN_PROGRAM_ID, false, 1, vec![], Pubkey::default()),
+            (system_program::id(), false, 1, vec![], Pubkey::default()),
+            (mints[0], false, 1, vec![0; MINT_LEN], TOKEN_PROGRAM_ID),
+            (metadata_key(&program_id, &mints[0]), false, 0, vec![], system_program::id()),
+            (mints[1], false, 1, vec![0; MINT_LEN], TOKEN_PROGRAM_ID),
+            (metadata_key(&program_id, &mints[1]), false, 0, vec![], system_program::id()),
+            (MetadataRegistry::find_address(&program_id).0, false, 1, registry_data(0), program_id),
+        ]);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let fields = |name: &str, symbol: &str| MetadataFields {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            icon: "https://example.com/icon.png".to_string(),
+            home: "https://example.com".to_string(),
+        };
+
+        let register_many = TokenMetadataInstruction::RegisterMany {
+            entries: vec![fields("First Token", "ONE"), fields("Second Token", "TWO")],
+        };
+        process(&program_id, &accounts, register_many).unwrap();
+
+        for (metadata_account, mint, name) in [(&accounts[4], mints[0], "First Token"), (&accounts[6], mints[1], "Second Token")] {
+            assert_eq!(metadata_account.owner, &program_id);
+            assert_eq!(metadata_account.lamports(), Rent::default().minimum_balance(metadata_account.data_len()));
+            let token_metadata = stored_metadata(metadata_account);
+            assert_eq!(token_metadata.mint, mint);
+            assert_eq!(token_metadata.authority, authority);
+            assert_eq!(token_metadata.name, name);
+        }
+        let registry = MetadataRegistry::deserialize(&mut &accounts[7].data.borrow()[..]).unwrap();
+        assert_eq!(registry.total_registered, 2);
+        assert_eq!(registry.page(0, 10), vec![*accounts[4].key, *accounts[6].key]);
+    }
+
+    #[test]
+    fn find_by_symbol_returns_the_first_match_or_nothing() {
+        install_stubs();
+        let program_id = Pubkey::new_unique();
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let with_symbol = |symbol: &str| {
+            let mut token_metadata = awesome_token(Pubkey::new_unique(), Pubkey::new_unique());
+            token_metadata.symbol = symbol.to_string();
+            token_metadata.try_to_vec().unwrap()
+        };
+        let (mut data0, mut data1, mut data2) = (with_symbol("ONE"), with_symbol("TWO"), with_symbol("TWO"));
+        let (mut lamports0, mut lamports1, mut lamports2) = (1, 1, 1);
+        let accounts = [
+            AccountInfo::new(&keys[0], false, false, &mut lamports0, &mut data0, &program_id, false, 0),
+            AccountInfo::new(&keys[1], false, false, &mut lamports1, &mut data1, &program_id, false, 0),
+            AccountInfo::new(&keys[2], false, false, &mut lamports2, &mut data2, &program_id, false, 0),
+        ];
+        let find = |symbol: &str| {
+            process(&program_id, &accounts, TokenMetadataInstruction::FindBySymbol { symbol: symbol.to_string() }).unwrap();
+            return_data()
+        };
+
+        assert_eq!(find("TWO"), keys[1].to_bytes());
+        assert_eq!(find("ONE"), keys[0].to_bytes());
+        assert_eq!(find("two"), Vec::<u8>::new());
+        assert_eq!(find("THREE"), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn find_by_symbol_rejects_accounts_owned_by_other_programs() {
+        install_stubs();
+        let (program_id, other_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let (mut data0, mut data1) = (
+            awesome_token(Pubkey::new_unique(), Pubkey::new_unique()).try_to_vec().unwrap(),
+            awesome_token(Pubkey::new_unique(), Pubkey::new_unique()).try_to_vec().unwrap(),
+        );
+        let (mut lamports0, mut lamports1) = (1, 1);
+        let accounts = [
+            AccountInfo::new(&keys[0], false, false, &mut lamports0, &mut data0, &other_program, false, 0),
+            AccountInfo::new(&keys[1], false, false, &mut lamports1, &mut data1, &program_id, false, 0),
+        ];
+
+        // The foreign account is rejected even though it holds the symbol being searched for
+        assert_eq!(
+            process(&program_id, &accounts, TokenMetadataInstruction::FindBySymbol { symbol: "AWE".to_string() }),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            process(&program_id, &accounts[1..], TokenMetadataInstruction::FindBySymbol { symbol: "AWE".to_string() }),
+            Ok(())
+        );
+        assert_eq!(return_data(), keys[1].to_bytes());
+    }
+
+    #[test]
+    fn debug_derive_pda_logs_the_independently_derived_address() {
+        install_stubs();
+        let (program_id, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let seeds: [&[u8]; 3] = [b"metadata", TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()];
+        let (expected, bump) = Pubkey::find_program_address(&seeds, &program_id);
+        let (mut lamports, mut data, loader) = (1, vec![], Pubkey::default());
+        let accounts = [AccountInfo::new(&TOKEN_PROGRAM_ID, false, false, &mut lamports, &mut data, &loader, true, 0)];
+        take_logs();
+
+        process(&program_id, &accounts, TokenMetadataInstruction::DebugDerivePda { mint }).unwrap();
+
+        let logs = take_logs();
+        assert!(logs.contains(&format!("Metadata address: {}", expected)));
+        assert!(logs.contains(&format!("Bump: {}", bump)));
+        assert!(logs.contains(&format!("Seed 2 (mint): {}", mint)));
+        assert_eq!(metadata_address(&program_id, &TOKEN_PROGRAM_ID, &mint), (expected, bump));
+    }
+
+    #[test]
+    fn verbose_flag_logs_compute_units_around_the_handler() {
+        install_stubs();
+        let program_id = Pubkey::new_unique();
+        let get_version = |suffix: &[u8]| {
+            let mut instruction_data = TokenMetadataInstruction::GetVersion.try_to_vec().unwrap();
+            instruction_data.extend_from_slice(suffix);
+            take_logs();
+            let result = Processor::process(&program_id, &[], &instruction_data);
+            (result, take_logs())
+        };
+        let version_log = format!("Program version: {}", VERSION);
+        let compute_units_log = "Program consumption: 200000 units remaining".to_string();
+
+        let (result, logs) = get_version(&[]);
+        assert_eq!(result, Ok(()));
+        assert_eq!(logs, vec![version_log.clone()]);
+
+        let (result, logs) = get_version(&[VERBOSE_FLAG]);
+        assert_eq!(result, Ok(()));
+        assert_eq!(logs, vec![compute_units_log.clone(), version_log, compute_units_log]);
+
+        for suffix in [&[VERBOSE_FLAG, 0][..], &[0], &[VERBOSE_FLAG + 1]] {
+            let (result, logs) = get_version(suffix);
+            assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+            assert_eq!(logs, Vec::<String>::new());
+        }
+    }
+
+    #[test]
+    fn metadata_account_info_reports_serialized_size_and_slack() {
+        install_stubs();
+        let program_id = Pubkey::new_unique();
+        let token_metadata = awesome_token(Pubkey::new_unique(), Pubkey::new_unique());
+        let serialized_size = token_metadata.try_to_vec().unwrap().len();
+        let account_info = |slack: usize, owner: &Pubkey| {
+            let key = Pubkey::new_unique();
+            let mut data = token_metadata.try_to_vec().unwrap();
+            data.resize(serialized_size + slack, 0);
+            let mut lamports = 1;
+            let accounts = [AccountInfo::new(&key, false, false, &mut lamports, &mut data, owner, false, 0)];
+            process(&program_id, &accounts, TokenMetadataInstruction::GetMetadataAccountInfo)
+                .map(|()| MetadataAccountInfo::try_from_slice(&return_data()).unwrap())
+        };
+
+        let info = account_info(40, &program_id).unwrap();
+        assert_eq!(info.serialized_size, serialized_size as u64);
+        assert_eq!(info.data_len, serialized_size as u64 + 40);
+        assert_eq!(info.slack, 40);
+
+        let info = account_info(0, &program_id).unwrap();
+        assert_eq!((info.data_len, info.serialized_size, info.slack), (serialized_size as u64, serialized_size as u64, 0));
+
+        assert_eq!(account_info(0, &Pubkey::new_unique()).unwrap_err(), ProgramError::IncorrectProgramId);
+    }
+
+    #[test]
+    fn shrinking_update_refunds_and_stays_rent_exempt() {
+        install_stubs();
+        let rent = Rent::default();
+        let (program_id, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut original = awesome_token(Pubkey::new_unique(), authority);
+        original.name = "A Token With A Much Longer Name Than It Needs".to_string();
+        original.update_checksum();
+        let original_size = original.try_to_vec().unwrap().len();
+        let mut input = update_input(&program_id, &authority, &original);
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let authority_lamports = accounts[0].lamports();
+
+        let patch = TokenMetadataInstruction::PatchMetadata {
+            name: Some("Short".to_string()),
+            symbol: None,
+            icon: None,
+            home: None,
+        };
+        process(&program_id, &accounts, patch).unwrap();
+        drop(accounts);
+
+        // The account shrank, so parse the input again to see its new length
+        let (_, accounts, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let new_size = accounts[1].data_len();
+        assert_eq!(new_size, original_size - (original.name.len() - "Short".len()));
+        assert!(rent.is_exempt(accounts[1].lamports(), new_size));
+        assert_eq!(accounts[1].lamports(), rent.minimum_balance(new_size));
+        assert_eq!(accounts[0].lamports(), authority_lamports + rent.minimum_balance(original_size) - rent.minimum_balance(new_size));
+        assert_eq!(stored_metadata(&accounts[1]).name, "Short");
+    }
+
+    #[test]
+    fn check_size_matches_the_borsh_layout() {
+        install_stubs();
+        let check_size = |name: &str, symbol: &str, icon: &str, home: &str| {
+            let instruction = TokenMetadataInstruction::CheckSize {
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                icon: icon.to_string(),
+                home: home.to_string(),
+            };
+            process(&Pubkey::new_unique(), &[], instruction).unwrap();
+            u64::from_le_bytes(return_data().try_into().unwrap())
+        };
+        // mint + authority, a u32 length prefix plus the UTF-8 bytes per string, then the u32 checksum
+        let expected = |fields: [&str; 4]| (32 + 32 + fields.iter().map(|field| 4 + field.len()).sum::<usize>() + 4) as u64;
+
+        let fields = ["Awesome Token", "AWE", "https://example.com/icon.png", "https://example.com"];
+        assert_eq!(check_size(fields[0], fields[1], fields[2], fields[3]), expected(fields));
+        assert_eq!(check_size("", "", "", ""), 32 + 32 + 4 * 4 + 4);
+        // Multi-byte characters count by their encoded length
+        let fields = ["Tökén ✓", "€", "", ""];
+        assert_eq!(check_size(fields[0], fields[1], fields[2], fields[3]), expected(fields));
+        assert_eq!(expected(fields), 32 + 32 + (4 + 11) + (4 + 3) + 4 + 4 + 4);
+    }
+
+    #[test]
+    fn metadata_account_size_is_serialized_size_and_its_rent() {
+        let rent = Rent::default();
+        let token_metadata = TokenMetadata::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            "Awesome Token".to_string(),
+            "AWE".to_string(),
+            "https://example.com/icon.png".to_string(),
+            "https://example.com".to_string(),
+        );
+
+        let (size, lamports) = Processor::metadata_account_size(&token_metadata, &rent).unwrap();
+        // 32 + 32 for the keys, 4-byte length prefixes for the strings, 4 for the checksum
+        assert_eq!(size, 32 + 32 + (4 + 13) + (4 + 3) + (4 + 28) + (4 + 19) + 4);
+        assert_eq!(lamports, rent.minimum_balance(size));
+    }
+
+    #[test]
+    #[cfg(feature = "verify-mint")]
+    fn check_mint_accepts_token_owned_mint_sized_account() {
+        let (mint_key, token_program_id) = (Pubkey::new_unique(), spl_token::id());
+        let mut mint_lamports = 0;
+        let mut mint_data = vec![0u8; spl_token::state::Mint::LEN];
+        let mint = AccountInfo::new(&mint_key, false, false, &mut mint_lamports, &mut mint_data, &token_program_id, false, 0);
+        let mut program_lamports = 0;
+        let mut program_data = vec![];
+        let token_program = AccountInfo::new(&token_program_id, false, false, &mut program_lamports, &mut program_data, &token_program_id, true, 0);
+
+        assert_eq!(Processor::check_mint(&mint, &token_program), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "verify-mint")]
+    fn check_mint_rejects_non_mint_accounts() {
+        let (mint_key, token_program_id, other_owner) = (Pubkey::new_unique(), spl_token::id(), Pubkey::new_unique());
+        let mut program_lamports = 0;
+        let mut program_data = vec![];
+        let token_program = AccountInfo::new(&token_program_id, false, false, &mut program_lamports, &mut program_data, &token_program_id, true, 0);
+
+        // mint-sized, but not owned by the token program
+        let mut lamports = 0;
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        let wrong_owner = AccountInfo::new(&mint_key, false, false, &mut lamports, &mut data, &other_owner, false, 0);
+        assert_eq!(
+            Processor::check_mint(&wrong_owner, &token_program),
+            Err(TokenMetadataError::InvalidMint.into())
+        );
+
+        // owned by the token program, but token-account sized
+        let mut lamports = 0;
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        let token_account = AccountInfo::new(&mint_key, false, false, &mut lamports, &mut data, &token_program_id, false, 0);
+        assert_eq!(
+            Processor::check_mint(&token_account, &token_program),
+            Err(TokenMetadataError::InvalidMint.into())
+        );
+    }
+}
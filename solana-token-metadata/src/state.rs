@@ -1,11 +1,174 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
+use crate::utils::metadata_checksum;
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct TokenMetadata {
     pub mint: Pubkey,
+    pub authority: Pubkey,
     pub name: String,
     pub symbol: String,
     pub icon: String,
     pub home: String,
+    /// metadata_checksum of the string fields, kept in sync on every write
+    pub checksum: u32,
+}
+
+impl TokenMetadata {
+    /// Builds metadata with its checksum filled in
+    pub fn new(mint: Pubkey, authority: Pubkey, name: String, symbol: String, icon: String, home: String) -> Self {
+        let mut token_metadata = Self {
+            mint,
+            authority,
+            name,
+            symbol,
+            icon,
+            home,
+            checksum: 0,
+        };
+        token_metadata.update_checksum();
+        token_metadata
+    }
+
+    /// Recomputes `checksum` after the string fields were changed in place
+    pub fn update_checksum(&mut self) {
+        self.checksum = metadata_checksum(&[&self.name, &self.symbol, &self.icon, &self.home]);
+    }
+}
+
+/// Result of a GetRegistrationInfo simulation, returned via return data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RegistrationInfo {
+    pub metadata_address: Pubkey,
+    pub required_lamports: u64,
+    pub size: u64,
+}
+
+/// Size breakdown of a metadata account, returned by GetMetadataAccountInfo
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MetadataAccountInfo {
+    pub data_len: u64,
+    pub serialized_size: u64,
+    /// Bytes allocated past the serialized metadata
+    pub slack: u64,
+}
+
+/// Global index of the most recently registered metadata accounts
+///
+/// Holds up to `CAPACITY` addresses; once full, each new registration overwrites
+/// the oldest entry. The account is allocated at `LEN` bytes up front, so it is read
+/// with `BorshDeserialize::deserialize` (the unused tail is left as zeros).
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MetadataRegistry {
+    pub next_index: u16,
+    pub total_registered: u64,
+    pub entries: Vec<Pubkey>,
+}
+
+impl MetadataRegistry {
+    pub const CAPACITY: usize = 256;
+    pub const SEED: &'static [u8] = b"registry";
+    // 2(next_index) + 8(total_registered) + 4 + 32 * CAPACITY(entries)
+    pub const LEN: usize = 2 + 8 + 4 + 32 * Self::CAPACITY;
+    // 8(total_registered) + 4 + 32 * n(entries) must fit in 1024 bytes of return data
+    pub const MAX_PAGE_SIZE: usize = 31;
+
+    pub fn new() -> Self {
+        Self {
+            next_index: 0,
+            total_registered: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    /// Record a metadata address, overwriting the oldest entry once the registry is full
+    pub fn push(&mut self, metadata_address: Pubkey) {
+        if self.entries.len() < Self::CAPACITY {
+            self.entries.push(metadata_address);
+        } else {
+            self.entries[self.next_index as usize] = metadata_address;
+        }
+        self.next_index = ((self.next_index as usize + 1) % Self::CAPACITY) as u16;
+        self.total_registered = self.total_registered.saturating_add(1);
+    }
+
+    /// Up to `limit` addresses starting at `offset`, counting from the oldest entry
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<Pubkey> {
+        let len = self.entries.len();
+        let start = if len < Self::CAPACITY { 0 } else { self.next_index as usize };
+        (offset..len.min(offset.saturating_add(limit)))
+            .map(|i| self.entries[(start + i) % len])
+            .collect()
+    }
+}
+
+impl Default for MetadataRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A page of the metadata registry, returned via return data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RegistryPage {
+    pub total_registered: u64,
+    pub entries: Vec<Pubkey>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    fn registry_with(keys: &[Pubkey]) -> MetadataRegistry {
+        let mut registry = MetadataRegistry::new();
+        for key in keys {
+            registry.push(*key);
+        }
+        registry
+    }
+
+    #[test]
+    fn partially_filled_registry_pages_in_push_order() {
+        let keys = keys(3);
+        let registry = registry_with(&keys);
+
+        assert_eq!(registry.total_registered, 3);
+        assert_eq!(registry.next_index, 3);
+        assert_eq!(registry.page(0, 10), keys);
+        assert_eq!(registry.page(1, 1), vec![keys[1]]);
+        assert_eq!(registry.page(2, 10), vec![keys[2]]);
+        assert_eq!(registry.page(3, 10), vec![]);
+        assert_eq!(registry.page(0, 0), vec![]);
+    }
+
+    #[test]
+    fn full_registry_overwrites_oldest_and_pages_from_it() {
+        let keys = keys(MetadataRegistry::CAPACITY + 3);
+        let registry = registry_with(&keys);
+
+        assert_eq!(registry.entries.len(), MetadataRegistry::CAPACITY);
+        assert_eq!(registry.total_registered, keys.len() as u64);
+        assert_eq!(registry.next_index, 3);
+        // The first three keys were overwritten, so the oldest left is keys[3]
+        assert_eq!(registry.page(0, 2), keys[3..5]);
+        assert_eq!(registry.page(MetadataRegistry::CAPACITY - 3, 10), keys[keys.len() - 3..]);
+        assert_eq!(registry.page(0, usize::MAX), keys[3..]);
+        assert_eq!(registry.page(usize::MAX, usize::MAX), vec![]);
+    }
+
+    #[test]
+    fn full_registry_fits_its_allocated_length() {
+        let registry = registry_with(&keys(MetadataRegistry::CAPACITY + 1));
+
+        assert_eq!(registry.try_to_vec().unwrap().len(), MetadataRegistry::LEN);
+    }
 }
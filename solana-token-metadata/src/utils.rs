@@ -0,0 +1,312 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+};
+
+/// Lamport change needed to keep an account exactly rent exempt after a resize
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RentAdjustment {
+    /// The account is short by this many lamports
+    TopUp(u64),
+    /// The account holds this many lamports more than it needs
+    Refund(u64),
+    /// The account already holds exactly the minimum balance
+    Unchanged,
+}
+
+/// Computes how an account holding `current_lamports` must be topped up or refunded
+/// to be exactly rent exempt at `new_size`
+///
+/// # Arguments
+/// * `current_lamports` - The account's balance before the resize
+/// * `new_size` - The account's data length after the resize
+/// * `rent` - The current Rent sysvar
+///
+/// # Returns
+/// * `RentAdjustment` - The top-up, refund, or no-op to apply
+pub fn rent_adjustment_for_resize(current_lamports: u64, new_size: usize, rent: &Rent) -> RentAdjustment {
+    let new_rent_lamports = rent.minimum_balance(new_size);
+
+    if new_rent_lamports > current_lamports {
+        RentAdjustment::TopUp(new_rent_lamports - current_lamports)
+    } else if new_rent_lamports < current_lamports {
+        RentAdjustment::Refund(current_lamports - new_rent_lamports)
+    } else {
+        RentAdjustment::Unchanged
+    }
+}
+
+/// Adjusts an account's lamports so it is exactly rent exempt at `new_size`
+///
+/// If the account needs more lamports, the difference is transferred from `payer`
+/// through the system program. If it holds more than required, the excess is moved
+/// back to `payer` directly (the account must be owned by this program).
+/// The caller is still responsible for reallocating the account data.
+///
+/// # Arguments
+/// * `account` - The program-owned account being resized
+/// * `payer` - [signer, writable] Funds growth and receives refunds
+/// * `system_program` - The system program, used for the top-up transfer
+/// * `new_size` - The account's data length after the resize
+/// * `rent` - The current Rent sysvar
+///
+/// # Returns
+/// * `ProgramResult` - Success or error result of the lamport adjustment
+pub fn adjust_rent_for_resize<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_size: usize,
+    rent: &Rent,
+) -> ProgramResult {
+    match rent_adjustment_for_resize(account.lamports(), new_size, rent) {
+        RentAdjustment::TopUp(lamports_diff) => {
+            // Account is growing - need to add more lamports
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, lamports_diff),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+
+            msg!("Transferred {} lamports for account expansion", lamports_diff);
+        }
+        RentAdjustment::Refund(lamports_diff) => {
+            // Account is shrinking - return excess lamports to payer
+            **account.try_borrow_mut_lamports()? -= lamports_diff;
+            **payer.try_borrow_mut_lamports()? += lamports_diff;
+
+            msg!("Returned {} excess lamports to authority", lamports_diff);
+        }
+        RentAdjustment::Unchanged => {}
+    }
+
+    Ok(())
+}
+
+/// Literal first seed of every metadata PDA
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// Derives the metadata PDA for a mint
+///
+/// The seeds are `METADATA_SEED`, the SPL Token program id and the mint, so each
+/// mint has exactly one metadata account per token program.
+///
+/// # Arguments
+/// * `program_id` - The program ID of this token metadata program
+/// * `token_program` - The SPL Token program that owns the mint
+/// * `mint` - The mint the metadata describes
+///
+/// # Returns
+/// * `(Pubkey, u8)` - The metadata account address and its bump seed
+pub fn metadata_address(program_id: &Pubkey, token_program: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[METADATA_SEED, token_program.as_ref(), mint.as_ref()], program_id)
+}
+
+/// CRC-32 (IEEE) checksum over the metadata string fields
+///
+/// Each field is fed in as its little-endian u32 length followed by its bytes, so
+/// moving bytes between fields (e.g. "ab" + "c" vs "a" + "bc") changes the result.
+/// Clients compare this against their cached copy to detect stale metadata.
+///
+/// # Arguments
+/// * `fields` - The string fields, in storage order (name, symbol, icon, home)
+///
+/// # Returns
+/// * `u32` - The checksum
+pub fn metadata_checksum(fields: &[&str]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for field in fields {
+        let len = (field.len() as u32).to_le_bytes();
+        for &byte in len.iter().chain(field.as_bytes()) {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+    !crc
+}
+
+/// Longest icon/home URL accepted by the program
+pub const MAX_URL_LEN: usize = 256;
+
+/// URL schemes accepted for icon/home URLs
+pub const ALLOWED_URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+/// Returns true if `url` is safe to store as an icon or home URL
+///
+/// Rejects URLs that don't start with one of `ALLOWED_URL_SCHEMES` (e.g. `javascript:`),
+/// URLs longer than `MAX_URL_LEN` bytes, and URLs containing whitespace or control
+/// characters (e.g. embedded spaces or newlines), which break many renderers.
+///
+/// # Arguments
+/// * `url` - The URL to check
+///
+/// # Returns
+/// * `bool` - Whether the URL passes the scheme, length and charset checks
+pub fn is_safe_url(url: &str) -> bool {
+    ALLOWED_URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+        && url.len() <= MAX_URL_LEN
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Uppercases a token symbol, e.g. "btc" -> "BTC"
+///
+/// Only ASCII letters and digits are accepted so the result is unambiguous.
+///
+/// # Arguments
+/// * `symbol` - The symbol to normalize
+///
+/// # Returns
+/// * `Option<String>` - The uppercased symbol, or `None` if it contains any other character
+pub fn normalize_symbol(symbol: &str) -> Option<String> {
+    if symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Some(symbol.to_ascii_uppercase())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_url_accepts_http_and_https() {
+        assert!(is_safe_url("https://example.com/awesome-token-icon.png"));
+        assert!(is_safe_url("http://awesome-token.example.com"));
+    }
+
+    #[test]
+    fn safe_url_rejects_other_schemes() {
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url("data:image/png;base64,AAAA"));
+        assert!(!is_safe_url("ftp://example.com/icon.png"));
+        assert!(!is_safe_url("example.com/icon.png"));
+        assert!(!is_safe_url(""));
+    }
+
+    #[test]
+    fn safe_url_rejects_whitespace_and_control_characters() {
+        assert!(!is_safe_url("https://example.com/icon\n.png"));
+        assert!(!is_safe_url("https://example.com/my icon.png"));
+        assert!(!is_safe_url("https://example.com/\ticon.png"));
+        assert!(!is_safe_url("https://example.com/\u{7}icon.png"));
+    }
+
+    #[test]
+    fn safe_url_enforces_max_length() {
+        let at_limit = format!("https://{}", "a".repeat(MAX_URL_LEN - "https://".len()));
+        assert!(is_safe_url(&at_limit));
+        assert!(!is_safe_url(&format!("{}a", at_limit)));
+    }
+
+    #[test]
+    fn normalize_symbol_uppercases_ascii_alphanumerics() {
+        assert_eq!(normalize_symbol("btc"), Some("BTC".to_string()));
+        assert_eq!(normalize_symbol("Sol2"), Some("SOL2".to_string()));
+        assert_eq!(normalize_symbol("USDC"), Some("USDC".to_string()));
+    }
+
+    #[test]
+    fn normalize_symbol_rejects_other_characters() {
+        assert_eq!(normalize_symbol("b€tc"), None);
+        assert_eq!(normalize_symbol("bt c"), None);
+        assert_eq!(normalize_symbol("btc-usd"), None);
+    }
+
+    #[test]
+    fn normalize_symbol_accepts_empty_symbol() {
+        assert_eq!(normalize_symbol(""), Some(String::new()));
+    }
+
+    #[test]
+    fn checksum_matches_crc32_of_length_prefixed_fields() {
+        // CRC-32 of no bytes, and of a single empty field's 4-byte zero length prefix
+        assert_eq!(metadata_checksum(&[]), 0);
+        assert_eq!(metadata_checksum(&[""]), 0x2144_DF1C);
+    }
+
+    #[test]
+    fn checksum_changes_when_any_field_changes() {
+        let fields = ["Awesome Token", "AWE", "https://example.com/icon.png", "https://example.com"];
+        let original = metadata_checksum(&fields);
+        assert_eq!(metadata_checksum(&fields), original);
+
+        for i in 0..fields.len() {
+            let mut changed = fields;
+            let edited = format!("{}!", fields[i]);
+            changed[i] = &edited;
+            assert_ne!(metadata_checksum(&changed), original, "field {} changed", i);
+        }
+    }
+
+    #[test]
+    fn checksum_depends_on_field_boundaries() {
+        assert_ne!(metadata_checksum(&["ab", "c"]), metadata_checksum(&["a", "bc"]));
+        assert_ne!(metadata_checksum(&["", ""]), metadata_checksum(&[""]));
+    }
+
+    #[test]
+    fn rent_adjustment_tops_up_a_growing_account() {
+        let rent = Rent::default();
+        let current = rent.minimum_balance(100);
+
+        assert_eq!(
+            rent_adjustment_for_resize(current, 200, &rent),
+            RentAdjustment::TopUp(rent.minimum_balance(200) - current)
+        );
+    }
+
+    #[test]
+    fn rent_adjustment_refunds_a_shrinking_account() {
+        let rent = Rent::default();
+        let current = rent.minimum_balance(200);
+
+        assert_eq!(
+            rent_adjustment_for_resize(current, 100, &rent),
+            RentAdjustment::Refund(current - rent.minimum_balance(100))
+        );
+    }
+
+    #[test]
+    fn rent_adjustment_is_unchanged_at_the_same_size() {
+        let rent = Rent::default();
+
+        assert_eq!(
+            rent_adjustment_for_resize(rent.minimum_balance(100), 100, &rent),
+            RentAdjustment::Unchanged
+        );
+    }
+
+    #[test]
+    fn adjust_rent_for_resize_refunds_excess_to_payer() {
+        let rent = Rent::default();
+        let (key, payer_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let system_program_id = solana_program::system_program::id();
+        let mut lamports = rent.minimum_balance(200);
+        let mut data = vec![0u8; 200];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+        let mut payer_lamports = 1_000;
+        let mut payer_data = vec![];
+        let payer = AccountInfo::new(&payer_key, true, true, &mut payer_lamports, &mut payer_data, &system_program_id, false, 0);
+        let mut system_lamports = 0;
+        let mut system_data = vec![];
+        let system_program = AccountInfo::new(&system_program_id, false, false, &mut system_lamports, &mut system_data, &system_program_id, true, 0);
+
+        let refund = rent.minimum_balance(200) - rent.minimum_balance(100);
+        assert_eq!(adjust_rent_for_resize(&account, &payer, &system_program, 100, &rent), Ok(()));
+        assert_eq!(account.lamports(), rent.minimum_balance(100));
+        assert_eq!(payer.lamports(), 1_000 + refund);
+
+        // a second call at the same size has nothing left to move
+        assert_eq!(adjust_rent_for_resize(&account, &payer, &system_program, 100, &rent), Ok(()));
+        assert_eq!(account.lamports(), rent.minimum_balance(100));
+        assert_eq!(payer.lamports(), 1_000 + refund);
+    }
+}